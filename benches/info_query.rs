@@ -0,0 +1,60 @@
+//! Concurrent `info_query` vs. sequential `info_query_streaming` of a large package set,
+//! against a mock RPC server with latency of AUR. Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Response, Server,
+};
+use std::{convert::Infallible, net::TcpListener, thread, time::Duration};
+
+use aur_thumbsup::aur::Authentication;
+
+/// Round trip of an RPC query to AUR
+const LATENCY: Duration = Duration::from_millis(50);
+
+/// Installed AUR packages of a heavy user, 5 chunks of RPC queries
+const PACKAGES: usize = 800;
+
+/// Mock RPC server answering each query after `LATENCY`, over HTTP/2 like AUR.
+/// Return its base URL.
+fn mock_rpc_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let make_service = make_service_fn(|_| async {
+                Ok::<_, Infallible>(service_fn(|_| async {
+                    tokio::time::sleep(LATENCY).await;
+                    let body = r#"{"version":5,"type":"multiinfo","resultcount":0,"results":[]}"#;
+                    Ok::<_, Infallible>(Response::new(Body::from(body)))
+                }))
+            });
+            Server::from_tcp(listener)
+                .unwrap()
+                .http2_only(true)
+                .serve(make_service)
+                .await
+                .unwrap();
+        });
+    });
+    url
+}
+
+fn info_query(c: &mut Criterion) {
+    let mut auth = Authentication::new();
+    auth.set_aur_url(&mock_rpc_server()).unwrap();
+    let pkgs: Vec<String> = (0..PACKAGES).map(|i| format!("pkg-{}", i)).collect();
+
+    let mut group = c.benchmark_group("info_query");
+    group.sample_size(10);
+    group.bench_function("concurrent", |b| b.iter(|| auth.info_query(&pkgs).unwrap()));
+    group.bench_function("sequential", |b| {
+        b.iter(|| auth.info_query_streaming(&pkgs, |_| Ok(())).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, info_query);
+criterion_main!(benches);
@@ -1,6 +1,12 @@
-use clap::{Parser, Subcommand};
+use clap::{AppSettings, ArgEnum, Parser, Subcommand};
+use clap_complete::Shell;
 use lazy_static::lazy_static;
-use std::path::PathBuf;
+use std::{fmt, path::PathBuf, time::Duration};
+
+use crate::{
+    helper::parse_age,
+    theme::{ColorMode, Theme},
+};
 
 lazy_static! {
     static ref DEFAULT_CONFIG_FILE: PathBuf =
@@ -20,43 +26,506 @@ pub struct Arguments {
     )]
     pub config: PathBuf,
 
+    /// Number of packages per page when listing voted packages: 50, 100, or 250
+    #[clap(long, global = true)]
+    pub page_size: Option<usize>,
+
+    /// Number of pages to fetch at once when listing voted packages, up to 8
+    #[clap(long, global = true)]
+    pub concurrent_pages: Option<usize>,
+
+    /// Number of packages to vote, unvote, or check at once, up to 8 (default: 4)
+    #[clap(long, global = true)]
+    pub concurrent_votes: Option<usize>,
+
+    /// Output format: `json` for vote, unvote, unvote-all, autovote, list, check, maintained, info,
+    /// stats, diff, and doctor, `csv` for vote, unvote, list, and check, `ndjson` for list (one package per line),
+    /// `table` for list and check (aligned columns), `count` prints number of voted packages
+    /// for list (after filters) and check (among given packages).
+    /// JSON and CSV are never colored.
+    #[clap(long, arg_enum, global = true, default_value = "human")]
+    pub format: OutputFormat,
+
+    /// Print elapsed time of command to stderr
+    #[clap(long, global = true)]
+    pub timing: bool,
+
+    /// Print to stderr how many logins and (un)votes succeeded on first try, after retries,
+    /// or failed. With `--format json`, as `{"retry_stats":{...}}`.
+    #[clap(long, global = true)]
+    pub stats: bool,
+
+    /// Stop starting vote/unvote/check of next package after SECONDS, then exit with status 4
+    #[clap(long, global = true, value_name = "SECONDS")]
+    pub deadline: Option<u64>,
+
+    /// Wait MS milliseconds between vote/unvote/check of packages and pages of voted list,
+    /// to stay under rate limit of AUR, default: `request_delay_ms` in configuration or 0
+    #[clap(long, global = true, value_name = "MS")]
+    pub delay: Option<u64>,
+
+    /// Login using user, pass instead of saved session in cookie file, then overwrite it
+    #[clap(long, global = true)]
+    pub no_login_cache: bool,
+
+    /// Only verify configuration and login, then print `OK`, without running sub-command
+    #[clap(long, global = true)]
+    pub check: bool,
+
+    /// After login, fetch a package page to verify that session is logged in (one more request)
+    #[clap(long, global = true)]
+    pub verify_login: bool,
+
+    /// Fetch voted packages again instead of using cache of `cache_ttl`, then cache them
+    #[clap(long, global = true)]
+    pub refresh: bool,
+
+    /// Directory of state files and caches, default: `$XDG_CACHE_HOME/aur-thumbsup`
+    #[clap(long, global = true, parse(from_os_str))]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Append timestamped log to file, in addition to terminal
+    #[clap(long, global = true, parse(from_os_str))]
+    pub log_file: Option<PathBuf>,
+
+    /// When to color human output: `auto` only on terminal unless `NO_COLOR` is set
+    #[clap(
+        long,
+        arg_enum,
+        global = true,
+        value_name = "WHEN",
+        default_value = "auto"
+    )]
+    pub color: ColorMode,
+
+    /// Colors of human output for terminal background, default: `theme` in configuration, or auto
+    #[clap(long, arg_enum, global = true)]
+    pub theme: Option<Theme>,
+
+    /// Show results of vote, unvote, and check as symbols, e.g. ✓ instead of Voted,
+    /// default: `symbols` in configuration
+    #[clap(long, global = true)]
+    pub symbols: bool,
+
+    /// Use ASCII symbols, e.g. + instead of ✓
+    #[clap(long, global = true)]
+    pub no_unicode: bool,
+
+    /// Pure ASCII output without color, for logs, pipes, or screen readers.
+    /// Stronger than disabling color alone, e.g. no Unicode symbols, dashes, or table borders.
+    #[clap(long, alias = "ascii", global = true)]
+    pub plain: bool,
+
+    /// Print nothing to stdout for vote, unvote, check, list, autovote, vote-all, and unvote-all,
+    /// even with `--format json`; only exit status and errors tell the result
+    #[clap(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Account of `accounts` in configuration to use, instead of `account`
+    #[clap(long, global = true, value_name = "NAME")]
+    pub account: Option<String>,
+
+    /// Override configuration for this run, e.g. `--set page_size=100`, repeatable.
+    /// Keys: page_size, concurrent_votes, autovote_batch_size, autovote_batch_pause,
+    /// autovote_skip_newer, ignore, default_command, min_tls_version, aur_url, proxy,
+    /// connect_timeout, request_timeout, cache_dir, allow_insecure_config, max_requests,
+    /// network_retries, retry_delay_ms, request_delay_ms, full_scan, remember_me,
+    /// prune_invalid_cookie, http_cache, cache_ttl, language, theme, symbols, pacman_timeout,
+    /// account.user, account.cookie_file, account.cookie_login_retries, account.auth_method
+    #[clap(
+        long = "set",
+        value_name = "KEY=VALUE",
+        global = true,
+        multiple_occurrences = true,
+        parse(try_from_str = parse_key_value)
+    )]
+    pub overrides: Vec<(String, String)>,
+
     #[clap(subcommand)]
     pub cmd: Option<Commands>,
 }
 
+/// Packages from positional arguments followed by `--package`, without duplicates
+pub fn merge_packages(packages: Vec<String>, package: Vec<String>) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::new();
+    for pkg in packages.into_iter().chain(package) {
+        if !merged.contains(&pkg) {
+            merged.push(pkg);
+        }
+    }
+    merged
+}
+
+/// Parse `key=value` of `--set`
+fn parse_key_value(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(format!("expected `key=value`, got `{}`", arg)),
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    Human,
+    Json,
+    Ndjson,
+    Count,
+    Table,
+    Csv,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.to_possible_value().expect("Possible value");
+        write!(f, "{}", value.get_name())
+    }
+}
+
+/// Expected vote status of packages for `check --expect`
+#[derive(ArgEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Expectation {
+    Voted,
+    Unvoted,
+
+    /// Package is not available on AUR
+    Absent,
+}
+
+/// Order of `list --sort`, ties are broken by name
+#[derive(ArgEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortKey {
+    /// Name, alphabetically
+    Name,
+
+    /// Number of votes, most voted first
+    Votes,
+
+    /// Popularity, most popular first
+    Popularity,
+}
+
+/// How `autovote` finds installed AUR packages
+#[derive(ArgEnum, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScanMode {
+    /// Foreign packages, i.e. not in any sync database (`pacman -Qm`), verified by RPC
+    #[default]
+    Foreign,
+
+    /// Installed packages from non-official repositories, verified by RPC
+    Repo,
+
+    /// Explicitly installed packages recognized by RPC, regardless of repositories
+    Rpc,
+}
+
 #[derive(Subcommand, PartialEq, Debug)]
 pub enum Commands {
     #[clap(about = "Vote for packages")]
     Vote {
-        #[clap(required = true)]
+        /// Packages to vote for, `-` alone reads them from stdin, one per line
+        #[clap(
+            required_unless_present_any = &["pick", "package", "from-file"],
+            conflicts_with = "pick"
+        )]
         packages: Vec<String>,
+
+        /// Package to vote for, repeatable, in addition to positional packages
+        #[clap(
+            short = 'p',
+            long,
+            value_name = "PKG",
+            multiple_occurrences = true,
+            conflicts_with = "pick"
+        )]
+        package: Vec<String>,
+
+        /// File of packages to vote for, one per line, blank lines and `#` comments are ignored
+        #[clap(long, value_name = "PATH", parse(from_os_str), conflicts_with = "pick")]
+        from_file: Option<PathBuf>,
+
+        /// Vote only for installed packages, skip the others
+        #[clap(long)]
+        if_installed: bool,
+
+        /// Pick packages to vote for from installed AUR packages, requires a terminal
+        #[clap(long)]
+        pick: bool,
+
+        /// Only print packages to vote for, without voting
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Exit with status 0 even if some packages are not available on AUR
+        #[clap(long)]
+        ignore_missing: bool,
     },
 
     #[clap(about = "Unvote packages")]
     Unvote {
-        #[clap(required = true)]
+        /// Packages to unvote, `-` alone reads them from stdin, one per line
+        #[clap(required_unless_present_any = &["only-orphaned", "package", "stale", "from-file"])]
         packages: Vec<String>,
+
+        /// Package to unvote, repeatable, in addition to positional packages
+        #[clap(short = 'p', long, value_name = "PKG", multiple_occurrences = true)]
+        package: Vec<String>,
+
+        /// File of packages to unvote, one per line, blank lines and `#` comments are ignored
+        #[clap(long, value_name = "PATH", parse(from_os_str))]
+        from_file: Option<PathBuf>,
+
+        /// Unvote all voted packages which are orphaned
+        #[clap(long, conflicts_with_all = &["packages", "package", "from-file"])]
+        only_orphaned: bool,
+
+        /// Unvote all voted packages not modified on AUR for AGE, e.g. 6mo, 1y (units: d, w, mo, y)
+        #[clap(
+            long,
+            alias = "older-than",
+            value_name = "AGE",
+            parse(try_from_str = parse_age),
+            conflicts_with_all = &["packages", "package", "from-file", "only-orphaned"]
+        )]
+        stale: Option<Duration>,
+
+        /// Show packages to unvote without unvoting them
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Exit with status 0 even if some packages are not available on AUR
+        #[clap(long)]
+        ignore_missing: bool,
+    },
+
+    #[clap(about = "Vote for all installed AUR packages, never unvote")]
+    VoteAll {
+        /// Only print packages to vote for, without voting
+        #[clap(long)]
+        dry_run: bool,
     },
 
     #[clap(about = "Unvote for all installed packages")]
-    UnvoteAll {},
+    UnvoteAll {
+        /// Packages to keep voted, comma separated or repeated
+        #[clap(long, use_delimiter = true, multiple_occurrences = true)]
+        exclude: Vec<String>,
+
+        /// File of packages to keep voted, one per line
+        #[clap(long, parse(from_os_str))]
+        exclude_file: Option<PathBuf>,
+
+        /// Resume an interrupted run, skipping packages already unvoted or now excluded
+        #[clap(long)]
+        resume: bool,
+
+        /// Only print packages to unvote, without unvoting or saving a run to resume
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Unvote without confirmation. Without it, unvote-all fails if not run from a terminal.
+        #[clap(short, long)]
+        yes: bool,
+    },
 
     #[clap(about = "Check for voted packages")]
     Check {
-        #[clap(required = true)]
+        /// Packages to check, `-` alone reads them from stdin, one per line
+        #[clap(required_unless_present_any = &["package", "from-file"])]
         packages: Vec<String>,
+
+        /// Package to check, repeatable, in addition to positional packages
+        #[clap(short = 'p', long, value_name = "PKG", multiple_occurrences = true)]
+        package: Vec<String>,
+
+        /// File of packages to check, one per line, blank lines and `#` comments are ignored
+        #[clap(long, value_name = "PATH", parse(from_os_str))]
+        from_file: Option<PathBuf>,
+
+        /// Exit with 2 if any package is not voted, 3 if any package is not available
+        #[clap(long)]
+        exit_code: bool,
+
+        /// Exit with 0 only if all packages are in expected state, otherwise 2
+        #[clap(long, arg_enum, conflicts_with = "exit-code")]
+        expect: Option<Expectation>,
+
+        /// Fetch voted list once instead of each package page,
+        /// default when more than 20 packages are checked
+        #[clap(long)]
+        via_voted_list: bool,
+
+        /// Print numbers of voted, not voted, and not available packages after results
+        #[clap(long)]
+        summary: bool,
     },
 
     #[clap(about = "List all voted packages")]
-    List {},
+    List {
+        /// Group split packages under their package base
+        #[clap(long)]
+        group_by_base: bool,
+
+        /// List only orphaned packages
+        #[clap(long, alias = "orphan")]
+        only_orphaned: bool,
+
+        /// List only installed packages
+        #[clap(long)]
+        installed: bool,
+
+        /// List only installed packages older than AUR version
+        #[clap(long)]
+        outdated: bool,
+
+        /// Show pending deletion/merge requests, one more request per package
+        #[clap(long)]
+        show_requests: bool,
+
+        /// Format each package using placeholders, e.g. "{name} {version} {votes} {installed}".
+        /// Placeholders: name, version, votes, popularity, voted, notify, description,
+        /// maintainer, installed (installed version), status (outdated, same, or newer)
+        #[clap(long, alias = "output-template")]
+        template: Option<String>,
+
+        /// Do not print summary of installed, outdated, and orphaned packages at the end.
+        /// Summary is never printed with `--template`.
+        #[clap(long)]
+        no_summary: bool,
+
+        /// List packages maintained by another user and their votes, without login.
+        /// Voted packages of other users are private, they are never listed.
+        #[clap(
+            long,
+            value_name = "NAME",
+            conflicts_with_all = &[
+                "group-by-base",
+                "only-orphaned",
+                "installed",
+                "outdated",
+                "show-requests",
+                "template",
+                "page",
+                "head",
+                "tail"
+            ]
+        )]
+        user: Option<String>,
+
+        /// Print only page N of packages, starting from 1, with a footer like "Showing 1–25 of 312"
+        #[clap(long, value_name = "N", conflicts_with_all = &["head", "tail"])]
+        page: Option<usize>,
+
+        /// Number of packages per page of `--page` [default: 25]
+        #[clap(long, value_name = "M", requires = "page")]
+        per_page: Option<usize>,
+
+        /// Print only the first N packages
+        #[clap(long, value_name = "N", conflicts_with = "tail")]
+        head: Option<usize>,
+
+        /// Print only the last N packages
+        #[clap(long, value_name = "N")]
+        tail: Option<usize>,
+
+        /// Print voted packages as a tree of dependencies among them, from RPC.
+        /// Packages not depended on by other voted packages are roots.
+        #[clap(
+            long,
+            conflicts_with_all = &[
+                "group-by-base",
+                "show-requests",
+                "template",
+                "user",
+                "page",
+                "head",
+                "tail"
+            ]
+        )]
+        graph: bool,
+
+        /// Sort voted packages by name, votes, or popularity, ties are broken by name
+        #[clap(long, arg_enum, value_name = "KEY", conflicts_with = "user")]
+        sort: Option<SortKey>,
+
+        /// Reverse order of `--sort`
+        #[clap(long, requires = "sort")]
+        reverse: bool,
+
+        /// Show votes and popularity of each package
+        #[clap(short, long, conflicts_with_all = &["template", "user"])]
+        verbose: bool,
+    },
 
     #[clap(about = "Vote/Unvote for installed packages")]
-    Autovote {},
+    Autovote {
+        /// Packages to keep voted, comma separated or repeated
+        #[clap(long, use_delimiter = true, multiple_occurrences = true)]
+        exclude: Vec<String>,
+
+        /// File of packages to keep voted, one per line
+        #[clap(long, parse(from_os_str))]
+        exclude_file: Option<PathBuf>,
+
+        /// Only vote for installed packages, never unvote
+        #[clap(long, conflicts_with = "unvote-only")]
+        vote_only: bool,
+
+        /// Only unvote for uninstalled packages, never vote
+        #[clap(long)]
+        unvote_only: bool,
+
+        /// Do not vote for packages installed in a newer version than AUR version, e.g. local builds.
+        /// Default from `autovote_skip_newer` in configuration.
+        #[clap(long, conflicts_with_all = &["hook", "unvote-only"])]
+        skip_newer: bool,
+
+        /// Only add votes for installed packages, never unvote any package,
+        /// even if it is no longer installed. The safe choice to support every package ever used.
+        #[clap(long, alias = "installed-only", conflicts_with_all = &["vote-only", "unvote-only"])]
+        additive: bool,
+
+        /// Only vote/unvote packages read from stdin, one per line, as given by pacman hook
+        #[clap(long)]
+        hook: bool,
+
+        /// Print only number of packages to vote/unvote, without voting/unvoting
+        #[clap(long, conflicts_with = "hook")]
+        count_only: bool,
+
+        /// Unvote without confirmation. Without it, unvote is skipped if not run from a terminal,
+        /// or with `--hook`.
+        #[clap(short, long)]
+        yes: bool,
+
+        /// How to find installed AUR packages: foreign packages (foreign),
+        /// from non-official repositories (repo), or all explicitly installed packages known to AUR (rpc)
+        #[clap(long, arg_enum, default_value = "foreign", conflicts_with = "hook")]
+        scan_mode: ScanMode,
+
+        /// Only vote for explicitly installed packages, not AUR dependencies of other packages.
+        /// Votes of packages installed as dependencies are kept.
+        #[clap(long, conflicts_with = "hook")]
+        explicit_only: bool,
+
+        /// Only print packages to vote/unvote, without voting/unvoting
+        #[clap(long, conflicts_with_all = &["hook", "count-only"])]
+        dry_run: bool,
+    },
 
     #[clap(about = "Create configuration file")]
     CreateConfig {
         #[clap(required = true, parse(from_os_str))]
         path: PathBuf,
+
+        /// Print the configuration instead of creating the file
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Print pass with `--dry-run`, instead of `***`
+        #[clap(long, requires = "dry-run")]
+        show_secrets: bool,
     },
 
     #[clap(about = "Check configuration file")]
@@ -64,6 +533,265 @@ pub enum Commands {
         #[clap(required = true, parse(from_os_str))]
         path: PathBuf,
     },
+
+    #[clap(
+        name = "__complete-packages",
+        about = "Print AUR package names starting with prefix, for shell completion",
+        setting = AppSettings::Hidden
+    )]
+    CompletePackages {
+        #[clap(required = true)]
+        prefix: String,
+    },
+
+    #[clap(
+        about = "Print shell completion script of subcommands and options, e.g. for bash, zsh, or fish",
+        setting = AppSettings::Hidden
+    )]
+    Completions {
+        #[clap(arg_enum)]
+        shell: Shell,
+    },
+
+    #[clap(about = "Check expiration of session in cookie file without network access")]
+    VerifySession {
+        #[clap(required = true, parse(from_os_str))]
+        path: PathBuf,
+    },
+
+    #[clap(about = "Remove cookie file, then login using user, pass to create a new one")]
+    RenewCookie,
+
+    #[clap(about = "Logout session in cookie file, then remove the cookie file")]
+    Logout,
+
+    #[clap(about = "List packages maintained by user")]
+    Maintained,
+
+    #[clap(about = "Summarize voted packages: installed, outdated, orphaned, and their votes")]
+    Stats,
+
+    #[clap(about = "Show installed AUR packages not voted and voted packages not installed")]
+    Diff {
+        /// Packages to keep voted, comma separated or repeated
+        #[clap(long, use_delimiter = true, multiple_occurrences = true)]
+        exclude: Vec<String>,
+
+        /// File of packages to keep voted, one per line
+        #[clap(long, parse(from_os_str))]
+        exclude_file: Option<PathBuf>,
+
+        /// How to find installed AUR packages, as `autovote --scan-mode`
+        #[clap(long, arg_enum, default_value = "foreign")]
+        scan_mode: ScanMode,
+
+        /// Only show explicitly installed packages to vote, as `autovote --explicit-only`
+        #[clap(long)]
+        explicit_only: bool,
+
+        /// Do not show packages installed in a newer version than AUR version to vote,
+        /// as `autovote --skip-newer`
+        #[clap(long)]
+        skip_newer: bool,
+    },
+
+    #[clap(about = "Vote for packages listed in file, one per line, or saved by `export`")]
+    Import {
+        /// File of packages, `.csv` and `.json` are read as saved by `export`
+        #[clap(required = true, parse(from_os_str))]
+        path: PathBuf,
+
+        /// Resume an interrupted import, skipping packages already voted
+        #[clap(long)]
+        resume: bool,
+
+        /// Print packages to vote for, without login nor voting
+        #[clap(long, conflicts_with = "resume")]
+        dry_run: bool,
+    },
+
+    #[clap(about = "Save voted packages to file as CSV or JSON, e.g. for backup")]
+    Export {
+        /// File to save to, its format is from `--format`, or from extension `.csv` or `.json`
+        #[clap(required = true, parse(from_os_str))]
+        path: PathBuf,
+
+        /// Overwrite existing file
+        #[clap(long)]
+        force: bool,
+    },
+
+    #[clap(about = "Diagnose configuration, pacman, network, cookie, and login")]
+    Doctor,
+
+    #[clap(about = "Post comment on package page")]
+    Comment {
+        package: String,
+
+        /// Comment, Markdown syntax is partially supported
+        text: String,
+    },
+
+    #[clap(about = "Flag package out-of-date")]
+    Flag {
+        package: String,
+
+        /// Why package is out-of-date, e.g. link to the new release
+        reason: String,
+    },
+
+    #[clap(about = "Unflag package, only its maintainers and the user who flagged it are allowed")]
+    Unflag { package: String },
+
+    #[clap(about = "Enable notifications of new comments on packages")]
+    Notify {
+        #[clap(required = true)]
+        packages: Vec<String>,
+
+        /// Disable notifications instead
+        #[clap(long)]
+        off: bool,
+    },
+
+    #[clap(about = "Save session of browser to cookie file, so pass is not required")]
+    ImportSession {
+        /// Value of AURSID cookie of logged in browser
+        #[clap(
+            long,
+            value_name = "VALUE",
+            required_unless_present = "cookies-txt",
+            conflicts_with = "cookies-txt"
+        )]
+        aursid: Option<String>,
+
+        /// Netscape cookies.txt exported by browser
+        #[clap(long, value_name = "FILE", parse(from_os_str))]
+        cookies_txt: Option<PathBuf>,
+    },
+
+    #[clap(about = "Show version, first submitted and last modified dates of packages")]
+    Info {
+        #[clap(required = true)]
+        packages: Vec<String>,
+    },
+
+    #[clap(about = "Vote for packages not voted yet, exit with 10 if any package is changed")]
+    EnsureVoted {
+        #[clap(required = true)]
+        packages: Vec<String>,
+    },
+
+    #[clap(about = "Unvote packages still voted, exit with 10 if any package is changed")]
+    EnsureUnvoted {
+        #[clap(required = true)]
+        packages: Vec<String>,
+    },
+
+    #[clap(about = "Check vote of all voted packages, vote again for packages whose vote is lost")]
+    Revote {
+        /// Only print packages whose vote is lost, without voting
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+/// Sub-commands allowed as `default_command` in configuration, they take no required arguments
+pub const DEFAULT_COMMANDS: [&str; 3] = ["autovote", "list", "unvote-all"];
+
+impl Commands {
+    /// Sub-command with default arguments for `default_command` in configuration
+    pub fn from_default_command(name: &str) -> Option<Commands> {
+        if !DEFAULT_COMMANDS.contains(&name) {
+            return None;
+        }
+        Arguments::try_parse_from(["aur-thumbsup", name])
+            .ok()
+            .and_then(|args| args.cmd)
+    }
+
+    /// Check if sub-command supports output format
+    pub fn supports_format(&self, format: OutputFormat) -> bool {
+        match format {
+            OutputFormat::Human => true,
+            OutputFormat::Json => matches!(
+                self,
+                Commands::Vote { .. }
+                    | Commands::Unvote { .. }
+                    | Commands::VoteAll { .. }
+                    | Commands::UnvoteAll { .. }
+                    | Commands::List { .. }
+                    | Commands::Check { .. }
+                    | Commands::Maintained
+                    | Commands::Stats
+                    | Commands::Diff { .. }
+                    | Commands::Info { .. }
+                    | Commands::Doctor
+                    | Commands::Autovote { hook: false, .. }
+                    | Commands::Export { .. }
+            ),
+            OutputFormat::Ndjson => matches!(self, Commands::List { .. }),
+            OutputFormat::Count => matches!(self, Commands::List { .. } | Commands::Check { .. }),
+            OutputFormat::Table => matches!(self, Commands::List { .. } | Commands::Check { .. }),
+            OutputFormat::Csv => matches!(
+                self,
+                Commands::Vote { .. }
+                    | Commands::Unvote { .. }
+                    | Commands::VoteAll { .. }
+                    | Commands::List { graph: false, .. }
+                    | Commands::Check { .. }
+                    | Commands::Export { .. }
+            ),
+        }
+    }
+
+    /// Whether `--quiet` suppresses output of sub-command
+    pub fn supports_quiet(&self) -> bool {
+        matches!(
+            self,
+            Commands::Vote { .. }
+                | Commands::Unvote { .. }
+                | Commands::Check { .. }
+                | Commands::List { .. }
+                | Commands::Autovote { .. }
+                | Commands::VoteAll { .. }
+                | Commands::UnvoteAll { .. }
+        )
+    }
+
+    /// Sub-command name as used on command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Vote { .. } => "vote",
+            Commands::Unvote { .. } => "unvote",
+            Commands::VoteAll { .. } => "vote-all",
+            Commands::UnvoteAll { .. } => "unvote-all",
+            Commands::Check { .. } => "check",
+            Commands::List { .. } => "list",
+            Commands::Autovote { .. } => "autovote",
+            Commands::CreateConfig { .. } => "create-config",
+            Commands::CheckConfig { .. } => "check-config",
+            Commands::CompletePackages { .. } => "__complete-packages",
+            Commands::Completions { .. } => "completions",
+            Commands::VerifySession { .. } => "verify-session",
+            Commands::RenewCookie => "renew-cookie",
+            Commands::Logout => "logout",
+            Commands::Maintained => "maintained",
+            Commands::Stats => "stats",
+            Commands::Diff { .. } => "diff",
+            Commands::Import { .. } => "import",
+            Commands::Export { .. } => "export",
+            Commands::Doctor => "doctor",
+            Commands::Comment { .. } => "comment",
+            Commands::EnsureVoted { .. } => "ensure-voted",
+            Commands::EnsureUnvoted { .. } => "ensure-unvoted",
+            Commands::Flag { .. } => "flag",
+            Commands::ImportSession { .. } => "import-session",
+            Commands::Info { .. } => "info",
+            Commands::Unflag { .. } => "unflag",
+            Commands::Notify { .. } => "notify",
+            Commands::Revote { .. } => "revote",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -101,99 +829,1166 @@ mod tests {
         .expect("Paring argument");
         assert_eq!(args.config, PathBuf::from(r"/etc/aur-thumbsup.toml"));
         assert_eq!(args.cmd, None);
-    }
 
-    #[test]
-    fn vote_cmd() {
+        // output format
+        assert_eq!(args.format, OutputFormat::Human);
         let args = Arguments::from_arg_matches(
-            &Arguments::into_app().get_matches_from(vec!["test", "vote", "pkg1", "pkg2"]),
+            &Arguments::into_app()
+                .get_matches_from(vec!["test", "check", "pkg1", "--format", "json"]),
         )
         .expect("Paring argument");
-        assert_eq!(
-            args.cmd,
-            Some(Commands::Vote {
-                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()]
-            })
-        );
-    }
+        assert_eq!(args.format, OutputFormat::Json);
+        assert!(args.cmd.unwrap().supports_format(OutputFormat::Json));
 
-    #[test]
-    fn unvote_cmd() {
         let args = Arguments::from_arg_matches(
-            &Arguments::into_app().get_matches_from(vec!["test", "unvote", "pkg1", "pkg2"]),
+            &Arguments::into_app()
+                .get_matches_from(vec!["test", "unvote", "pkg1", "--format", "csv"]),
         )
         .expect("Paring argument");
-        assert_eq!(
-            args.cmd,
-            Some(Commands::Unvote {
-                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()]
-            })
-        );
-    }
+        assert_eq!(args.format, OutputFormat::Csv);
+        let cmd = args.cmd.unwrap();
+        assert!(cmd.supports_format(OutputFormat::Csv));
+        assert!(cmd.supports_format(OutputFormat::Json));
+        assert!(!cmd.supports_format(OutputFormat::Table));
 
-    #[test]
-    fn unvote_all_cmd() {
         let args = Arguments::from_arg_matches(
-            &Arguments::into_app().get_matches_from(vec!["test", "unvote-all"]),
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--format", "count"]),
         )
         .expect("Paring argument");
-        assert_eq!(args.cmd, Some(Commands::UnvoteAll {}));
-    }
+        assert_eq!(args.format, OutputFormat::Count);
+        let cmd = args.cmd.unwrap();
+        assert!(cmd.supports_format(OutputFormat::Count));
+        assert!(cmd.supports_format(OutputFormat::Json));
 
-    #[test]
-    fn check_cmd() {
-        let args = Arguments::from_arg_matches(
-            &Arguments::into_app().get_matches_from(vec!["test", "check", "pkg1", "pkg2"]),
-        )
+        // page size
+        assert_eq!(args.page_size, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--page-size",
+            "50",
+        ]))
         .expect("Paring argument");
-        assert_eq!(
-            args.cmd,
-            Some(Commands::Check {
-                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()]
-            })
-        );
-    }
+        assert_eq!(args.page_size, Some(50));
 
-    #[test]
-    fn list_cmd() {
+        // concurrent pages
+        assert_eq!(args.concurrent_pages, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--concurrent-pages",
+            "3",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.concurrent_pages, Some(3));
+
+        // concurrent votes
+        assert_eq!(args.concurrent_votes, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "check",
+            "--concurrent-votes",
+            "8",
+            "yay",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.concurrent_votes, Some(8));
+
+        // timing flag
+        assert!(!args.timing);
         let args = Arguments::from_arg_matches(
-            &Arguments::into_app().get_matches_from(vec!["test", "list"]),
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--timing"]),
         )
         .expect("Paring argument");
-        assert_eq!(args.cmd, Some(Commands::List {}));
-    }
+        assert!(args.timing);
 
-    #[test]
-    fn autovote_cmd() {
+        // stats flag
+        assert!(!args.stats);
         let args = Arguments::from_arg_matches(
-            &Arguments::into_app().get_matches_from(vec!["test", "autovote"]),
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--stats"]),
         )
         .expect("Paring argument");
-        assert_eq!(args.cmd, Some(Commands::Autovote {}));
-    }
+        assert!(args.stats);
 
-    #[test]
-    fn create_config_cmd() {
+        // no login cache
+        assert!(!args.no_login_cache);
         let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
             "test",
-            "create-config",
-            "/etc/aur-thumbsup.toml",
+            "list",
+            "--no-login-cache",
         ]))
         .expect("Paring argument");
-        assert_eq!(
-            args.cmd,
-            Some(Commands::CreateConfig {
-                path: PathBuf::from(r"/etc/aur-thumbsup.toml")
-            })
-        );
-    }
+        assert!(args.no_login_cache);
 
-    #[test]
-    fn check_config_cmd() {
+        // preflight check
+        assert!(!args.check);
         let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
             "test",
-            "check-config",
-            "/etc/aur-thumbsup.toml",
+            "unvote-all",
+            "--check",
+        ]))
+        .expect("Paring argument");
+        assert!(args.check);
+        assert!(matches!(args.cmd, Some(Commands::UnvoteAll { .. })));
+
+        // verify login
+        assert!(!args.verify_login);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote",
+            "pkg1",
+            "--verify-login",
+        ]))
+        .expect("Paring argument");
+        assert!(args.verify_login);
+
+        // cache dir
+        assert_eq!(args.cache_dir, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "unvote-all",
+            "--cache-dir",
+            "/tmp/aur-thumbsup",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.cache_dir, Some(PathBuf::from("/tmp/aur-thumbsup")));
+
+        // deadline
+        assert_eq!(args.deadline, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote",
+            "pkg1",
+            "--deadline",
+            "300",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.deadline, Some(300));
+
+        // theme
+        assert_eq!(args.theme, None);
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--theme", "light"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.theme, Some(Theme::Light));
+
+        // symbols
+        assert!(!args.symbols);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "check",
+            "pkg1",
+            "--symbols",
+            "--no-unicode",
+        ]))
+        .expect("Paring argument");
+        assert!(args.symbols);
+        assert!(args.no_unicode);
+
+        // color
+        assert_eq!(args.color, ColorMode::Auto);
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--color", "never"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.color, ColorMode::Never);
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--color", "sometimes"])
+            .is_err());
+
+        // plain
+        assert!(!args.plain);
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--ascii"]),
+        )
+        .expect("Paring argument");
+        assert!(args.plain);
+
+        // configuration overrides
+        assert!(args.overrides.is_empty());
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--set",
+            "page_size=100",
+            "--set",
+            "account.cookie_file=/tmp/foo.cookie",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.overrides,
+            vec![
+                ("page_size".to_owned(), "100".to_owned()),
+                (
+                    "account.cookie_file".to_owned(),
+                    "/tmp/foo.cookie".to_owned()
+                )
+            ]
+        );
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--set", "page_size"])
+            .is_err());
+
+        // account
+        assert_eq!(args.account, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--account",
+            "work",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.account.as_deref(), Some("work"));
+
+        // log file
+        assert_eq!(args.log_file, None);
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--log-file",
+            "/var/log/aur-thumbsup.log",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.log_file,
+            Some(PathBuf::from("/var/log/aur-thumbsup.log"))
+        );
+    }
+
+    #[test]
+    fn default_command() {
+        for name in DEFAULT_COMMANDS {
+            let cmd = Commands::from_default_command(name).expect("Default command");
+            assert_eq!(cmd.name(), name);
+        }
+        assert_eq!(
+            Commands::from_default_command("list"),
+            Some(Commands::List {
+                group_by_base: false,
+                only_orphaned: false,
+                installed: false,
+                outdated: false,
+                show_requests: false,
+                template: None,
+                no_summary: false,
+                user: None,
+                page: None,
+                per_page: None,
+                head: None,
+                tail: None,
+                graph: false,
+                sort: None,
+                reverse: false,
+                verbose: false
+            })
+        );
+        assert_eq!(Commands::from_default_command("vote"), None);
+        assert_eq!(Commands::from_default_command("foo"), None);
+    }
+
+    #[test]
+    fn vote_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "vote", "pkg1", "pkg2"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Vote {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                package: vec![],
+                from_file: None,
+                if_installed: false,
+                pick: false,
+                dry_run: false,
+                ignore_missing: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote",
+            "pkg1",
+            "--ignore-missing",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::Vote {
+                ignore_missing: true,
+                ..
+            })
+        ));
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote",
+            "--if-installed",
+            "pkg1",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Vote {
+                packages: vec!["pkg1".to_owned()],
+                package: vec![],
+                from_file: None,
+                if_installed: true,
+                pick: false,
+                dry_run: false,
+                ignore_missing: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote",
+            "--pick",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Vote {
+                packages: vec![],
+                package: vec![],
+                from_file: None,
+                if_installed: false,
+                pick: true,
+                dry_run: true,
+                ignore_missing: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote",
+            "-p",
+            "pkg1",
+            "--package",
+            "pkg2",
+            "pkg3",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Vote {
+                packages: vec!["pkg3".to_owned()],
+                package: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                from_file: None,
+                if_installed: false,
+                pick: false,
+                dry_run: false,
+                ignore_missing: false
+            })
+        );
+
+        // Packages are required unless picked, not both
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "vote"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "vote", "--pick", "pkg1"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "vote", "--pick", "-p", "pkg1"])
+            .is_err());
+    }
+
+    #[test]
+    fn packages_from_stdin_or_file() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "vote", "-"]),
+        )
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::Vote { packages, .. }) if packages == vec!["-"]
+        ));
+
+        for cmd in ["vote", "unvote", "check"] {
+            let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+                "test",
+                cmd,
+                "--from-file",
+                "/tmp/pkgs.txt",
+            ]))
+            .expect("Paring argument");
+            let from_file = match args.cmd {
+                Some(Commands::Vote { from_file, .. })
+                | Some(Commands::Unvote { from_file, .. })
+                | Some(Commands::Check { from_file, .. }) => from_file,
+                _ => None,
+            };
+            assert_eq!(from_file, Some(PathBuf::from("/tmp/pkgs.txt")));
+        }
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec![
+                "test",
+                "unvote",
+                "--from-file",
+                "pkgs.txt",
+                "--only-orphaned"
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn test_merge_packages() {
+        assert_eq!(
+            merge_packages(
+                vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                vec!["pkg2".to_owned(), "pkg3".to_owned(), "pkg1".to_owned()]
+            ),
+            vec!["pkg1".to_owned(), "pkg2".to_owned(), "pkg3".to_owned()]
+        );
+        assert!(merge_packages(vec![], vec![]).is_empty());
+    }
+
+    #[test]
+    fn unvote_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "unvote", "pkg1", "pkg2"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Unvote {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                package: vec![],
+                from_file: None,
+                only_orphaned: false,
+                stale: None,
+                dry_run: false,
+                ignore_missing: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "unvote",
+            "--only-orphaned",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Unvote {
+                packages: vec![],
+                package: vec![],
+                from_file: None,
+                only_orphaned: true,
+                stale: None,
+                dry_run: true,
+                ignore_missing: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "unvote",
+            "--older-than",
+            "2y",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Unvote {
+                packages: vec![],
+                package: vec![],
+                from_file: None,
+                only_orphaned: false,
+                stale: Some(Duration::from_secs(2 * 365 * 24 * 60 * 60)),
+                dry_run: true,
+                ignore_missing: false
+            })
+        );
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "unvote", "--stale", "2"])
+            .is_err());
+
+        // Packages are required without `--only-orphaned`
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "unvote"])
+            .is_err());
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "unvote", "-p", "pkg1"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Unvote {
+                packages: vec![],
+                package: vec!["pkg1".to_owned()],
+                from_file: None,
+                only_orphaned: false,
+                stale: None,
+                dry_run: false,
+                ignore_missing: false
+            })
+        );
+
+        // Packages conflict with `--only-orphaned`
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "unvote", "--only-orphaned", "pkg1"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "unvote", "--only-orphaned", "-p", "pkg1"])
+            .is_err());
+    }
+
+    #[test]
+    fn unvote_all_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "unvote-all"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::UnvoteAll {
+                exclude: vec![],
+                exclude_file: None,
+                resume: false,
+                dry_run: false,
+                yes: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "unvote-all",
+            "--exclude",
+            "pkg1,pkg2",
+            "--exclude",
+            "pkg3",
+            "--exclude-file",
+            "/etc/aur-thumbsup.exclude",
+            "--resume",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::UnvoteAll {
+                exclude: vec!["pkg1".to_owned(), "pkg2".to_owned(), "pkg3".to_owned()],
+                exclude_file: Some(PathBuf::from(r"/etc/aur-thumbsup.exclude")),
+                resume: true,
+                dry_run: false,
+                yes: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "unvote-all",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::UnvoteAll { dry_run: true, .. })
+        ));
+    }
+
+    #[test]
+    fn check_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "check", "pkg1", "pkg2"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Check {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                package: vec![],
+                from_file: None,
+                exit_code: false,
+                expect: None,
+                via_voted_list: false,
+                summary: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "check",
+            "pkg1",
+            "pkg2",
+            "--exit-code",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Check {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                package: vec![],
+                from_file: None,
+                exit_code: true,
+                expect: None,
+                via_voted_list: false,
+                summary: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app()
+                .get_matches_from(vec!["test", "check", "--expect", "unvoted", "pkg1"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Check {
+                packages: vec!["pkg1".to_owned()],
+                package: vec![],
+                from_file: None,
+                exit_code: false,
+                expect: Some(Expectation::Unvoted),
+                via_voted_list: false,
+                summary: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "check",
+            "--via-voted-list",
+            "pkg1",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Check {
+                packages: vec!["pkg1".to_owned()],
+                package: vec![],
+                from_file: None,
+                exit_code: false,
+                expect: None,
+                via_voted_list: true,
+                summary: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "check",
+            "--summary",
+            "--exit-code",
+            "--format",
+            "table",
+            "pkg1",
+        ]))
+        .expect("Paring argument");
+        let cmd = args.cmd.unwrap();
+        assert_eq!(
+            cmd,
+            Commands::Check {
+                packages: vec!["pkg1".to_owned()],
+                package: vec![],
+                from_file: None,
+                exit_code: true,
+                expect: None,
+                via_voted_list: false,
+                summary: true
+            }
+        );
+        assert!(cmd.supports_format(OutputFormat::Table));
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app()
+                .get_matches_from(vec!["test", "check", "-p", "pkg1", "-p", "pkg2"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Check {
+                packages: vec![],
+                package: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                from_file: None,
+                exit_code: false,
+                expect: None,
+                via_voted_list: false,
+                summary: false
+            })
+        );
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "check"])
+            .is_err());
+
+        // `--expect` conflicts with `--exit-code`
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec![
+                "test",
+                "check",
+                "--exit-code",
+                "--expect",
+                "voted",
+                "pkg1"
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn list_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::List {
+                group_by_base: false,
+                only_orphaned: false,
+                installed: false,
+                outdated: false,
+                show_requests: false,
+                template: None,
+                no_summary: false,
+                user: None,
+                page: None,
+                per_page: None,
+                head: None,
+                tail: None,
+                graph: false,
+                sort: None,
+                reverse: false,
+                verbose: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--group-by-base",
+            "--only-orphaned",
+            "--show-requests",
+            "--template",
+            "{name} {version}",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::List {
+                group_by_base: true,
+                only_orphaned: true,
+                installed: false,
+                outdated: false,
+                show_requests: true,
+                template: Some("{name} {version}".to_owned()),
+                no_summary: false,
+                user: None,
+                page: None,
+                per_page: None,
+                head: None,
+                tail: None,
+                graph: false,
+                sort: None,
+                reverse: false,
+                verbose: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--user", "bpetlert"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::List {
+                group_by_base: false,
+                only_orphaned: false,
+                installed: false,
+                outdated: false,
+                show_requests: false,
+                template: None,
+                no_summary: false,
+                user: Some("bpetlert".to_owned()),
+                page: None,
+                per_page: None,
+                head: None,
+                tail: None,
+                graph: false,
+                sort: None,
+                reverse: false,
+                verbose: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--page",
+            "2",
+            "--per-page",
+            "50",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::List {
+                group_by_base: false,
+                only_orphaned: false,
+                installed: false,
+                outdated: false,
+                show_requests: false,
+                template: None,
+                no_summary: false,
+                user: None,
+                page: Some(2),
+                per_page: Some(50),
+                head: None,
+                tail: None,
+                graph: false,
+                sort: None,
+                reverse: false,
+                verbose: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "--graph"]),
+        )
+        .expect("Paring argument");
+        assert!(matches!(args.cmd, Some(Commands::List { graph: true, .. })));
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "list", "-v"]),
+        )
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::List { verbose: true, .. })
+        ));
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "-v", "--template", "{name}"])
+            .is_err());
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--orphan",
+            "--installed",
+            "--outdated",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::List {
+                only_orphaned: true,
+                installed: true,
+                outdated: true,
+                ..
+            })
+        ));
+        assert!(args.cmd.unwrap().supports_format(OutputFormat::Json));
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--graph", "--group-by-base"])
+            .is_err());
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "list",
+            "--sort",
+            "votes",
+            "--reverse",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::List {
+                sort: Some(SortKey::Votes),
+                reverse: true,
+                ..
+            })
+        ));
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--reverse"])
+            .is_err());
+
+        // Only one way of pagination, `--per-page` is for `--page`
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--page", "2", "--head", "10"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--head", "10", "--tail", "10"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "list", "--per-page", "10"])
+            .is_err());
+
+        // Voted list options do not apply to other users
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec![
+                "test",
+                "list",
+                "--user",
+                "bpetlert",
+                "--only-orphaned"
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn autovote_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "autovote"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Autovote {
+                exclude: vec![],
+                exclude_file: None,
+                vote_only: false,
+                unvote_only: false,
+                additive: false,
+                hook: false,
+                count_only: false,
+                yes: false,
+                scan_mode: ScanMode::Foreign,
+                explicit_only: false,
+                dry_run: false,
+                skip_newer: false,
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--exclude",
+            "pkg1,pkg2",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Autovote {
+                exclude: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                exclude_file: None,
+                vote_only: false,
+                unvote_only: false,
+                additive: false,
+                hook: false,
+                count_only: false,
+                yes: false,
+                scan_mode: ScanMode::Foreign,
+                explicit_only: false,
+                dry_run: false,
+                skip_newer: false,
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--vote-only",
+            "--yes",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Autovote {
+                exclude: vec![],
+                exclude_file: None,
+                vote_only: true,
+                unvote_only: false,
+                additive: false,
+                hook: false,
+                count_only: false,
+                yes: true,
+                scan_mode: ScanMode::Foreign,
+                explicit_only: false,
+                dry_run: false,
+                skip_newer: false,
+            })
+        );
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "autovote", "--hook"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Autovote {
+                exclude: vec![],
+                exclude_file: None,
+                vote_only: false,
+                unvote_only: false,
+                additive: false,
+                hook: true,
+                count_only: false,
+                yes: false,
+                scan_mode: ScanMode::Foreign,
+                explicit_only: false,
+                dry_run: false,
+                skip_newer: false,
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--count-only",
+            "--format",
+            "json",
+        ]))
+        .expect("Paring argument");
+        let cmd = args.cmd.unwrap();
+        assert_eq!(
+            cmd,
+            Commands::Autovote {
+                exclude: vec![],
+                exclude_file: None,
+                vote_only: false,
+                unvote_only: false,
+                additive: false,
+                hook: false,
+                count_only: true,
+                yes: false,
+                scan_mode: ScanMode::Foreign,
+                explicit_only: false,
+                dry_run: false,
+                skip_newer: false,
+            }
+        );
+        assert!(cmd.supports_format(OutputFormat::Json));
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--scan-mode",
+            "rpc",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Autovote {
+                exclude: vec![],
+                exclude_file: None,
+                vote_only: false,
+                unvote_only: false,
+                additive: false,
+                hook: false,
+                count_only: false,
+                yes: false,
+                scan_mode: ScanMode::Rpc,
+                explicit_only: false,
+                dry_run: false,
+                skip_newer: false,
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--scan-mode",
+            "repo",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::Autovote {
+                scan_mode: ScanMode::Repo,
+                ..
+            })
+        ));
+
+        // Targets of `--hook` are given by pacman
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "autovote", "--hook", "--scan-mode", "rpc"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "autovote", "--hook", "--explicit-only"])
+            .is_err());
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--installed-only",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::Autovote { additive: true, .. })
+        ));
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "autovote", "--additive", "--unvote-only"])
+            .is_err());
+
+        // `--vote-only` and `--unvote-only` are mutually exclusive
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "autovote", "--vote-only", "--unvote-only"])
+            .is_err());
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "autovote",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert!(matches!(
+            args.cmd,
+            Some(Commands::Autovote { dry_run: true, .. })
+        ));
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "autovote", "--dry-run", "--hook"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "autovote", "--dry-run", "--count-only"])
+            .is_err());
+    }
+
+    #[test]
+    fn create_config_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "create-config",
+            "/etc/aur-thumbsup.toml",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::CreateConfig {
+                path: PathBuf::from(r"/etc/aur-thumbsup.toml"),
+                dry_run: false,
+                show_secrets: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "create-config",
+            "--dry-run",
+            "--show-secrets",
+            "/etc/aur-thumbsup.toml",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::CreateConfig {
+                path: PathBuf::from(r"/etc/aur-thumbsup.toml"),
+                dry_run: true,
+                show_secrets: true
+            })
+        );
+
+        // Secrets are shown only for preview
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec![
+                "test",
+                "create-config",
+                "--show-secrets",
+                "/etc/aur-thumbsup.toml"
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn check_config_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "check-config",
+            "/etc/aur-thumbsup.toml",
         ]))
         .expect("Paring argument");
         assert_eq!(
@@ -203,4 +1998,402 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn verify_session_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "verify-session",
+            "/var/tmp/aur-thumbsup-foo.cookie",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::VerifySession {
+                path: PathBuf::from(r"/var/tmp/aur-thumbsup-foo.cookie")
+            })
+        );
+    }
+
+    #[test]
+    fn renew_cookie_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "renew-cookie"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::RenewCookie));
+    }
+
+    #[test]
+    fn logout_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "logout"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Logout));
+    }
+
+    #[test]
+    fn quiet_flag() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "vote", "-q", "yay"]),
+        )
+        .expect("Paring argument");
+        assert!(args.quiet);
+        assert!(args.cmd.unwrap().supports_quiet());
+        assert!(!Commands::Stats.supports_quiet());
+    }
+
+    #[test]
+    fn vote_all_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "vote-all",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::VoteAll { dry_run: true }));
+        assert!(Commands::VoteAll { dry_run: false }.supports_format(OutputFormat::Csv));
+    }
+
+    #[test]
+    fn stats_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "--format", "json", "stats"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Stats));
+        assert!(Commands::Stats.supports_format(OutputFormat::Json));
+        assert!(!Commands::Stats.supports_format(OutputFormat::Csv));
+    }
+
+    #[test]
+    fn diff_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "diff",
+            "--exclude",
+            "foo,bar",
+            "--scan-mode",
+            "repo",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Diff {
+                exclude: vec!["foo".to_owned(), "bar".to_owned()],
+                exclude_file: None,
+                scan_mode: ScanMode::Repo,
+                explicit_only: false,
+                skip_newer: false,
+            })
+        );
+    }
+
+    #[test]
+    fn maintained_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "maintained",
+            "--format",
+            "json",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Maintained));
+        assert!(args.cmd.unwrap().supports_format(args.format));
+    }
+
+    #[test]
+    fn import_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "import",
+            "/tmp/votes.txt",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Import {
+                path: PathBuf::from(r"/tmp/votes.txt"),
+                resume: false,
+                dry_run: false
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "import",
+            "/tmp/votes.txt",
+            "--resume",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Import {
+                path: PathBuf::from(r"/tmp/votes.txt"),
+                resume: true,
+                dry_run: false
+            })
+        );
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "import", "votes.csv", "--resume", "--dry-run"])
+            .is_err());
+    }
+
+    #[test]
+    fn export_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "--format",
+            "json",
+            "export",
+            "/tmp/votes.json",
+            "--force",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Export {
+                path: PathBuf::from(r"/tmp/votes.json"),
+                force: true
+            })
+        );
+        let cmd = args.cmd.unwrap();
+        assert!(cmd.supports_format(OutputFormat::Csv));
+        assert!(!cmd.supports_format(OutputFormat::Table));
+    }
+
+    #[test]
+    fn doctor_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "doctor"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Doctor));
+        assert!(Commands::Doctor.supports_format(OutputFormat::Json));
+    }
+
+    #[test]
+    fn revote_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "revote"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Revote { dry_run: false }));
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "revote",
+            "--dry-run",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Revote { dry_run: true }));
+    }
+
+    #[test]
+    fn comment_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "comment",
+            "pkg1",
+            "Thank you!",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Comment {
+                package: "pkg1".to_owned(),
+                text: "Thank you!".to_owned()
+            })
+        );
+
+        // Text is required
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "comment", "pkg1"])
+            .is_err());
+    }
+
+    #[test]
+    fn import_session_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "import-session",
+            "--aursid",
+            "abc123",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::ImportSession {
+                aursid: Some("abc123".to_owned()),
+                cookies_txt: None
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "import-session",
+            "--cookies-txt",
+            "cookies.txt",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::ImportSession {
+                aursid: None,
+                cookies_txt: Some(PathBuf::from("cookies.txt"))
+            })
+        );
+
+        // One of them is required, not both
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "import-session"])
+            .is_err());
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec![
+                "test",
+                "import-session",
+                "--aursid",
+                "abc123",
+                "--cookies-txt",
+                "cookies.txt"
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn info_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "info", "pkg1", "pkg2"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Info {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()]
+            })
+        );
+        assert!(Commands::Info { packages: vec![] }.supports_format(OutputFormat::Json));
+    }
+
+    #[test]
+    fn notify_cmd() {
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app()
+                .get_matches_from(vec!["test", "notify", "pkg1", "pkg2", "--off"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Notify {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()],
+                off: true
+            })
+        );
+
+        // Packages are required
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "notify"])
+            .is_err());
+    }
+
+    #[test]
+    fn flag_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "flag",
+            "pkg1",
+            "Version 2.0 is released",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Flag {
+                package: "pkg1".to_owned(),
+                reason: "Version 2.0 is released".to_owned()
+            })
+        );
+
+        // Reason is required
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "flag", "pkg1"])
+            .is_err());
+
+        let args = Arguments::from_arg_matches(
+            &Arguments::into_app().get_matches_from(vec!["test", "unflag", "pkg1"]),
+        )
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::Unflag {
+                package: "pkg1".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn ensure_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "ensure-voted",
+            "pkg1",
+            "pkg2",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::EnsureVoted {
+                packages: vec!["pkg1".to_owned(), "pkg2".to_owned()]
+            })
+        );
+
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "ensure-unvoted",
+            "pkg1",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::EnsureUnvoted {
+                packages: vec!["pkg1".to_owned()]
+            })
+        );
+
+        // Packages are required
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "ensure-voted"])
+            .is_err());
+    }
+
+    #[test]
+    fn complete_packages_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "__complete-packages",
+            "pac",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(
+            args.cmd,
+            Some(Commands::CompletePackages {
+                prefix: "pac".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn completions_cmd() {
+        let args = Arguments::from_arg_matches(&Arguments::into_app().get_matches_from(vec![
+            "test",
+            "completions",
+            "fish",
+        ]))
+        .expect("Paring argument");
+        assert_eq!(args.cmd, Some(Commands::Completions { shell: Shell::Fish }));
+        assert!(Arguments::into_app()
+            .try_get_matches_from(vec!["test", "completions", "tcsh"])
+            .is_err());
+    }
 }
@@ -1,30 +1,52 @@
 use anyhow::{anyhow, Result};
 use cookie::{Cookie, CookieJar, Expiration};
-use lazy_static::lazy_static;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use reqwest::{
-    blocking::{Client, Response},
-    header, redirect, StatusCode, Url,
+    blocking::{Client, ClientBuilder, RequestBuilder, Response},
+    header, redirect, tls, Proxy, StatusCode, Url,
 };
 use scraper::{Html, Selector};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, File},
-    io::{BufRead, BufReader, Write},
-    os::unix::fs::OpenOptionsExt,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
     path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use time::OffsetDateTime;
-use tracing::debug;
+use tracing::{debug, info, warn};
 
-lazy_static! {
-    static ref AUR_URL: String = String::from("https://aur.archlinux.org");
-    static ref AUR_URL_LOGIN: String = AUR_URL.to_string() + "/login?next=/";
-    static ref AUR_URL_PKG_PAGE: String = AUR_URL.to_string() + "/packages/<PKG>";
-    static ref AUR_URL_PKG_INFO: String = AUR_URL.to_string() + "/rpc?v=5&type=info";
-    static ref AUR_URL_SORT_VOTED_PKG: String =
-        AUR_URL.to_string() + "/packages/?O=<OFFSET>&SeB=nd&SB=w&SO=d&PP=250&do_Search=Go";
-}
+use crate::error::{AccountSuspended, AurError, ErrorKind as AurErrorKind, Maintenance, Stop};
+use crate::helper::is_file_secure;
+use crate::httpcache::{CacheEntry, HttpCache};
+use crate::stats;
+use crate::votedcache::VotedCache;
+
+/// Default base URL of AUR, see [`AurUrl`]
+pub const DEFAULT_AUR_URL: &str = "https://aur.archlinux.org";
+
+/// Paths of RPC queries, relative to base URL of AUR
+const AUR_PATH_RPC_INFO: &str = "/rpc?v=5&type=info";
+const AUR_PATH_RPC_SUGGEST: &str = "/rpc?v=5&type=suggest";
+const AUR_PATH_RPC_SEARCH_MAINTAINER: &str = "/rpc?v=5&type=search&by=maintainer";
+
+/// Path of login page, relative to base URL of AUR
+const AUR_PATH_LOGIN: &str = "/login?next=/";
+
+/// Path of package page, relative to base URL of AUR
+const AUR_PATH_PKG_PAGE: &str = "/packages/<PKG>";
+
+/// Path of packages sorted by voted, relative to base URL of AUR
+const AUR_PATH_SORT_VOTED_PKG: &str =
+    "/packages/?O=<OFFSET>&SeB=nd&SB=w&SO=d&PP=<PAGE_SIZE>&do_Search=Go";
 
 static APP_USER_AGENT: &str = concat!(
     env!("CARGO_PKG_NAME"),
@@ -38,8 +60,68 @@ static APP_USER_AGENT: &str = concat!(
 /// See: https://wiki.archlinux.org/index.php/Aurweb_RPC_interface#Limitations
 const PACKAGE_QUERY_LIMIT: usize = 160;
 
+/// Largest number of RPC info queries at once, each of up to `PACKAGE_QUERY_LIMIT` packages.
+/// The number of requests counted by rate limit of RPC is the same, only sooner.
+const CONCURRENT_INFO_QUERIES: usize = 4;
+
+/// Allowed number of packages per page of https://aur.archlinux.org/packages/
+pub const PAGE_SIZES: [usize; 3] = [50, 100, 250];
+
+/// Characters of package name to percent-encode in URL, all except unreserved characters
+const PKG_NAME_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Default number of packages per page when listing voted packages
+pub const DEFAULT_PAGE_SIZE: usize = 250;
+
+/// Largest number of voted packages pages to fetch at once
+pub const MAX_CONCURRENT_PAGES: usize = 8;
+
+/// Default number of packages to (un)vote/check at once
+pub const DEFAULT_CONCURRENT_VOTES: usize = 4;
+
+/// Largest number of packages to (un)vote/check at once
+pub const MAX_CONCURRENT_VOTES: usize = 8;
+
+/// Default number of retries of a request on connection errors and server errors
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Default delay in milliseconds before the first retry, doubled before each next one
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 500;
+
+/// Default seconds to wait for connection to AUR
+pub const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+
+/// Default seconds to wait for a whole request to AUR, from connecting until body is read
+pub const DEFAULT_REQUEST_TIMEOUT: u64 = 30;
+
+/// Shortest prefix to query package name suggestions, shorter prefix yields too many results
+const SUGGEST_MIN_PREFIX: usize = 2;
+
+/// Language of AUR pages, scraping relies on English texts, e.g. `orphan`
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Names of AUR cookies saved to cookie file
+const AUR_COOKIES: [&str; 3] = ["AURTZ", "AURLANG", "AURSID"];
+
+/// Package whose page is fetched by `--verify-login`
+const VERIFY_LOGIN_PKG: &str = "aur-thumbsup";
+
+/// Pause between cookie login attempts after a transient failure
+const COOKIE_LOGIN_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+fn default_cookie_login_retries() -> u32 {
+    2
+}
+
+/// Number of columns in result table of https://aur.archlinux.org/packages/ page
+const RESULT_TABLE_COLUMNS: usize = 9;
+
 /// For result table from https://aur.archlinux.org/packages/ page
-#[derive(Default, Deserialize, PartialEq, Debug)]
+#[derive(Default, Deserialize, Serialize, Clone, PartialEq, Debug)]
 pub struct AurPackageResultItem {
     #[serde(rename = "Name")]
     pub name: String,
@@ -53,10 +135,20 @@ pub struct AurPackageResultItem {
     #[serde(rename = "Popularity?")]
     pub popularity: f64,
 
-    #[serde(rename = "Voted", default, deserialize_with = "de_from_yes")]
+    #[serde(
+        rename = "Voted",
+        default,
+        deserialize_with = "de_from_yes",
+        serialize_with = "ser_to_yes"
+    )]
     pub voted: bool,
 
-    #[serde(rename = "Notify", default, deserialize_with = "de_from_yes")]
+    #[serde(
+        rename = "Notify",
+        default,
+        deserialize_with = "de_from_yes",
+        serialize_with = "ser_to_yes"
+    )]
     pub notify: bool,
 
     #[serde(rename = "Description")]
@@ -74,6 +166,21 @@ where
     Ok(s == "Yes")
 }
 
+/// As in result table, so serialized packages are read back by [`de_from_yes`]
+fn ser_to_yes<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(if *value { "Yes" } else { "No" })
+}
+
+impl AurPackageResultItem {
+    /// Package has no maintainer
+    pub fn is_orphan(&self) -> bool {
+        self.maintainer == "orphan"
+    }
+}
+
 pub type AurPackageResults = Vec<AurPackageResultItem>;
 
 pub trait Extraction<T> {
@@ -96,43 +203,65 @@ impl Extraction<AurPackageResults> for AurPackageResults {
         };
 
         let table = html.select(&table_selector);
-        for row in table {
+        for (index, row) in table.enumerate() {
             let cols: Vec<String> = row
                 .select(&td_selector)
-                .into_iter()
                 .map(|td| td.inner_html().trim().to_owned())
                 .collect();
 
-            let name: String = match Html::parse_fragment(cols[1].as_str())
+            if cols.len() < 2 {
+                warn!("Skip package row {}: no package name", index);
+                continue;
+            }
+
+            if cols.len() < RESULT_TABLE_COLUMNS {
+                warn!(
+                    "Package row {} has {} of {} columns",
+                    index,
+                    cols.len(),
+                    RESULT_TABLE_COLUMNS
+                );
+            }
+
+            // Missing trailing columns are treated as empty
+            let col = |n: usize| -> &str { cols.get(n).map_or("", |c| c.as_str()) };
+
+            let name: String = match Html::parse_fragment(col(1))
                 .select(&Selector::parse("a").expect("Paring selector"))
                 .next()
             {
-                Some(n) => n.inner_html(),
-                None => cols[1].to_owned(),
+                Some(n) => n.text().collect(),
+                None => html_text(col(1)),
             };
 
-            let version: String = cols[2].to_owned();
-            let votes: u64 = cols[3].parse::<u64>()?;
-            let popularity: f64 = cols[4].parse::<f64>()?;
-            let voted: bool = cols[5] == "Yes";
-            let notify: bool = cols[6] == "Yes";
-            let description: String = cols[7].to_owned();
+            let version: String = col(2).to_owned();
+            let votes: u64 = match col(3) {
+                "" => 0,
+                v => v.parse::<u64>()?,
+            };
+            let popularity: f64 = match col(4) {
+                "" => 0.0,
+                p => p.parse::<f64>()?,
+            };
+            let voted: bool = col(5) == "Yes";
+            let notify: bool = col(6) == "Yes";
+            let description: String = html_text(col(7));
 
-            let maintainer: String = match Html::parse_fragment(cols[8].as_str())
+            let maintainer: String = match Html::parse_fragment(col(8))
                 .select(&Selector::parse("a").expect("Paring selector"))
                 .next()
             {
                 // Maintainer with link
                 // <a href="/account/NAME" title="View account information for NAME">NAME</a>
-                Some(m) => m.inner_html(),
+                Some(m) => m.text().collect(),
 
                 // Orphan
                 // <span>orphan</span>
-                None => match Html::parse_fragment(cols[8].as_str())
+                None => match Html::parse_fragment(col(8))
                     .select(&Selector::parse("span").expect("Paring selector"))
                     .next()
                 {
-                    Some(s) => s.inner_html(),
+                    Some(s) => s.text().collect(),
                     None => String::new(),
                 },
             };
@@ -153,27 +282,313 @@ impl Extraction<AurPackageResults> for AurPackageResults {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+/// Text of html fragment, with HTML entities decoded, e.g. `&amp;` to `&`
+fn html_text(fragment: &str) -> String {
+    Html::parse_fragment(fragment)
+        .root_element()
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
 pub enum VoteResult {
     Voted,
     AlreadyVoted,
+    #[serde(rename = "unvoted")]
     UnVoted,
+    #[serde(rename = "already-unvoted")]
     AlreadyUnVoted,
     NotAvailable,
     Failed,
+
+    /// AUR refused to (un)vote this fast, with its advice
+    #[serde(skip_deserializing)]
+    Throttled(String),
+}
+
+/// Serialized as its name, e.g. `already-voted`. Advice of `Throttled` is logged, not serialized.
+impl Serialize for VoteResult {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl VoteResult {
+    pub fn name(&self) -> &'static str {
+        match self {
+            VoteResult::Voted => "voted",
+            VoteResult::AlreadyVoted => "already-voted",
+            VoteResult::UnVoted => "unvoted",
+            VoteResult::AlreadyUnVoted => "already-unvoted",
+            VoteResult::NotAvailable => "not-available",
+            VoteResult::Failed => "failed",
+            VoteResult::Throttled(_) => "throttled",
+        }
+    }
+
+    /// Package is not (un)voted as requested, and should be tried again later
+    pub fn is_failed(&self) -> bool {
+        matches!(self, VoteResult::Failed | VoteResult::Throttled(_))
+    }
+}
+
+/// Result of flag/unflag package out-of-date
+#[derive(PartialEq, Eq, Debug)]
+pub enum FlagResult {
+    Flagged,
+    AlreadyFlagged,
+    Unflagged,
+    NotFlagged,
+}
+
+/// Result of enable/disable notifications of new comments on package
+#[derive(PartialEq, Eq, Debug)]
+pub enum NotifyResult {
+    Enabled,
+    AlreadyEnabled,
+    Disabled,
+    AlreadyDisabled,
+}
+
+/// Result of logout of session in cookie file
+#[derive(PartialEq, Eq, Debug)]
+pub enum LogoutResult {
+    LoggedOut,
+
+    /// Session was already invalid, e.g. expired or logged out elsewhere
+    SessionInvalid,
+
+    NoCookieFile,
+}
+
+/// Form found in a page, with its hidden inputs, e.g. token
+#[derive(PartialEq, Debug)]
+struct PageForm {
+    action: String,
+    fields: Vec<(String, String)>,
+}
+
+/// Package page returned after (un)vote
+#[derive(PartialEq, Debug)]
+enum VoteResponse {
+    /// Vote status is changed
+    Changed,
+
+    /// Still logged in, but vote status is not changed, with errors/notices of AUR if any
+    Unchanged(Option<String>),
+
+    /// Session became invalid, e.g. page asks to login
+    NotLoggedIn,
+
+    /// Voting too fast, with notice of AUR
+    Throttled(String),
 }
 
-#[derive(Default, Deserialize, Serialize, PartialEq, Debug)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Account {
     pub user: String,
+
+    /// Empty with `pass_cmd`, or to login using cookie file alone
+    #[serde(default)]
     pub pass: String,
+
+    /// Shell command printing pass, e.g. `pass show aur`, instead of `pass`.
+    /// Run only when login using user, pass is needed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pass_cmd: Option<String>,
+
+    /// `~` and environment variables are expanded when loaded from configuration file
     pub cookie_file: PathBuf,
+
+    /// Number of cookie login retries on network errors before giving up
+    #[serde(default = "default_cookie_login_retries")]
+    pub cookie_login_retries: u32,
+
+    /// How to login to AUR
+    #[serde(default, skip_serializing_if = "AuthMethod::is_default")]
+    pub auth_method: AuthMethod,
+}
+
+/// Method of login to AUR
+#[derive(Deserialize, Serialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthMethod {
+    /// Login using saved session in cookie file, fallback to user, pass if session is stale
+    #[default]
+    Cookie,
+
+    /// Always login using user, pass. Session is still saved to cookie file.
+    Password,
+}
+
+impl AuthMethod {
+    fn is_default(&self) -> bool {
+        *self == AuthMethod::default()
+    }
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Account {
+            user: String::new(),
+            pass: String::new(),
+            pass_cmd: None,
+            cookie_file: PathBuf::new(),
+            cookie_login_retries: default_cookie_login_retries(),
+            auth_method: AuthMethod::default(),
+        }
+    }
+}
+
+impl Account {
+    /// Check if pass is configured, directly or by `pass_cmd`
+    pub fn has_pass(&self) -> bool {
+        !self.pass.is_empty() || self.pass_cmd.is_some()
+    }
+
+    /// Pass, or trimmed stdout of `pass_cmd` run by `sh -c`
+    pub fn password(&self) -> Result<String> {
+        let cmd = match &self.pass_cmd {
+            Some(cmd) => cmd,
+            None => return Ok(self.pass.to_owned()),
+        };
+        debug!("Run `pass_cmd` to get pass");
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|err| AurError::config(anyhow!("Unable to run `pass_cmd`: {}", err)))?;
+        if !output.status.success() {
+            return Err(AurError::config(anyhow!(
+                "`pass_cmd` failed with {}",
+                output.status
+            )));
+        }
+        let pass = String::from_utf8(output.stdout)
+            .map_err(|_| AurError::config(anyhow!("Output of `pass_cmd` is not UTF-8")))?
+            .trim()
+            .to_owned();
+        if pass.is_empty() {
+            return Err(AurError::config(anyhow!("`pass_cmd` printed no pass")));
+        }
+        Ok(pass)
+    }
+}
+
+/// Check if error is caused by network or server side, i.e. not by stale session
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(err) => {
+            err.is_timeout()
+                || err.is_connect()
+                || err.is_request()
+                || err.is_body()
+                || err.status().is_some_and(|s| s.is_server_error())
+        }
+        None => false,
+    }
+}
+
+/// Options of AUR session, usually from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct SessionOptions {
+    /// Number of packages per page when listing voted packages, see [`PAGE_SIZES`]
+    pub page_size: Option<usize>,
+
+    /// Number of pages to fetch at once when listing voted packages
+    pub concurrent_pages: Option<usize>,
+
+    /// Number of packages to (un)vote/check at once
+    pub concurrent_votes: Option<usize>,
+
+    /// Do not login using saved session in cookie file
+    pub no_login_cache: bool,
+
+    /// Seconds to stop starting (un)vote/check of next package, see [`Authentication::set_deadline`]
+    pub deadline: Option<u64>,
+
+    /// Verify login by fetching a package page, see [`Authentication::set_verify_login`]
+    pub verify_login: bool,
+
+    /// Show progress bar of (un)vote/check, see [`Authentication::set_progress`]
+    pub progress: bool,
+
+    /// Fetch voted list again instead of using its cache, see [`Authentication::set_voted_cache`]
+    pub refresh: bool,
+
+    /// Milliseconds between requests, see [`Authentication::set_request_delay`]
+    pub request_delay_ms: Option<u64>,
 }
 
 #[derive(Debug)]
 pub struct Authentication {
     session: Option<Client>,
     cookie_jar: CookieJar,
+    page_size: usize,
+    concurrent_pages: usize,
+    concurrent_votes: usize,
+    client: AurClient,
+    aur_url: AurUrl,
+    retry: Retry,
+    login_cache: bool,
+    verify_login: bool,
+
+    /// Keep listing voted packages until an empty page, instead of the first not voted package
+    full_scan: bool,
+
+    /// AURLANG sent with every request, regardless of language of account
+    language: String,
+
+    /// Login with `remember_me`, for a long-lived session saved to cookie file
+    remember_me: bool,
+
+    /// Remove cookie file once AUR does not accept its session
+    prune_invalid_cookie: bool,
+
+    /// Cache of voted list and package pages, revalidated by conditional requests
+    http_cache: Option<HttpCache>,
+
+    /// Voted packages saved by [`Authentication::list_voted_pkgs_cached`], removed on vote changes
+    voted_cache: Option<VotedCache>,
+
+    /// Account of last login, used to re-login when session became invalid
+    account: Option<Account>,
+
+    /// Time to stop starting (un)vote/check of next package
+    deadline: Option<Instant>,
+
+    /// Number of requests to AUR after which no (un)vote/check of next package is started
+    max_requests: Option<usize>,
+
+    /// Number of requests to AUR made so far, including login
+    requests: AtomicUsize,
+
+    /// Why (un)vote/check stopped before all packages are processed
+    stopped: Mutex<Option<Stop>>,
+
+    /// Pause between (un)vote/check of packages and pages of voted list, to stay under rate limit
+    request_delay: Duration,
+
+    /// Time of last request paced by `request_delay`
+    last_request: Mutex<Option<Instant>>,
+
+    /// Called with numbers of processed and all packages as (un)vote/check goes on
+    progress: Option<fn(usize, usize)>,
+
+    /// Package base of packages whose info was queried, to (un)vote each base once
+    pkg_bases: Mutex<HashMap<String, String>>,
+}
+
+impl Default for Authentication {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Authentication {
@@ -181,305 +596,1289 @@ impl Authentication {
         Authentication {
             session: None,
             cookie_jar: CookieJar::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+            concurrent_pages: 1,
+            concurrent_votes: DEFAULT_CONCURRENT_VOTES,
+            client: AurClient::default(),
+            aur_url: AurUrl::default(),
+            retry: Retry::default(),
+            login_cache: true,
+            verify_login: false,
+            full_scan: false,
+            language: DEFAULT_LANGUAGE.to_owned(),
+            remember_me: true,
+            prune_invalid_cookie: true,
+            http_cache: None,
+            voted_cache: None,
+            account: None,
+            deadline: None,
+            max_requests: None,
+            requests: AtomicUsize::new(0),
+            stopped: Mutex::new(None),
+            request_delay: Duration::ZERO,
+            last_request: Mutex::new(None),
+            progress: None,
+            pkg_bases: Mutex::new(HashMap::new()),
         }
     }
 
-    pub fn login(&mut self, account: &Account) -> Result<()> {
-        if self.login_with_cookie_file(&account.cookie_file).is_err() {
-            debug!("Failed to login using cookies.");
-
-            self.login_with_user_pass(account)?;
-            debug!("Logged in using user, pass.");
-
-            self.save_cookie(&account.cookie_file)?;
-            debug!(
-                "Save cookie to `{}`",
-                &account.cookie_file.to_str().expect("To str")
-            );
+    /// Set number of packages per page when listing voted packages
+    pub fn set_page_size(&mut self, page_size: usize) -> Result<()> {
+        if !PAGE_SIZES.contains(&page_size) {
+            return Err(anyhow!(
+                "Page size must be one of {:?}, got {}.",
+                PAGE_SIZES,
+                page_size
+            ));
         }
+        self.page_size = page_size;
+        Ok(())
+    }
 
-        debug!("Logged in using cookies.");
+    /// Set number of voted packages pages to fetch at once, after the first page
+    pub fn set_concurrent_pages(&mut self, concurrent_pages: usize) -> Result<()> {
+        if !(1..=MAX_CONCURRENT_PAGES).contains(&concurrent_pages) {
+            return Err(anyhow!(
+                "Concurrent pages must be between 1 and {}, got {}.",
+                MAX_CONCURRENT_PAGES,
+                concurrent_pages
+            ));
+        }
+        self.concurrent_pages = concurrent_pages;
         Ok(())
     }
 
-    pub fn is_login(&self) -> Result<()> {
-        if self.session.is_some() {
-            return Ok(());
+    /// Set number of packages to (un)vote/check at once, their pages are fetched concurrently
+    pub fn set_concurrent_votes(&mut self, concurrent_votes: usize) -> Result<()> {
+        if !(1..=MAX_CONCURRENT_VOTES).contains(&concurrent_votes) {
+            return Err(anyhow!(
+                "Concurrent votes must be between 1 and {}, got {}.",
+                MAX_CONCURRENT_VOTES,
+                concurrent_votes
+            ));
         }
-        Err(anyhow!("Not logged in."))
+        self.concurrent_votes = concurrent_votes;
+        Ok(())
     }
 
-    pub fn check_vote(&self, packages: &[String]) -> Result<Vec<(String, Option<bool>)>> {
-        self.is_login()?;
-        let session = self.session.as_ref().expect("as ref");
+    /// Set hook called with numbers of processed and all packages after each package
+    /// is (un)voted/checked, e.g. to show a progress bar
+    pub fn set_progress(&mut self, progress: fn(usize, usize)) {
+        self.progress = Some(progress);
+    }
 
-        let mut voted: Vec<(String, Option<bool>)> = Vec::new();
-        for pkg in packages.iter() {
-            let url = Url::parse(AUR_URL_PKG_PAGE.replace("<PKG>", pkg).as_str())?;
-            let response = session.get(url).send()?;
-            let page = Html::parse_document(response.text()?.as_str());
-            let vote_status = self.is_vote_html(&page)?;
-            voted.push((pkg.to_owned(), vote_status));
+    /// Report progress of `done` of `total` packages to progress hook, if any
+    fn report_progress(&self, done: usize, total: usize) {
+        if let Some(progress) = self.progress {
+            progress(done, total);
         }
+    }
 
-        Ok(voted)
+    /// Enable/Disable login using saved session in cookie file.
+    /// If disabled, always login using user, pass then overwrite cookie file.
+    pub fn set_login_cache(&mut self, enabled: bool) {
+        self.login_cache = enabled;
     }
 
-    pub fn vote(&self, packages: &[String]) -> Result<Vec<(String, VoteResult)>> {
-        self.is_login()?;
-        let session = self.session.as_ref().expect("as ref");
+    /// Enable/Disable verification after login, by fetching a package page with the new session
+    /// and finding its vote form, which is only rendered for a logged-in session.
+    /// It costs one more request, but catches a session whose home page only looks logged in.
+    pub fn set_verify_login(&mut self, enabled: bool) {
+        self.verify_login = enabled;
+    }
 
-        let mut result: Vec<(String, VoteResult)> = Vec::new();
-        for pkg in packages.iter() {
-            let url = Url::parse(AUR_URL_PKG_PAGE.replace("<PKG>", pkg).as_str())?;
-            let response = session.get(url).send()?;
-            let page = Html::parse_document(response.text()?.as_str());
-            if let Some(status) = self.is_vote_html(&page)? {
-                match status {
-                    true => result.push((pkg.to_owned(), VoteResult::AlreadyVoted)),
-                    false => {
-                        if let Err(err) = self.do_vote(pkg, true, &page) {
-                            debug!("{}", err);
-                            result.push((pkg.to_owned(), VoteResult::Failed));
-                            continue;
-                        }
-
-                        result.push((pkg.to_owned(), VoteResult::Voted));
-                    }
-                }
-            } else {
-                result.push((pkg.to_owned(), VoteResult::NotAvailable))
-            }
-        }
+    /// Enable/Disable full scan when listing voted packages. Listing relies on voted packages
+    /// sorted before the others, then stops at the first not voted package. Full scan does not,
+    /// it fetches every page of AUR packages, i.e. hundreds of requests, and keeps voted ones.
+    pub fn set_full_scan(&mut self, enabled: bool) {
+        self.full_scan = enabled;
+    }
 
-        Ok(result)
+    /// Enable/Disable `remember_me` of login using user, pass. Without it, AUR gives
+    /// a short-lived session, which is not saved to cookie file.
+    pub fn set_remember_me(&mut self, enabled: bool) {
+        self.remember_me = enabled;
     }
 
-    pub fn unvote(&self, packages: &[String]) -> Result<Vec<(String, VoteResult)>> {
-        self.is_login()?;
-        let session = self.session.as_ref().expect("as ref");
+    /// Enable/Disable removing cookie file when AUR does not accept its session,
+    /// so the next run does not try it again. Network errors never remove it.
+    pub fn set_prune_invalid_cookie(&mut self, enabled: bool) {
+        self.prune_invalid_cookie = enabled;
+    }
 
-        let mut result: Vec<(String, VoteResult)> = Vec::new();
-        for pkg in packages.iter() {
-            let url = Url::parse(AUR_URL_PKG_PAGE.replace("<PKG>", pkg).as_str())?;
-            let response = session.get(url).send()?;
-            let page = Html::parse_document(response.text()?.as_str());
-            if let Some(status) = self.is_vote_html(&page)? {
-                match status {
-                    true => {
-                        if let Err(err) = self.do_vote(pkg, false, &page) {
-                            debug!("{}", err);
-                            result.push((pkg.to_owned(), VoteResult::Failed));
-                            continue;
-                        }
-
-                        result.push((pkg.to_owned(), VoteResult::UnVoted));
-                    }
-                    false => result.push((pkg.to_owned(), VoteResult::AlreadyUnVoted)),
-                }
-            } else {
-                result.push((pkg.to_owned(), VoteResult::NotAvailable))
+    /// Stop starting (un)vote/check of next package after `budget` from now.
+    /// Packages not started are left out of results.
+    pub fn set_deadline(&mut self, budget: Duration) {
+        self.deadline = Some(Instant::now() + budget);
+    }
+
+    /// Wait at least `delay` between (un)vote/check of packages and pages of voted list,
+    /// even if they are processed at once
+    pub fn set_request_delay(&mut self, delay: Duration) {
+        self.request_delay = delay;
+    }
+
+    /// Wait until `request_delay` passed since the previous paced request
+    fn pace(&self) {
+        if self.request_delay.is_zero() {
+            return;
+        }
+        // Hold the lock while waiting, so concurrent requests are spaced too
+        let mut last_request = self.last_request.lock().expect("Lock last request");
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.request_delay {
+                thread::sleep(self.request_delay - elapsed);
             }
         }
+        *last_request = Some(Instant::now());
+    }
 
-        Ok(result)
+    fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
     }
 
-    pub fn list_voted_pkgs(&self) -> Result<AurPackageResults> {
-        self.is_login()?;
-        let session = self.session.as_ref().expect("as ref");
+    /// Stop starting (un)vote/check of next package after `max_requests` requests to AUR,
+    /// including login. A package being processed may still make its remaining requests.
+    pub fn set_max_requests(&mut self, max_requests: usize) {
+        self.max_requests = Some(max_requests);
+    }
 
-        let mut voted_pkgs = AurPackageResults::new();
-        let mut offset: i32 = -250;
-        loop {
-            offset += 250;
-            let url = Url::parse(
-                AUR_URL_SORT_VOTED_PKG
-                    .replace("<OFFSET>", offset.to_string().as_str())
-                    .as_str(),
-            )?;
-            let response = session.get(url).send()?;
-            let page = Html::parse_document(response.text()?.as_str());
-            let packages = AurPackageResults::from_html(&page)?;
-
-            if packages.is_empty() {
-                return Ok(voted_pkgs);
-            }
+    fn count_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
 
-            for pkg in packages {
-                if !pkg.voted {
-                    return Ok(voted_pkgs);
-                }
-                voted_pkgs.push(pkg);
-            }
-        }
+    fn is_request_cap_reached(&self) -> bool {
+        self.max_requests
+            .is_some_and(|max| self.requests.load(Ordering::Relaxed) >= max)
     }
 
-    pub(self) fn login_with_user_pass(&mut self, account: &Account) -> Result<()> {
-        debug!("Attempt to login using user and password.");
+    /// Check if (un)vote/check should stop before `pkg`, and remember why
+    fn should_stop(&self, pkg: &str) -> bool {
+        let (stop, reason) = if self.is_past_deadline() {
+            (Stop::Deadline, "Deadline")
+        } else if self.is_request_cap_reached() {
+            (Stop::RequestCap, "Request cap")
+        } else {
+            return false;
+        };
+        debug!("{} reached, stop before `{}`", reason, pkg);
+        *self.stopped.lock().expect("Lock stopped") = Some(stop);
+        true
+    }
 
-        let login_url = Url::parse_with_params(
-            &AUR_URL_LOGIN,
-            &[
-                ("user", account.user.as_str()),
-                ("passwd", account.pass.as_str()),
-                ("remember_me", "on"),
-            ],
-        )?;
-        debug!("Login URL: {login_url}");
+    /// Why the last (un)vote/check stopped before all packages are processed, if it did
+    pub fn stopped(&self) -> Option<Stop> {
+        *self.stopped.lock().expect("Lock stopped")
+    }
 
-        // Stop redirect to https://aur.archlinux.org/ after logged in
-        let login_no_redirect = redirect::Policy::custom(|attempt| {
-            if attempt.status() == StatusCode::FOUND
-                && attempt.url().to_string() == (AUR_URL.to_string() + "/")
-            {
-                return attempt.stop();
+    /// Packages of `batch` to start, up to the one (un)vote/check should stop before.
+    /// Page request of each package to `fetch` is counted as it starts.
+    fn start_batch<'a, P>(&self, batch: &'a [String], fetch: P) -> &'a [String]
+    where
+        P: Fn(&str) -> bool,
+    {
+        for (i, pkg) in batch.iter().enumerate() {
+            if !fetch(pkg) {
+                continue;
             }
-            redirect::Policy::default().redirect(attempt)
-        });
-        let login_client = Client::builder()
-            .user_agent(APP_USER_AGENT)
-            .cookie_store(true)
-            .redirect(login_no_redirect)
-            .gzip(true)
-            .http2_prior_knowledge()
-            .use_rustls_tls()
-            .build()?;
-        let login_response = login_client.get(login_url).send()?;
-        debug!("Login response: {login_response:?}");
-
-        // Login success
-        if login_response.status() == StatusCode::FOUND
-            && login_response
-                .url()
-                .to_string()
-                .contains(&AUR_URL.to_string())
-        {
-            // Get AURSID for login cookie
-            if let Some(aursid) = login_response.headers().get(header::SET_COOKIE) {
-                let cookie_str = aursid.to_str()?.to_owned();
-                let mut c = Cookie::parse(cookie_str)?;
-                c.set_domain("aur.archlinux.org");
-                self.cookie_jar.add(c);
+            if self.should_stop(pkg) {
+                return &batch[..i];
+            }
+            self.count_request();
+        }
+        batch
+    }
 
-                // Access https://aur.archlinux.org/ with AURSID to get another cookies
-                let (response, _) = self.login_with_cookies()?;
+    /// Set language of AUR pages, sent as AURLANG cookie instead of the saved one
+    pub fn set_language(&mut self, language: &str) {
+        self.language = language.to_owned();
+    }
 
-                // Get AURTZ, AURLANG cookie
-                let aur_cookies = response.headers().get_all(header::SET_COOKIE);
-                for c in aur_cookies.iter() {
-                    let cookie_str = c.to_str()?.to_owned();
-                    let mut cookie = Cookie::parse(cookie_str)?;
-                    cookie.set_domain("aur.archlinux.org");
-                    self.cookie_jar.add(cookie);
-                }
+    /// Cache pages of voted list and packages in `cache`, and revalidate them using
+    /// `If-None-Match`/`If-Modified-Since` instead of fetching them again
+    pub fn set_http_cache(&mut self, cache: HttpCache) {
+        self.http_cache = Some(cache);
+    }
 
-                // Re-login using cookies
-                let (response, session) = self.login_with_cookies()?;
-                let logged_page = Html::parse_document(response.text()?.as_str());
-                self.is_login_html(&logged_page)?;
-                self.session = Some(session);
+    /// Use voted packages saved in `cache` by [`Authentication::list_voted_pkgs_cached`].
+    /// The cache is removed once a vote is changed.
+    pub fn set_voted_cache(&mut self, cache: VotedCache) {
+        self.voted_cache = Some(cache);
+    }
 
-                return Ok(());
-            }
+    /// Set minimum TLS version of connections to AUR: 1.2 or 1.3
+    pub fn set_min_tls_version(&mut self, version: &str) -> Result<()> {
+        self.client.set_min_tls_version(version)
+    }
 
-            return Err(anyhow!("Login failed: no cookie found."));
-        }
+    /// Connect to AUR through proxy `url`, instead of proxy from environment variables
+    pub fn set_proxy(&mut self, url: &str) -> Result<()> {
+        self.client.set_proxy(url)
+    }
 
-        self.session = None;
+    /// Set seconds to wait for connection, and for a whole request, see [`AurClient::set_timeouts`]
+    pub fn set_timeouts(&mut self, connect: u64, request: u64) -> Result<()> {
+        self.client.set_timeouts(connect, request)
+    }
 
-        if !login_response.status().is_success() {
-            return Err(anyhow!("Unable to access `{}`", &AUR_URL_LOGIN.to_string()));
-        }
+    /// Set base URL of AUR, e.g. of a self-hosted aurweb instance. Cookies are of its host.
+    pub fn set_aur_url(&mut self, url: &str) -> Result<()> {
+        self.aur_url = AurUrl::parse(url)?;
+        Ok(())
+    }
 
-        // Login failed, get error messages
-        let page = Html::parse_document(login_response.text()?.as_str());
-        let error_list = LoginErrorList::from_html(&page)?;
-        if !error_list.errors.is_empty() {
-            return Err(anyhow!("Login failed: {}", error_list.errors.join(", ")));
-        }
+    /// Base URL of AUR of this session
+    pub fn aur_url(&self) -> &AurUrl {
+        &self.aur_url
+    }
 
-        Err(anyhow!("Login failed"))
+    /// Retry GET requests up to `retries` times on connection errors and server errors,
+    /// waiting `base_delay` before the first retry, then twice as long before each next one
+    pub fn set_retry(&mut self, retries: u32, base_delay: Duration) {
+        self.retry = Retry {
+            retries,
+            base_delay,
+        };
     }
 
-    pub(self) fn login_with_cookie_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        debug!("Attemp to login using cookies.");
+    /// Send request, retry on connection errors and server errors, see [`Authentication::set_retry`]
+    fn send_with_retry(&self, request: RequestBuilder) -> Result<Response> {
+        send_retrying(request, &self.retry, &self.requests)
+    }
 
-        // Load cookies from file
-        let cookie_file = File::open(path)?;
-        let reader = BufReader::new(cookie_file);
-        for line in reader.lines() {
-            let c = Cookie::parse(line?)?;
-            self.cookie_jar.add(c);
+    /// Explain connection error which may be caused by enforced minimum TLS version
+    fn tls_hint(&self, err: &reqwest::Error) -> Option<String> {
+        match &self.client.min_tls_version {
+            Some(version) if err.is_connect() => Some(format!(
+                "TLS handshake may fail because `min_tls_version` is {}",
+                version
+            )),
+            _ => None,
         }
+    }
 
-        let (response, session) = self.login_with_cookies()?;
-        let logged_page = Html::parse_document(response.text()?.as_str());
-        self.is_login_html(&logged_page)?;
-        self.session = Some(session);
+    /// Login using `auth_method` of account
+    pub fn login(&mut self, account: &Account) -> Result<()> {
+        let method = if self.login_cache {
+            account.auth_method
+        } else {
+            info!("Bypass login using cookie file.");
+            AuthMethod::Password
+        };
+
+        match method {
+            AuthMethod::Cookie => self.login_cookie(account)?,
+            AuthMethod::Password => self.login_password(account)?,
+        }
+        if self.verify_login {
+            self.verify_session()?;
+        }
+        self.account = Some(account.clone());
         Ok(())
     }
 
-    pub(self) fn login_with_cookies(&mut self) -> Result<(Response, Client)> {
-        // Add cookies to headers, ordering is matter
-        let mut headers = header::HeaderMap::new();
-        // AURTZ
-        if let Some(aurtz) = self.cookie_jar.get("AURTZ") {
-            if let Some(expire_time) = aurtz.expires() {
-                match expire_time {
-                    Expiration::DateTime(d) => {
-                        if d.unix_timestamp() < OffsetDateTime::now_utc().unix_timestamp() {
-                            debug!("Cookies were expired.");
-                            return Err(anyhow!("Cookies were expired."));
-                        }
+    /// Verify session using AUR home page, like [`Authentication::is_logged_in`]
+    fn verify_session(&self) -> Result<()> {
+        let session = self.session.as_ref().expect("as ref");
+        self.count_request();
+        let response =
+            self.send_with_retry(session.get(pkg_page_url(&self.aur_url, VERIFY_LOGIN_PKG)?))?;
+        let page = Html::parse_document(&response_text(response)?);
+        self.verify_session_html(&page)?;
+        debug!("Verified login using page of `{}`", VERIFY_LOGIN_PKG);
+        Ok(())
+    }
+
+    pub(self) fn verify_session_html(&self, html: &Html) -> Result<()> {
+        check_maintenance(html)?;
+        match self.is_vote_html(html)? {
+            Some(_) => Ok(()),
+            None => Err(AurError::auth(anyhow!(
+                "Login verification failed: AUR home page looks logged in, but page of `{}` has no vote form.",
+                VERIFY_LOGIN_PKG
+            ))),
+        }
+    }
+
+    /// Check that session is still logged in using AUR home page, otherwise login again
+    /// using user, pass of `account`, which also saves a new cookie file.
+    /// Call it before a long run of votes, cookies may expire in between.
+    pub fn ensure_logged_in(&mut self, account: &Account) -> Result<()> {
+        if self.session.is_some() {
+            match self.is_logged_in() {
+                Ok(()) => return Ok(()),
+                Err(err) if is_invalid_session_error(&err) => {
+                    warn!("Session expired, login again.")
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        self.session = None;
+        self.login_password(account)?;
+        self.account = Some(account.clone());
+        Ok(())
+    }
+
+    /// Check login state of session using AUR home page
+    fn is_logged_in(&self) -> Result<()> {
+        let session = self.session.as_ref().expect("as ref");
+        self.count_request();
+        let response = self.send_with_retry(session.get(self.aur_url.join("/")?))?;
+        let page = Html::parse_document(&response_text(response)?);
+        self.is_login_html(&page)
+    }
+
+    /// User name of logged-in session from AUR home page, `user` of account may be an email
+    pub fn current_user(&self) -> Result<String> {
+        let session = self.session.as_ref().expect("as ref");
+        self.count_request();
+        let response = self.send_with_retry(session.get(self.aur_url.join("/")?))?;
+        let page = Html::parse_document(&response_text(response)?);
+        self.is_login_html(&page)?;
+        current_user_html(&page)?.ok_or_else(|| anyhow!("User name not found in AUR home page."))
+    }
+
+    /// Login again using user, pass of last login, after session became invalid
+    fn relogin(&mut self) -> Result<()> {
+        let account = match self.account.take() {
+            Some(account) => account,
+            None => return Err(AurError::auth(anyhow!("Not logged in."))),
+        };
+        self.session = None;
+        self.login_password(&account)?;
+        self.account = Some(account);
+        Ok(())
+    }
+
+    /// Login using saved session in cookie file, fallback to user, pass if session is stale
+    fn login_cookie(&mut self, account: &Account) -> Result<()> {
+        let mut retries: u32 = 0;
+        loop {
+            match self.login_with_cookie_file(&account.cookie_file) {
+                Ok(_) => {
+                    debug!("Logged in using cookies.");
+                    stats::record(retries + 1, true);
+                    return Ok(());
+                }
+
+                // Network error, session may still be valid. Do not fallback to user, pass.
+                Err(err) if is_transient_error(&err) => {
+                    if retries >= account.cookie_login_retries {
+                        stats::record(retries + 1, false);
+                        let hint = err
+                            .downcast_ref::<reqwest::Error>()
+                            .and_then(|err| self.tls_hint(err));
+                        let err = err.context("Unable to login using cookies");
+                        return Err(match hint {
+                            Some(hint) => err.context(hint),
+                            None => err,
+                        });
                     }
-                    Expiration::Session => (),
+                    retries += 1;
+                    debug!(
+                        "Failed to login using cookies: {}, retry {}/{}",
+                        err, retries, account.cookie_login_retries
+                    );
+                    thread::sleep(COOKIE_LOGIN_RETRY_DELAY);
+                }
+
+                // Stale session or no cookie file
+                Err(err) => {
+                    debug!("Failed to login using cookies: {}", err);
+                    if self.prune_invalid_cookie && is_invalid_session_error(&err) {
+                        remove_cookie_file(&account.cookie_file)?;
+                        info!(
+                            "Removed cookie file `{}`, its session is no longer valid",
+                            account.cookie_file.display()
+                        );
+                    }
+                    let result = self.login_password(account);
+                    stats::record(retries + 1, result.is_ok());
+                    return result;
                 }
             }
+        }
+    }
 
-            let code = aurtz.encoded().to_string();
-            headers.insert(header::COOKIE, code.parse()?);
+    /// Login using user, pass, then save session to cookie file
+    fn login_password(&mut self, account: &Account) -> Result<()> {
+        if !account.has_pass() {
+            return Err(AurError::auth(anyhow!(
+                "Session in cookie file is not valid, and pass is not configured. Import a new session using `import-session`."
+            )));
         }
-        // AURLANG
-        if let Some(aurlang) = self.cookie_jar.get("AURLANG") {
-            let code = aurlang.encoded().to_string();
-            headers.append(header::COOKIE, code.parse()?);
+        self.login_with_user_pass(account)?;
+        debug!("Logged in using user, pass.");
+
+        if !self.remember_me {
+            debug!("Short-lived session without remember_me, cookie file is not saved");
+            return Ok(());
         }
-        // AURSID
-        if let Some(aursid) = self.cookie_jar.get("AURSID") {
-            let code = aursid.encoded().to_string();
-            headers.append(header::COOKIE, code.parse()?);
+        self.save_cookie(&account.cookie_file)?;
+        debug!(
+            "Save cookie to `{}`",
+            &account.cookie_file.to_str().expect("To str")
+        );
+
+        Ok(())
+    }
+
+    /// Login using session of browser in `cookies`, then save it to cookie file of account
+    pub fn import_session(&mut self, account: &Account, cookies: &CookieJar) -> Result<()> {
+        if cookies.get("AURSID").is_none() {
+            return Err(anyhow!("No AURSID cookie of AUR to import."));
+        }
+        self.cookie_jar = CookieJar::new();
+        for cookie in cookies.iter() {
+            self.cookie_jar.add(cookie.to_owned());
         }
 
-        let session = Client::builder()
-            .user_agent(APP_USER_AGENT)
+        let (response, session) = self.login_with_cookies()?;
+        let logged_page = Html::parse_document(&response_text(response)?);
+        self.is_login_html(&logged_page)
+            .map_err(|err| err.context("Session to import is not logged in"))?;
+        self.session = Some(session);
+
+        self.save_cookie(&account.cookie_file)?;
+        self.account = Some(account.clone());
+        Ok(())
+    }
+
+    /// Remove cookie file of account, then login using user, pass and save a new cookie file.
+    /// The old cookie file is removed even if login fails.
+    pub fn renew_login(&mut self, account: &Account) -> Result<()> {
+        remove_cookie_file(&account.cookie_file)?;
+        self.login_password(account)?;
+        self.account = Some(account.clone());
+        Ok(())
+    }
+
+    /// Logout session in cookie file of account, then remove the cookie file.
+    /// Never login using user, pass. Cookie file is kept on network errors.
+    pub fn logout(&mut self, account: &Account) -> Result<LogoutResult> {
+        if !account.cookie_file.exists() {
+            return Ok(LogoutResult::NoCookieFile);
+        }
+        let result = match self.logout_cookie_session(&account.cookie_file) {
+            Ok(_) => LogoutResult::LoggedOut,
+            Err(err) if is_invalid_session_error(&err) => {
+                debug!("Session is not valid: {}", err);
+                LogoutResult::SessionInvalid
+            }
+            Err(err) => return Err(err),
+        };
+        self.session = None;
+        self.account = None;
+        remove_cookie_file(&account.cookie_file)?;
+        Ok(result)
+    }
+
+    /// Login using cookie file, then submit logout form of AUR home page
+    fn logout_cookie_session(&mut self, path: &Path) -> Result<()> {
+        for c in load_cookie_file(path)?.iter() {
+            self.cookie_jar.add(c.to_owned());
+        }
+        let (response, session) = self.login_with_cookies()?;
+        let page = Html::parse_document(&response_text(response)?);
+        self.is_login_html(&page)?;
+
+        let request = match self.extract_form(&page, "form[action^=\"/logout\"]")? {
+            Some(form) => session
+                .post(self.aur_url.join(&form.action)?)
+                .form(&form.fields),
+            // Older aurweb logs out by link
+            None => session.get(self.aur_url.join("/logout/")?),
+        };
+        self.count_request();
+        let response = request.send().map_err(send_error)?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Unable to logout: {}", response.status()));
+        }
+        let page = Html::parse_document(&response_text(response)?);
+        check_maintenance(&page)?;
+        match self.is_login_html(&page) {
+            Ok(_) => Err(anyhow!("Still logged in after logout.")),
+            Err(_) => Ok(()),
+        }
+    }
+
+    pub fn is_login(&self) -> Result<()> {
+        if self.session.is_some() {
+            return Ok(());
+        }
+        Err(AurError::auth(anyhow!("Not logged in.")))
+    }
+
+    /// Check vote status of packages, `on_result` is called as soon as each package is checked.
+    /// Packages in fresh voted cache, if any, are voted without fetching their pages.
+    pub fn check_vote<F>(
+        &self,
+        packages: &[String],
+        on_result: F,
+    ) -> Result<Vec<(String, Option<bool>)>>
+    where
+        F: FnMut(&(String, Option<bool>)) -> Result<()>,
+    {
+        let voted_pkgs = match self.voted_cache.as_ref().and_then(|cache| cache.load()) {
+            Some(voted_pkgs) => {
+                debug!(
+                    "Check vote using {} cached voted packages",
+                    voted_pkgs.len()
+                );
+                voted_names(voted_pkgs)
+            }
+            None => HashSet::new(),
+        };
+        self.check_vote_with(packages, &voted_pkgs, on_result)
+    }
+
+    /// Check vote status of packages from voted list, fetched once or from voted cache.
+    /// Only packages not in voted list are fetched one by one, to tell unvoted from not available.
+    pub fn check_vote_via_voted_list<F>(
+        &self,
+        packages: &[String],
+        on_result: F,
+    ) -> Result<Vec<(String, Option<bool>)>>
+    where
+        F: FnMut(&(String, Option<bool>)) -> Result<()>,
+    {
+        let voted_pkgs = voted_names(self.list_voted_pkgs_cached()?);
+        self.check_vote_with(packages, &voted_pkgs, on_result)
+    }
+
+    /// Check vote status of packages, those in `voted_pkgs` are voted without fetching their pages
+    fn check_vote_with<F>(
+        &self,
+        packages: &[String],
+        voted_pkgs: &HashSet<String>,
+        mut on_result: F,
+    ) -> Result<Vec<(String, Option<bool>)>>
+    where
+        F: FnMut(&(String, Option<bool>)) -> Result<()>,
+    {
+        self.is_login()?;
+        let session = self.session.as_ref().expect("as ref");
+
+        let mut voted: Vec<(String, Option<bool>)> = Vec::new();
+        for batch in packages.chunks(self.concurrent_votes) {
+            let started = self.start_batch(batch, |pkg| !voted_pkgs.contains(pkg));
+            let to_fetch: Vec<&String> = started
+                .iter()
+                .filter(|pkg| !voted_pkgs.contains(*pkg))
+                .collect();
+            let mut fetched = map_concurrently(&to_fetch, |pkg| {
+                self.pace();
+                let url = pkg_page_url(&self.aur_url, pkg)?;
+                let get_start = Instant::now();
+                let page = Html::parse_document(&get_text(
+                    session,
+                    &self.retry,
+                    &self.requests,
+                    self.http_cache.as_ref(),
+                    url,
+                )?);
+                log_timing(pkg, get_start.elapsed(), None);
+                self.is_vote_html(&page)
+            })
+            .into_iter();
+
+            // In order of packages, up to the first failed one
+            for pkg in started {
+                let status = match voted_pkgs.contains(pkg) {
+                    true => Some(true),
+                    false => fetched.next().expect("Fetched page of package")?,
+                };
+                let vote_status = (pkg.to_owned(), status);
+                on_result(&vote_status)?;
+                voted.push(vote_status);
+                self.report_progress(voted.len(), packages.len());
+            }
+            if started.len() < batch.len() {
+                break;
+            }
+        }
+
+        Ok(voted)
+    }
+
+    /// Vote for packages, `on_result` is called as soon as each package is voted, in order of packages
+    pub fn vote<F>(
+        &mut self,
+        packages: &[String],
+        on_result: F,
+    ) -> Result<Vec<(String, VoteResult)>>
+    where
+        F: FnMut(&(String, VoteResult)) -> Result<()>,
+    {
+        self.change_votes_by_base(packages, true, on_result)
+    }
+
+    /// Unvote packages, `on_result` is called as soon as each package is unvoted, in order of packages
+    pub fn unvote<F>(
+        &mut self,
+        packages: &[String],
+        on_result: F,
+    ) -> Result<Vec<(String, VoteResult)>>
+    where
+        F: FnMut(&(String, VoteResult)) -> Result<()>,
+    {
+        self.change_votes_by_base(packages, false, on_result)
+    }
+
+    /// Vote/Unvote only the first package of each package base, since a vote applies to
+    /// the whole base. Its result is also the result of the other packages of the base.
+    /// Results are in order of packages, and stop at the first package not processed.
+    fn change_votes_by_base<F>(
+        &mut self,
+        packages: &[String],
+        vote: bool,
+        mut on_result: F,
+    ) -> Result<Vec<(String, VoteResult)>>
+    where
+        F: FnMut(&(String, VoteResult)) -> Result<()>,
+    {
+        self.resolve_pkg_bases(packages);
+        let (firsts, group_of) = group_by_base(
+            packages,
+            &self.pkg_bases.lock().expect("Lock package bases"),
+        );
+        if firsts.len() < packages.len() {
+            debug!(
+                "{} packages share package base, (un)vote {} packages",
+                packages.len(),
+                firsts.len()
+            );
+        }
+
+        let mut statuses: Vec<Option<VoteResult>> = vec![None; firsts.len()];
+        let mut done = 0;
+        let mut results: Vec<(String, VoteResult)> = Vec::new();
+        let mut voted_cache = self.voted_cache.clone();
+        self.change_votes(&firsts, vote, |(_, status)| {
+            // Voted list is no longer as cached
+            if matches!(status, VoteResult::Voted | VoteResult::UnVoted) {
+                if let Some(cache) = voted_cache.take() {
+                    cache.clear()?;
+                }
+            }
+            statuses[done] = Some(status.clone());
+            done += 1;
+            // Groups are in order of their first package, so results stay in order of packages
+            while results.len() < packages.len() {
+                let i = results.len();
+                let status = match &statuses[group_of[i]] {
+                    Some(status) => status.clone(),
+                    None => break,
+                };
+                let result = (packages[i].to_owned(), status);
+                on_result(&result)?;
+                results.push(result);
+            }
+            Ok(())
+        })?;
+        Ok(results)
+    }
+
+    /// Query package bases of `packages` not known from earlier info queries, in one batched
+    /// RPC query. If it fails, or deadline or request cap is reached, packages of unknown base
+    /// are (un)voted each.
+    fn resolve_pkg_bases(&self, packages: &[String]) {
+        if packages.len() < 2 {
+            return;
+        }
+        let unknown: Vec<String> = {
+            let pkg_bases = self.pkg_bases.lock().expect("Lock package bases");
+            let mut unknown: Vec<String> = packages
+                .iter()
+                .filter(|pkg| !pkg_bases.contains_key(*pkg))
+                .cloned()
+                .collect();
+            unknown.sort();
+            unknown.dedup();
+            unknown
+        };
+        if unknown.is_empty() || self.is_past_deadline() || self.is_request_cap_reached() {
+            return;
+        }
+        if let Err(err) = self.info_query(&unknown) {
+            warn!(
+                "Unable to query package bases, (un)vote each package: {:#}",
+                err
+            );
+        }
+    }
+
+    /// Vote/Unvote packages in batches of `concurrent_votes` packages at once.
+    /// If session became invalid, re-login once per batch and retry its packages.
+    /// A failed package does not stop the others, except on maintenance.
+    fn change_votes<F>(
+        &mut self,
+        packages: &[String],
+        vote: bool,
+        mut on_result: F,
+    ) -> Result<Vec<(String, VoteResult)>>
+    where
+        F: FnMut(&(String, VoteResult)) -> Result<()>,
+    {
+        self.is_login()?;
+
+        let mut result: Vec<(String, VoteResult)> = Vec::new();
+        for batch in packages.chunks(self.concurrent_votes) {
+            let started = self.start_batch(batch, |_| true);
+            let mut attempts: Vec<(u32, Result<Option<VoteResult>>)> =
+                map_concurrently(started, |pkg| (1, self.try_change_vote(pkg, vote)));
+
+            let not_logged_in: Vec<usize> = (0..attempts.len())
+                .filter(|&i| matches!(attempts[i].1, Ok(None)))
+                .collect();
+            if !not_logged_in.is_empty() {
+                warn!("Session is no longer valid, login again");
+                self.relogin()?;
+                let retries: Vec<&String> = not_logged_in.iter().map(|&i| &started[i]).collect();
+                let retried = map_concurrently(&retries, |pkg| {
+                    self.count_request();
+                    self.try_change_vote(pkg, vote)
+                });
+                for (i, attempt) in not_logged_in.into_iter().zip(retried) {
+                    attempts[i] = (2, attempt);
+                }
+            }
+
+            for (pkg, (tries, attempt)) in started.iter().zip(attempts) {
+                let status = match attempt {
+                    Ok(Some(status)) => status,
+                    Ok(None) => {
+                        warn!(
+                            "Cannot {} `{}`, not logged in even after login again",
+                            if vote { "vote for" } else { "unvote" },
+                            pkg
+                        );
+                        VoteResult::Failed
+                    }
+                    // Next packages would fail the same way
+                    Err(err) if err.is::<Maintenance>() => {
+                        stats::record(tries, false);
+                        return Err(err);
+                    }
+                    Err(err) => {
+                        warn!(
+                            "Cannot {} `{}`: {:#}",
+                            if vote { "vote for" } else { "unvote" },
+                            pkg,
+                            err
+                        );
+                        VoteResult::Failed
+                    }
+                };
+                stats::record(tries, !status.is_failed());
+                let status = (pkg.to_owned(), status);
+                on_result(&status)?;
+                result.push(status);
+                self.report_progress(result.len(), packages.len());
+            }
+            if started.len() < batch.len() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Vote/Unvote a package, `None` if session is no longer valid.
+    /// Its page request is counted by caller.
+    fn try_change_vote(&self, pkg: &str, vote: bool) -> Result<Option<VoteResult>> {
+        self.pace();
+        let session = self.session.as_ref().expect("as ref");
+        let get_start = Instant::now();
+        let response = self.send_with_retry(session.get(pkg_page_url(&self.aur_url, pkg)?))?;
+        let page = Html::parse_document(&response_text(response)?);
+        let get_elapsed = get_start.elapsed();
+        let response = match self.is_vote_html(&page)? {
+            Some(voted) if voted == vote => {
+                log_timing(pkg, get_elapsed, None);
+                return Ok(Some(match vote {
+                    true => VoteResult::AlreadyVoted,
+                    false => VoteResult::AlreadyUnVoted,
+                }));
+            }
+            Some(_) => {
+                let post_start = Instant::now();
+                let response = self.do_vote(pkg, vote, &page);
+                log_timing(pkg, get_elapsed, Some(post_start.elapsed()));
+                match response {
+                    Ok(response) => response,
+                    // Next packages would fail the same way
+                    Err(err) if err.is::<Maintenance>() => return Err(err),
+                    Err(err) => {
+                        warn!("{:#}", err);
+                        return Ok(Some(VoteResult::Failed));
+                    }
+                }
+            }
+            None if self.is_login_html(&page).is_ok() => {
+                log_timing(pkg, get_elapsed, None);
+                return Ok(Some(VoteResult::NotAvailable));
+            }
+            None => {
+                log_timing(pkg, get_elapsed, None);
+                VoteResponse::NotLoggedIn
+            }
+        };
+
+        Ok(match response {
+            VoteResponse::Changed => Some(match vote {
+                true => VoteResult::Voted,
+                false => VoteResult::UnVoted,
+            }),
+            VoteResponse::Unchanged(notice) => {
+                let action = if vote { "voting" } else { "unvoting" };
+                match notice {
+                    Some(notice) => warn!(
+                        "Vote of `{}` is unchanged after {}: {}",
+                        pkg, action, notice
+                    ),
+                    None => warn!("Vote of `{}` is unchanged after {}", pkg, action),
+                }
+                Some(VoteResult::Failed)
+            }
+            VoteResponse::NotLoggedIn => None,
+            VoteResponse::Throttled(advice) => {
+                warn!("Throttled by AUR: {}", advice);
+                Some(VoteResult::Throttled(advice))
+            }
+        })
+    }
+
+    /// Fetch page of package, fail if package is not available or not logged in
+    fn available_pkg_page(&self, pkg: &str) -> Result<Html> {
+        let session = self.session.as_ref().expect("as ref");
+        self.count_request();
+        let response = self.send_with_retry(session.get(pkg_page_url(&self.aur_url, pkg)?))?;
+        let page = Html::parse_document(&response_text(response)?);
+        match self.is_vote_html(&page)? {
+            Some(_) => Ok(page),
+            None if self.is_login_html(&page).is_ok() => {
+                Err(anyhow!("Package `{}` is not available on AUR.", pkg))
+            }
+            None => Err(AurError::auth(anyhow!("Not logged in."))),
+        }
+    }
+
+    /// Submit form with its hidden inputs and `params`, `what` describes it in error
+    fn submit_form(&self, form: PageForm, params: &[(&str, &str)], what: &str) -> Result<()> {
+        let session = self.session.as_ref().expect("as ref");
+        let url = self.aur_url.join(&form.action)?;
+        let mut fields = form.fields;
+        fields.extend(
+            params
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        debug!("Submit URL: {}", url);
+
+        self.count_request();
+        let response = session.post(url).form(&fields).send().map_err(send_error)?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Unable to {}: {}", what, response.status()));
+        }
+
+        // Status may be success even if session became invalid, check the returned page instead
+        let page = Html::parse_document(&response_text(response)?);
+        self.is_login_html(&page)
+    }
+
+    /// Post comment on package page
+    pub fn comment(&self, pkg: &str, text: &str) -> Result<()> {
+        self.is_login()?;
+        if text.trim().is_empty() {
+            return Err(anyhow!("Comment must not be empty."));
+        }
+
+        let page = self.available_pkg_page(pkg)?;
+        let form = self
+            .extract_form(&page, "div#generic-form form")?
+            .ok_or_else(|| anyhow!("Unable to find comment form of `{}`", pkg))?;
+        self.submit_form(form, &[("comment", text)], &format!("comment on `{}`", pkg))
+    }
+
+    /// Flag package out-of-date with `reason`
+    pub fn flag(&self, pkg: &str, reason: &str) -> Result<FlagResult> {
+        self.is_login()?;
+        if reason.trim().is_empty() {
+            return Err(anyhow!("Reason of flagging must not be empty."));
+        }
+
+        let page = self.available_pkg_page(pkg)?;
+        if self.is_flagged_html(&page)? {
+            return Ok(FlagResult::AlreadyFlagged);
+        }
+
+        // Flag form is on its own page
+        let link_selector = match Selector::parse("div#actionlist li a[href$=\"/flag/\"]") {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        let link = match page
+            .select(&link_selector)
+            .next()
+            .and_then(|link| link.value().attr("href"))
+        {
+            Some(link) => link.to_owned(),
+            None => return Err(anyhow!("Unable to find flag link of `{}`", pkg)),
+        };
+        let session = self.session.as_ref().expect("as ref");
+        self.count_request();
+        let response = self.send_with_retry(session.get(self.aur_url.join(&link)?))?;
+        let page = Html::parse_document(&response_text(response)?);
+
+        let form = self
+            .extract_form(&page, "form[action$=\"/flag/\"]")?
+            .ok_or_else(|| anyhow!("Unable to find flag form of `{}`", pkg))?;
+        self.submit_form(
+            form,
+            &[("comments", reason), ("do_Flag", "Flag")],
+            &format!("flag `{}`", pkg),
+        )?;
+        Ok(FlagResult::Flagged)
+    }
+
+    /// Unflag package, only its maintainers and the user who flagged it are allowed
+    pub fn unflag(&self, pkg: &str) -> Result<FlagResult> {
+        self.is_login()?;
+
+        let page = self.available_pkg_page(pkg)?;
+        if !self.is_flagged_html(&page)? {
+            return Ok(FlagResult::NotFlagged);
+        }
+
+        let form = self
+            .extract_form(&page, "div#actionlist li form[action$=\"/unflag/\"]")?
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unable to unflag `{}`: only its maintainers and the user who flagged it may unflag it.",
+                    pkg
+                )
+            })?;
+        self.submit_form(
+            form,
+            &[("do_UnFlag", "Unflag package")],
+            &format!("unflag `{}`", pkg),
+        )?;
+        Ok(FlagResult::Unflagged)
+    }
+
+    /// Enable/Disable notifications of new comments on package
+    pub fn set_notify(&self, pkg: &str, enable: bool) -> Result<NotifyResult> {
+        self.is_login()?;
+
+        let page = self.available_pkg_page(pkg)?;
+        match (self.is_notify_html(&page)?, enable) {
+            (Some(true), true) => return Ok(NotifyResult::AlreadyEnabled),
+            (Some(false), false) => return Ok(NotifyResult::AlreadyDisabled),
+            (Some(_), _) => {}
+            (None, _) => return Err(anyhow!("Unable to find notification form of `{}`", pkg)),
+        }
+
+        let (action, param, result) = match enable {
+            true => (
+                "notify",
+                ("do_Notify", "Enable notifications"),
+                NotifyResult::Enabled,
+            ),
+            false => (
+                "unnotify",
+                ("do_UnNotify", "Disable notifications"),
+                NotifyResult::Disabled,
+            ),
+        };
+        let form = self
+            .extract_form(
+                &page,
+                &format!("div#actionlist li form[action$=\"/{}/\"]", action),
+            )?
+            .ok_or_else(|| anyhow!("Unable to find {} form of `{}`", action, pkg))?;
+        self.submit_form(form, &[param], &format!("{} `{}`", action, pkg))?;
+        Ok(result)
+    }
+
+    pub fn list_voted_pkgs(&self) -> Result<AurPackageResults> {
+        self.list_voted_pkgs_with(|_| Ok(()))
+    }
+
+    /// List voted packages saved by voted cache if not expired, otherwise fetch and save them
+    pub fn list_voted_pkgs_cached(&self) -> Result<AurPackageResults> {
+        self.list_voted_pkgs_cached_with(|_| Ok(()))
+    }
+
+    /// As [`Authentication::list_voted_pkgs_with`], using voted cache if any
+    pub fn list_voted_pkgs_cached_with<F>(&self, mut on_pkg: F) -> Result<AurPackageResults>
+    where
+        F: FnMut(&AurPackageResultItem) -> Result<()>,
+    {
+        let cache = match &self.voted_cache {
+            Some(cache) => cache,
+            None => return self.list_voted_pkgs_with(on_pkg),
+        };
+        if let Some(voted_pkgs) = cache.load() {
+            debug!("Use {} cached voted packages", voted_pkgs.len());
+            for pkg in &voted_pkgs {
+                on_pkg(pkg)?;
+            }
+            return Ok(voted_pkgs);
+        }
+        let voted_pkgs = self.list_voted_pkgs_with(on_pkg)?;
+        if let Err(err) = cache.store(&voted_pkgs) {
+            warn!("Unable to cache voted packages: {:#}", err);
+        }
+        Ok(voted_pkgs)
+    }
+
+    /// List voted packages, `on_pkg` is called as soon as each voted package is fetched
+    pub fn list_voted_pkgs_with<F>(&self, mut on_pkg: F) -> Result<AurPackageResults>
+    where
+        F: FnMut(&AurPackageResultItem) -> Result<()>,
+    {
+        self.is_login()?;
+        let session = self.session.as_ref().expect("as ref");
+
+        let mut voted_pkgs = AurPackageResults::new();
+        let mut page_num: usize = 0;
+        loop {
+            // Fetch the first page alone, it may be the only one.
+            // Then fetch next pages at once, the total is unknown.
+            let batch = match page_num {
+                0 => 0..1,
+                _ => page_num..page_num + self.concurrent_pages,
+            };
+            page_num = batch.end;
+
+            let retry = &self.retry;
+            let requests = &self.requests;
+            let cache = self.http_cache.as_ref();
+            self.requests.fetch_add(batch.len(), Ordering::Relaxed);
+            let pages: Vec<Result<AurPackageResults>> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .map(|n| {
+                        let url = voted_pkgs_page_url(&self.aur_url, n, self.page_size);
+                        scope.spawn(move || {
+                            self.pace();
+                            fetch_voted_pkgs_page(session, retry, requests, cache, &url)
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("Join page fetching thread"))
+                    .collect()
+            });
+
+            if collect_voted_pkgs(&mut voted_pkgs, pages, self.full_scan, &mut on_pkg)? {
+                return Ok(voted_pkgs);
+            }
+        }
+    }
+
+    pub(self) fn login_with_user_pass(&mut self, account: &Account) -> Result<()> {
+        debug!("Attempt to login using user and password.");
+
+        let pass = account.password()?;
+        let login_url = login_url(&self.aur_url, &account.user, &pass, self.remember_me)?;
+        debug!("Login URL: {}", redact_url(&login_url));
+
+        // Stop redirect to AUR after logged in, to get AURSID from the redirect response
+        let aur_host = self.aur_url.host().to_owned();
+        let login_no_redirect = redirect::Policy::custom(move |attempt| {
+            if is_login_redirect(&aur_host, attempt.status(), attempt.url()) {
+                return attempt.stop();
+            }
+            redirect::Policy::default().redirect(attempt)
+        });
+        let login_client = self
+            .client
+            .builder()
+            .cookie_store(true)
+            .redirect(login_no_redirect)
+            .build()?;
+        // Error from reqwest includes URL which contains password
+        let login_page = self.aur_url.base.clone() + AUR_PATH_LOGIN;
+        self.count_request();
+        let login_response = login_client.get(login_url).send().map_err(|err| {
+            let reason = match std::error::Error::source(&err) {
+                Some(source) => source.to_string(),
+                None => "request failed".to_owned(),
+            };
+            AurError::network(match self.tls_hint(&err) {
+                Some(hint) => anyhow!("Unable to access `{}`: {}, {}", login_page, reason, hint),
+                None => anyhow!("Unable to access `{}`: {}", login_page, reason),
+            })
+        })?;
+        debug!(
+            "Login response: {} from {}",
+            login_response.status(),
+            redact_url(login_response.url())
+        );
+
+        // Login success
+        if login_response.status() == StatusCode::FOUND
+            && login_response
+                .url()
+                .to_string()
+                .contains(&self.aur_url.base)
+        {
+            // Get AURSID for login cookie
+            if let Some(mut c) = find_aursid(login_response.headers()) {
+                c.set_domain(self.aur_url.host().to_owned());
+                self.cookie_jar.add(c);
+
+                // Access home page of AUR with AURSID to get another cookies
+                let (response, _) = self.login_with_cookies()?;
+
+                // Get AURTZ, AURLANG cookie
+                let aur_cookies = response.headers().get_all(header::SET_COOKIE);
+                for c in aur_cookies.iter() {
+                    let cookie_str = c.to_str()?.to_owned();
+                    let mut cookie = Cookie::parse(cookie_str)?;
+                    cookie.set_domain(self.aur_url.host().to_owned());
+                    self.cookie_jar.add(cookie);
+                }
+
+                // Re-login using cookies
+                let (response, session) = self.login_with_cookies()?;
+                let logged_page = Html::parse_document(&response_text(response)?);
+                self.is_login_html(&logged_page)?;
+                self.session = Some(session);
+
+                return Ok(());
+            }
+
+            return Err(AurError::auth(anyhow!("Login failed: no cookie found.")));
+        }
+
+        self.session = None;
+
+        let status = login_response.status();
+        let retry_after = login_response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        let page = Html::parse_document(&response_text(login_response)?);
+        check_maintenance(&page)?;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            // Rate limited, not a wrong password
+            return Err(AurError::network(match retry_after {
+                Some(secs) => anyhow!("Login rate limited by AUR, retry after {} seconds", secs),
+                None => anyhow!("Login rate limited by AUR, try later"),
+            }));
+        }
+        if !status.is_success() {
+            return Err(AurError::network(anyhow!(
+                "Unable to access `{}`",
+                login_page
+            )));
+        }
+
+        // Login failed, get error messages
+        let error_list = LoginErrorList::from_html(&page)?;
+        if error_list.is_suspended() {
+            return Err(AccountSuspended.into());
+        }
+        Err(error_list.login_error())
+    }
+
+    pub(self) fn login_with_cookie_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        debug!("Attemp to login using cookies.");
+
+        // Session of cookie file readable by other users may be stolen, do not use it.
+        // Login using user, pass saves a new session with 0600 permission.
+        let path = path.as_ref();
+        if !is_file_secure(path)? {
+            warn!(
+                "Cookie file `{}` is not secure, its session may be used by other users. Set its permission to 0600.",
+                path.display()
+            );
+            return Err(anyhow!("Cookie file `{}` is not secure", path.display()));
+        }
+
+        // Load cookies from file
+        for c in load_cookie_file(path)?.iter() {
+            self.cookie_jar.add(c.to_owned());
+        }
+
+        let (response, session) = self.login_with_cookies()?;
+        let logged_page = Html::parse_document(&response_text(response)?);
+        self.is_login_html(&logged_page)?;
+        self.session = Some(session);
+        Ok(())
+    }
+
+    pub(self) fn login_with_cookies(&mut self) -> Result<(Response, Client)> {
+        let headers = self.cookie_headers()?;
+        let session = self
+            .client
+            .builder()
             .default_headers(headers)
             .cookie_store(true)
-            .gzip(true)
-            .http2_prior_knowledge()
-            .use_rustls_tls()
             .build()?;
-        let aur_url = Url::parse(&AUR_URL)?;
-        let response = session.get(aur_url).send()?;
+        let aur_url = self.aur_url.join("/")?;
+        self.count_request();
+        let response = self.send_with_retry(session.get(aur_url))?;
 
         if response.status().is_success() {
             return Ok((response, session));
         }
 
+        if response.status().is_server_error() {
+            return Err(response
+                .error_for_status()
+                .expect_err("Server error")
+                .into());
+        }
+
         Err(anyhow!(
             "Unable to access `{}` with AURSID cookie",
-            &AUR_URL.to_string()
+            self.aur_url.base
         ))
     }
 
+    /// Cookie headers of session. AURLANG is always `language`, so pages are scraped in that language.
+    fn cookie_headers(&self) -> Result<header::HeaderMap> {
+        // Add cookies to headers, ordering is matter
+        let mut headers = header::HeaderMap::new();
+        // AURTZ
+        if let Some(aurtz) = self.cookie_jar.get("AURTZ") {
+            if is_cookie_expired(aurtz, OffsetDateTime::now_utc()) {
+                debug!("Cookies were expired.");
+                return Err(anyhow!("Cookies were expired."));
+            }
+
+            let code = aurtz.encoded().to_string();
+            headers.insert(header::COOKIE, code.parse()?);
+        }
+        // AURLANG
+        let aurlang = Cookie::new("AURLANG", self.language.as_str());
+        headers.append(header::COOKIE, aurlang.encoded().to_string().parse()?);
+        // AURSID
+        if let Some(aursid) = self.cookie_jar.get("AURSID") {
+            let code = aursid.encoded().to_string();
+            headers.append(header::COOKIE, code.parse()?);
+        }
+        Ok(headers)
+    }
+
     pub(self) fn save_cookie<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         self.is_login()?;
 
         let mut cookie_file = fs::OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .mode(0o600)
             .open(path)?;
+        // Mode is only set on create, an existing file may have looser permission
+        cookie_file.set_permissions(fs::Permissions::from_mode(0o600))?;
 
         // AURTZ
         if let Some(aurtz) = self.cookie_jar.get("AURTZ") {
@@ -519,11 +1918,82 @@ impl Authentication {
             Err(err) => return Err(anyhow!("{:?}", err)),
         };
 
-        if html.select(&do_vote_selector).next().is_some() {
-            return Ok(Some(false));
+        if html.select(&do_vote_selector).next().is_some() {
+            return Ok(Some(false));
+        }
+
+        Ok(None)
+    }
+
+    /// Extract notification status of new comments from html
+    pub(self) fn is_notify_html(&self, html: &Html) -> Result<Option<bool>> {
+        let unnotify_selector = match Selector::parse(
+            "div#actionlist li form[action$=\"/unnotify/\"] input[name=\"do_UnNotify\"]",
+        ) {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        if html.select(&unnotify_selector).next().is_some() {
+            return Ok(Some(true));
+        }
+
+        let notify_selector = match Selector::parse(
+            "div#actionlist li form[action$=\"/notify/\"] input[name=\"do_Notify\"]",
+        ) {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        if html.select(&notify_selector).next().is_some() {
+            return Ok(Some(false));
+        }
+
+        Ok(None)
+    }
+
+    /// Check if package is flagged out-of-date using its page
+    pub(self) fn is_flagged_html(&self, html: &Html) -> Result<bool> {
+        let flagged_selector = match Selector::parse("div#actionlist li span.flagged a") {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        Ok(html.select(&flagged_selector).next().is_some())
+    }
+
+    /// Number of pending deletion/merge/orphan requests of package
+    pub fn pending_requests(&self, pkg: &str) -> Result<u32> {
+        self.is_login()?;
+        let session = self.session.as_ref().expect("as ref");
+        self.count_request();
+        let url = pkg_page_url(&self.aur_url, pkg)?;
+        let page = Html::parse_document(&get_text(
+            session,
+            &self.retry,
+            &self.requests,
+            self.http_cache.as_ref(),
+            url,
+        )?);
+        self.pending_requests_html(&page)
+    }
+
+    /// Extract number of pending requests from html, e.g. `2 pending requests`
+    pub(self) fn pending_requests_html(&self, html: &Html) -> Result<u32> {
+        let notice_selector = match Selector::parse("div#actionlist li span.flagged") {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+
+        for notice in html.select(&notice_selector) {
+            let text = notice.text().collect::<String>();
+            let text = text.trim();
+            if let Some(count) = text
+                .strip_suffix("pending requests")
+                .or_else(|| text.strip_suffix("pending request"))
+            {
+                return Ok(count.trim().parse()?);
+            }
         }
 
-        Ok(None)
+        Ok(0)
     }
 
     pub(self) fn extract_token(&self, html: &Html) -> Result<String> {
@@ -541,7 +2011,38 @@ impl Authentication {
         Ok(String::new())
     }
 
-    pub(self) fn do_vote(&self, pkg: &str, vote: bool, page: &Html) -> Result<()> {
+    /// Extract action and hidden inputs of the first form matching `selector`
+    pub(self) fn extract_form(&self, html: &Html, selector: &str) -> Result<Option<PageForm>> {
+        let form_selector = match Selector::parse(selector) {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        let hidden_selector = match Selector::parse("input[type=\"hidden\"]") {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+
+        let form = match html.select(&form_selector).next() {
+            Some(form) => form,
+            None => return Ok(None),
+        };
+        let action = match form.value().attr("action") {
+            Some(action) => action.to_owned(),
+            None => return Ok(None),
+        };
+        let fields = form
+            .select(&hidden_selector)
+            .filter_map(|input| {
+                let name = input.value().attr("name")?;
+                let value = input.value().attr("value").unwrap_or_default();
+                Some((name.to_owned(), value.to_owned()))
+            })
+            .collect();
+
+        Ok(Some(PageForm { action, fields }))
+    }
+
+    pub(self) fn do_vote(&self, pkg: &str, vote: bool, page: &Html) -> Result<VoteResponse> {
         let session = self.session.as_ref().expect("as ref");
         // Get token
         let token = self.extract_token(page)?;
@@ -560,9 +2061,8 @@ impl Authentication {
             None => return Err(anyhow!("Error: cannot get pkgbase of {}", pkg)),
         };
 
-        let url = Url::parse(
-            &(AUR_URL.to_string()
-                + &pkgbase
+        let url = self.aur_url.join(
+            &(pkgbase
                 + match vote {
                     true => "vote/",
                     false => "unvote/",
@@ -580,17 +2080,51 @@ impl Authentication {
         );
         debug!("Un(Vote) URL: {}", url);
 
-        let response = session.post(url).form(&params).send()?;
+        self.count_request();
+        let response = session.post(url).form(&params).send().map_err(send_error)?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        let page = Html::parse_document(&response_text(response)?);
 
-        if !response.status().is_success() {
-            if vote {
-                return Err(anyhow!("Error: cannot vote for {}", pkg));
-            } else {
-                return Err(anyhow!("Error: cannot unvote {}", pkg));
-            }
+        // Maintenance page may be returned with success status, nothing is changed
+        check_maintenance(&page)?;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let advice = match (throttle_notice(&page)?, retry_after) {
+                (Some(notice), _) => notice,
+                (None, Some(secs)) => format!("Too many requests, retry after {} seconds", secs),
+                (None, None) => "Too many requests".to_owned(),
+            };
+            return Ok(VoteResponse::Throttled(advice));
+        }
+        if !status.is_success() {
+            let action = if vote { "vote for" } else { "unvote" };
+            return Err(match page_notice(&page)? {
+                Some(notice) => anyhow!("Error: cannot {} {}: {}", action, pkg, notice),
+                None => anyhow!("Error: cannot {} {} ({})", action, pkg, status),
+            });
         }
 
-        Ok(())
+        // Status may be success even if session became invalid, check the returned page instead
+        self.vote_response_html(&page, vote)
+    }
+
+    /// Check if (un)vote is reflected in package page returned after (un)vote
+    pub(self) fn vote_response_html(&self, html: &Html, vote: bool) -> Result<VoteResponse> {
+        if let Some(notice) = throttle_notice(html)? {
+            return Ok(VoteResponse::Throttled(notice));
+        }
+        match self.is_vote_html(html)? {
+            Some(voted) if voted == vote => Ok(VoteResponse::Changed),
+            Some(_) => Ok(VoteResponse::Unchanged(page_notice(html)?)),
+            None if self.is_login_html(html).is_ok() => {
+                Ok(VoteResponse::Unchanged(page_notice(html)?))
+            }
+            None => Ok(VoteResponse::NotLoggedIn),
+        }
     }
 
     /// Check if user logged in using html from https://aur.archlinux.org/
@@ -601,14 +2135,643 @@ impl Authentication {
         };
         match html.select(&logout_selector).next() {
             Some(_) => Ok(()),
-            None => Err(anyhow!("Not logged in.")),
+            None if is_maintenance_html(html)? => Err(Maintenance.into()),
+            None if LoginErrorList::from_html(html)?.is_suspended() => Err(AccountSuspended.into()),
+            None => Err(AurError::auth(anyhow!("Not logged in."))),
+        }
+    }
+}
+
+/// First package of each package base in order, and index of group of each package.
+/// Package without known base, e.g. not available, is a group of its own.
+fn group_by_base(
+    packages: &[String],
+    bases: &HashMap<String, String>,
+) -> (Vec<String>, Vec<usize>) {
+    let mut firsts: Vec<String> = Vec::new();
+    let mut group_bases: Vec<Option<&str>> = Vec::new();
+    let mut group_of: Vec<usize> = Vec::new();
+    for pkg in packages {
+        let base = bases.get(pkg).map(String::as_str);
+        match group_bases
+            .iter()
+            .position(|b| base.is_some() && *b == base)
+        {
+            Some(group) => group_of.push(group),
+            None => {
+                group_of.push(firsts.len());
+                firsts.push(pkg.to_owned());
+                group_bases.push(base);
+            }
+        }
+    }
+    (firsts, group_of)
+}
+
+/// Log time of requests of a package page, to tell slow AUR responses from the others
+fn log_timing(pkg: &str, get: Duration, post: Option<Duration>) {
+    match post {
+        Some(post) => debug!("pkg={} get={:?} post={:?}", pkg, get, post),
+        None => debug!("pkg={} get={:?}", pkg, get),
+    }
+}
+
+/// Run `f` on each package at once, results are in order of `packages`
+fn map_concurrently<S, T, F>(packages: &[S], f: F) -> Vec<T>
+where
+    S: AsRef<str> + Sync,
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = packages
+            .iter()
+            .map(|pkg| scope.spawn(move || f(pkg.as_ref())))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Join package thread"))
+            .collect()
+    })
+}
+
+/// Redirect after login, to any page of AUR at `aur_host`, e.g. `/`, `/packages/`, or with query
+fn is_login_redirect(aur_host: &str, status: StatusCode, target: &Url) -> bool {
+    status == StatusCode::FOUND && target.host_str() == Some(aur_host)
+}
+
+/// AURSID cookie among `Set-Cookie` headers of login response
+fn find_aursid(headers: &header::HeaderMap) -> Option<Cookie<'static>> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| Cookie::parse(value.to_owned()).ok())
+        .find(|cookie| cookie.name() == "AURSID")
+}
+
+/// Check if html is the maintenance page of AUR, by its maintenance banner.
+/// Title and headings are not checked, since they contain package names.
+fn is_maintenance_html(html: &Html) -> Result<bool> {
+    let selector = match Selector::parse("div#content div#maintenance") {
+        Ok(selector) => selector,
+        Err(err) => return Err(anyhow!("{:?}", err)),
+    };
+    Ok(html.select(&selector).next().is_some())
+}
+
+/// Errors and notices of AUR on a page, e.g. why (un)vote is rejected, joined for logs
+fn page_notice(html: &Html) -> Result<Option<String>> {
+    let list = LoginErrorList::from_html(html)?;
+    let messages: Vec<String> = list.errors.into_iter().chain(list.notices).collect();
+    Ok((!messages.is_empty()).then(|| messages.join(", ")))
+}
+
+/// User name from `My Account` link of logged-in page, e.g. `/account/bpetlert/edit/`
+fn current_user_html(html: &Html) -> Result<Option<String>> {
+    let selector = match Selector::parse("div#archdev-navbar li a[href^=\"/account/\"]") {
+        Ok(selector) => selector,
+        Err(err) => return Err(anyhow!("{:?}", err)),
+    };
+    Ok(html
+        .select(&selector)
+        .filter_map(|element| element.value().attr("href"))
+        .find_map(|href| {
+            let user = href.strip_prefix("/account/")?.split('/').next()?;
+            (!user.is_empty()).then(|| user.to_owned())
+        }))
+}
+
+/// Notice of AUR refusing to (un)vote because of voting too fast, if any
+fn throttle_notice(html: &Html) -> Result<Option<String>> {
+    let selector = match Selector::parse("ul.errorlist li, p.error") {
+        Ok(selector) => selector,
+        Err(err) => return Err(anyhow!("{:?}", err)),
+    };
+    Ok(html
+        .select(&selector)
+        .map(|element| element.text().collect::<String>().trim().to_owned())
+        .find(|text| {
+            let text = text.to_lowercase();
+            ["too fast", "too many", "rate limit"]
+                .iter()
+                .any(|pattern| text.contains(pattern))
+        }))
+}
+
+/// Fail with `Maintenance` if html is the maintenance page of AUR
+fn check_maintenance(html: &Html) -> Result<()> {
+    match is_maintenance_html(html)? {
+        true => Err(Maintenance.into()),
+        false => Ok(()),
+    }
+}
+
+/// GET text of `url`. With `cache`, send validators of cached response,
+/// and use cached text if it is not modified.
+fn get_text(
+    session: &Client,
+    retry: &Retry,
+    requests: &AtomicUsize,
+    cache: Option<&HttpCache>,
+    url: Url,
+) -> Result<String> {
+    let cache = match cache {
+        Some(cache) => cache,
+        None => return response_text(send_retrying(session.get(url), retry, requests)?),
+    };
+
+    let cached = cache.load(url.as_str());
+    let mut request = session.get(url.clone());
+    if let Some(entry) = &cached {
+        if let Some(etag) = &entry.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let response = send_retrying(request, retry, requests)?;
+    if let (StatusCode::NOT_MODIFIED, Some(entry)) = (response.status(), cached) {
+        debug!("Not modified, use cached `{}`", redact_url(&url));
+        return Ok(entry.body);
+    }
+
+    let is_success = response.status().is_success();
+    let validator = |name: header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let etag = validator(header::ETAG);
+    let last_modified = validator(header::LAST_MODIFIED);
+    let body = response_text(response)?;
+    if is_success {
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            body,
+        };
+        // Cache is best effort
+        if let Err(err) = cache.store(&entry) {
+            debug!("Unable to cache `{}`: {}", redact_url(&url), err);
+        }
+        return Ok(entry.body);
+    }
+    Ok(body)
+}
+
+/// Body of response as text. Decoding error is explained, since a corrupted or mislabeled
+/// gzip body is usually caused by a proxy, not AUR.
+fn response_text(response: Response) -> Result<String> {
+    let url = redact_url(response.url());
+    response.text().map_err(|err| {
+        if err.is_decode() {
+            AurError::network(anyhow!(
+                "Failed to decode response body of `{}` (is a proxy mangling it?): {}",
+                url,
+                err
+            ))
+        } else {
+            err.into()
+        }
+    })
+}
+
+/// Replace credentials in query of URL with `***`, for logging
+fn redact_url(url: &Url) -> Url {
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| match key.as_ref() {
+            "user" | "passwd" => (key.into_owned(), "***".to_owned()),
+            _ => (key.into_owned(), value.into_owned()),
+        })
+        .collect();
+    if !pairs.is_empty() {
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    redacted
+}
+
+/// Options of HTTP clients to AUR, shared by login, session, and RPC queries
+#[derive(Clone, PartialEq, Debug)]
+pub struct AurClient {
+    /// Minimum TLS version: 1.2 or 1.3
+    min_tls_version: Option<String>,
+
+    /// URL of proxy, e.g. `http://proxy.example.org:3128`. If not set, proxy is taken from
+    /// `HTTP_PROXY`, `HTTPS_PROXY`, or `ALL_PROXY` environment variables.
+    proxy: Option<String>,
+
+    /// Time to wait for connection
+    connect_timeout: Duration,
+
+    /// Time to wait for a whole request, from connecting until body is read
+    request_timeout: Duration,
+}
+
+impl Default for AurClient {
+    fn default() -> Self {
+        AurClient {
+            min_tls_version: None,
+            proxy: None,
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT),
+        }
+    }
+}
+
+impl AurClient {
+    /// Set minimum TLS version of connections to AUR: 1.2 or 1.3
+    pub fn set_min_tls_version(&mut self, version: &str) -> Result<()> {
+        tls_version(version)?;
+        self.min_tls_version = Some(version.to_owned());
+        Ok(())
+    }
+
+    /// Set proxy of all connections to AUR, e.g. `http://proxy.example.org:3128`
+    pub fn set_proxy(&mut self, url: &str) -> Result<()> {
+        proxy(url)?;
+        self.proxy = Some(url.to_owned());
+        Ok(())
+    }
+
+    /// Set seconds to wait for connection, and for a whole request, both greater than 0
+    pub fn set_timeouts(&mut self, connect: u64, request: u64) -> Result<()> {
+        if connect == 0 || request == 0 {
+            return Err(anyhow!("Timeouts must be greater than 0 seconds."));
+        }
+        self.connect_timeout = Duration::from_secs(connect);
+        self.request_timeout = Duration::from_secs(request);
+        Ok(())
+    }
+
+    /// Client builder with all options applied, to be extended by its caller, e.g. with cookies
+    pub fn builder(&self) -> ClientBuilder {
+        let builder = Client::builder()
+            .user_agent(APP_USER_AGENT)
+            .gzip(true)
+            .http2_prior_knowledge()
+            .use_rustls_tls()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout);
+        let builder = match &self.min_tls_version {
+            Some(version) => builder.min_tls_version(tls_version(version).expect("TLS version")),
+            None => builder,
+        };
+        // Without explicit proxy, reqwest uses proxy from environment variables
+        match &self.proxy {
+            Some(url) => builder.proxy(proxy(url).expect("Proxy")),
+            None => builder,
+        }
+    }
+
+    pub fn build_client(&self) -> Result<Client> {
+        Ok(self.builder().build()?)
+    }
+}
+
+/// Base URL of AUR, e.g. `https://aur.archlinux.org` or of a self-hosted aurweb instance
+#[derive(Clone, PartialEq, Debug)]
+pub struct AurUrl {
+    /// Scheme, host, and port, without trailing slash
+    base: String,
+    host: String,
+}
+
+impl Default for AurUrl {
+    fn default() -> Self {
+        AurUrl::parse(DEFAULT_AUR_URL).expect("Default AUR URL")
+    }
+}
+
+impl AurUrl {
+    /// Parse base URL of AUR. Links in its pages are absolute paths, so it has no path.
+    pub fn parse(url: &str) -> Result<AurUrl> {
+        let parsed =
+            Url::parse(url).map_err(|err| anyhow!("Invalid AUR URL `{}`: {}", url, err))?;
+        let host = match parsed.host_str() {
+            Some(host) if matches!(parsed.scheme(), "http" | "https") => host.to_owned(),
+            _ => {
+                return Err(anyhow!(
+                    "AUR URL must be http(s) URL of a host, got `{}`.",
+                    url
+                ))
+            }
+        };
+        if parsed.path() != "/" || parsed.query().is_some() || parsed.fragment().is_some() {
+            return Err(anyhow!(
+                "AUR URL must have no path or query, e.g. `{}`, got `{}`.",
+                DEFAULT_AUR_URL,
+                url
+            ));
+        }
+        Ok(AurUrl {
+            base: parsed.as_str().trim_end_matches('/').to_owned(),
+            host,
+        })
+    }
+
+    /// Host of AUR, i.e. domain of its cookies
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// URL of absolute `path` on AUR, e.g. a link or form action in its page
+    fn join(&self, path: &str) -> Result<Url> {
+        Ok(Url::parse(&(self.base.clone() + path))?)
+    }
+}
+
+/// Retry of requests on connection errors and server errors, with exponential backoff
+#[derive(Clone, PartialEq, Debug)]
+pub struct Retry {
+    /// Number of retries after the first attempt
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled before each next one
+    pub base_delay: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            retries: DEFAULT_RETRIES,
+            base_delay: Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
+        }
+    }
+}
+
+/// Send request, retry on connection errors, timeouts, and 5xx status.
+/// Other responses, successful or not, are returned at once. After the last retry,
+/// its response is returned even with 5xx status, e.g. for maintenance page.
+/// Each retry is counted in `requests`, the first attempt is counted by its caller.
+fn send_retrying(
+    request: RequestBuilder,
+    retry: &Retry,
+    requests: &AtomicUsize,
+) -> Result<Response> {
+    let mut delay = retry.base_delay;
+    for _ in 0..retry.retries {
+        // Request with body which cannot be cloned is sent only once
+        let attempt = match request.try_clone() {
+            Some(attempt) => attempt,
+            None => break,
+        };
+        match attempt.send() {
+            Ok(response) if !response.status().is_server_error() => return Ok(response),
+            Ok(response) => debug!(
+                "{} from `{}`, retry in {:?}",
+                response.status(),
+                redact_url(response.url()),
+                delay
+            ),
+            Err(err) if err.is_connect() || err.is_timeout() => {
+                // Error includes URL which may contain password
+                match err.url() {
+                    Some(url) => debug!(
+                        "Unable to access `{}`, retry in {:?}",
+                        redact_url(url),
+                        delay
+                    ),
+                    None => debug!("Unable to send request, retry in {:?}", delay),
+                }
+            }
+            Err(err) => return Err(err.into()),
+        }
+        thread::sleep(delay);
+        delay *= 2;
+        requests.fetch_add(1, Ordering::Relaxed);
+    }
+    request.send().map_err(send_error)
+}
+
+/// Error of sending request, naming URL which stalled on timeout
+fn send_error(err: reqwest::Error) -> anyhow::Error {
+    let message = match (err.is_timeout(), err.url()) {
+        (true, Some(url)) => format!("Request to `{}` timed out", redact_url(url)),
+        (true, None) => "Request timed out".to_owned(),
+        _ => return err.into(),
+    };
+    // Keep `reqwest::Error` as source, e.g. for `is_transient_error`
+    anyhow::Error::from(err).context(message)
+}
+
+/// URL to login using user, pass, with `remember_me` for a long-lived session
+fn login_url(aur_url: &AurUrl, user: &str, pass: &str, remember_me: bool) -> Result<Url> {
+    let mut params = vec![("user", user), ("passwd", pass)];
+    if remember_me {
+        params.push(("remember_me", "on"));
+    }
+    Ok(Url::parse_with_params(
+        &(aur_url.base.clone() + AUR_PATH_LOGIN),
+        &params,
+    )?)
+}
+
+/// TLS version from configuration value
+fn tls_version(version: &str) -> Result<tls::Version> {
+    match version {
+        "1.2" => Ok(tls::Version::TLS_1_2),
+        "1.3" => Ok(tls::Version::TLS_1_3),
+        _ => Err(anyhow!(
+            "Minimum TLS version must be 1.2 or 1.3, got `{}`.",
+            version
+        )),
+    }
+}
+
+fn proxy(url: &str) -> Result<Proxy> {
+    Proxy::all(url).map_err(|err| anyhow!("Invalid proxy `{}`: {}", url, err))
+}
+
+/// URL of package page, package name is percent-encoded
+fn pkg_page_url(aur_url: &AurUrl, pkg: &str) -> Result<Url> {
+    let pkg = utf8_percent_encode(pkg, PKG_NAME_ENCODE_SET).to_string();
+    aur_url.join(&AUR_PATH_PKG_PAGE.replace("<PKG>", &pkg))
+}
+
+/// URL of n-th page (start from 0) of packages sorted by voted
+fn voted_pkgs_page_url(aur_url: &AurUrl, page_num: usize, page_size: usize) -> String {
+    aur_url.base.clone()
+        + &AUR_PATH_SORT_VOTED_PKG
+            .replace("<OFFSET>", (page_num * page_size).to_string().as_str())
+            .replace("<PAGE_SIZE>", page_size.to_string().as_str())
+}
+
+fn fetch_voted_pkgs_page(
+    session: &Client,
+    retry: &Retry,
+    requests: &AtomicUsize,
+    cache: Option<&HttpCache>,
+    url: &str,
+) -> Result<AurPackageResults> {
+    let url = Url::parse(url)?;
+    let page = Html::parse_document(&get_text(session, retry, requests, cache, url)?);
+    AurPackageResults::from_html(&page)
+}
+
+/// Append voted packages of consecutive pages to `voted_pkgs`, in order, calling `on_pkg`
+/// for each. Return true when the end of voted packages is reached, i.e. an empty page
+/// or a not voted package (unless `full_scan`). Later pages, including failed ones, are ignored.
+fn collect_voted_pkgs<F>(
+    voted_pkgs: &mut AurPackageResults,
+    pages: Vec<Result<AurPackageResults>>,
+    full_scan: bool,
+    on_pkg: &mut F,
+) -> Result<bool>
+where
+    F: FnMut(&AurPackageResultItem) -> Result<()>,
+{
+    for packages in pages {
+        let packages = packages?;
+        if packages.is_empty() {
+            return Ok(true);
         }
+
+        for pkg in packages {
+            if !pkg.voted && full_scan {
+                continue;
+            }
+            if !pkg.voted {
+                return Ok(true);
+            }
+            on_pkg(&pkg)?;
+            voted_pkgs.push(pkg);
+        }
+    }
+    Ok(false)
+}
+
+/// Parse AUR cookies of `aur_host` from Netscape cookies.txt exported by browser,
+/// other cookies are ignored
+pub fn parse_cookies_txt(content: &str, aur_host: &str) -> Result<CookieJar> {
+    let mut cookie_jar = CookieJar::new();
+    for line in content.lines() {
+        // `#HttpOnly_` prefixes domain of HttpOnly cookie, other `#` lines are comments
+        let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+            Some(line) => (line, true),
+            None if line.starts_with('#') || line.trim().is_empty() => continue,
+            None => (line, false),
+        };
+        let fields: Vec<&str> = line.split('\t').collect();
+        let (domain, path, secure, expires, name, value) = match fields[..] {
+            [domain, _, path, secure, expires, name, value] => {
+                (domain, path, secure, expires, name, value)
+            }
+            _ => return Err(anyhow!("Invalid line of cookies.txt: `{}`", line)),
+        };
+        if domain.trim_start_matches('.') != aur_host || !AUR_COOKIES.contains(&name) {
+            continue;
+        }
+
+        let mut cookie = Cookie::build(name.to_owned(), value.to_owned())
+            .domain(domain.to_owned())
+            .path(path.to_owned())
+            .secure(secure == "TRUE")
+            .http_only(http_only);
+        // Expiration 0 is a session cookie
+        let expires: i64 = expires
+            .parse()
+            .map_err(|_| anyhow!("Invalid expiration of cookie `{}`: `{}`", name, expires))?;
+        if expires > 0 {
+            cookie = cookie.expires(OffsetDateTime::from_unix_timestamp(expires)?);
+        }
+        cookie_jar.add(cookie.finish());
+    }
+    Ok(cookie_jar)
+}
+
+/// Load cookies from cookie file, one cookie per line
+pub fn load_cookie_file<P: AsRef<Path>>(path: P) -> Result<CookieJar> {
+    let mut cookie_jar = CookieJar::new();
+    let cookie_file = File::open(path)?;
+    let reader = BufReader::new(cookie_file);
+    for line in reader.lines() {
+        let c = Cookie::parse(line?)?;
+        cookie_jar.add(c.into_owned());
+    }
+    Ok(cookie_jar)
+}
+
+/// Names of voted packages, to check vote status by membership
+fn voted_names(voted_pkgs: AurPackageResults) -> HashSet<String> {
+    voted_pkgs.into_iter().map(|pkg| pkg.name).collect()
+}
+
+/// Remove cookie file, if any
+fn remove_cookie_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(_) => debug!("Remove cookie file `{}`", path.to_str().expect("To str")),
+        Err(err) if err.kind() == ErrorKind::NotFound => (),
+        Err(err) => {
+            return Err(anyhow!(
+                "Unable to remove cookie file `{}`: {}",
+                path.to_str().expect("To str"),
+                err
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Session of cookie file is proven invalid: AUR answered, but not logged in.
+/// Unlike network errors, missing cookie file, maintenance, or suspended account.
+fn is_invalid_session_error(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<AurError>(), Some(err) if err.kind == AurErrorKind::Auth)
+}
+
+/// Check if cookie was expired at `now`, session cookie never expires
+pub fn is_cookie_expired(cookie: &Cookie, now: OffsetDateTime) -> bool {
+    match cookie.expires() {
+        Some(Expiration::DateTime(d)) => d.unix_timestamp() < now.unix_timestamp(),
+        Some(Expiration::Session) | None => false,
     }
 }
 
 #[derive(Default, Deserialize, PartialEq, Debug)]
 struct LoginErrorList {
+    /// Errors of login form, e.g. `Bad username or password.`
     pub errors: Vec<String>,
+
+    /// Other notices/warnings of login page, e.g. of rate limit
+    pub notices: Vec<String>,
+
+    /// Login page asks to solve a CAPTCHA or another challenge
+    pub challenge: bool,
+}
+
+impl LoginErrorList {
+    /// Check if login is refused because account is suspended, e.g. `Account Suspended`
+    fn is_suspended(&self) -> bool {
+        self.errors
+            .iter()
+            .any(|err| err.to_lowercase().contains("suspended"))
+    }
+
+    /// Error of failed login: credentials rejected, or unexpected login page,
+    /// e.g. of rate limit or challenge, which does not need another password
+    fn login_error(&self) -> anyhow::Error {
+        if !self.errors.is_empty() {
+            return AurError::auth(anyhow!("Login failed: {}", self.errors.join(", ")));
+        }
+        let mut reason = match self.challenge {
+            true => "AUR asks to solve a CAPTCHA or another challenge".to_owned(),
+            false => "unexpected login page, possibly a rate limit or challenge".to_owned(),
+        };
+        if !self.notices.is_empty() {
+            reason = format!("{}: {}", reason, self.notices.join(", "));
+        }
+        AurError::auth(anyhow!(
+            "Login failed: {}. Credentials were not rejected, try later or login using a browser.",
+            reason.trim_end_matches('.')
+        ))
+    }
 }
 
 impl Extraction<LoginErrorList> for LoginErrorList {
@@ -623,10 +2786,32 @@ impl Extraction<LoginErrorList> for LoginErrorList {
 
         let errlist = html.select(&errlist_selector);
         error_list.errors = errlist
-            .into_iter()
             .map(|li| li.inner_html().trim().to_owned())
             .collect();
 
+        let notice_selector = match Selector::parse(
+            "p.error, div.error, p.warning, div.warning, p.notice, div.notice",
+        ) {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        error_list.notices = html
+            .select(&notice_selector)
+            .map(|element| {
+                let text: String = element.text().collect();
+                text.split_whitespace().collect::<Vec<&str>>().join(" ")
+            })
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        let challenge_selector = match Selector::parse(
+            ".g-recaptcha, .h-captcha, .cf-turnstile, input[name*=\"captcha\"], iframe[src*=\"captcha\"]",
+        ) {
+            Ok(selector) => selector,
+            Err(err) => return Err(anyhow!("{:?}", err)),
+        };
+        error_list.challenge = html.select(&challenge_selector).next().is_some();
+
         Ok(error_list)
     }
 }
@@ -641,47 +2826,165 @@ struct AurPackageInfoResult {
 
 /// For data from https://aur.archlinux.org/rpc?v=5&type=info&arg[]=pkg1&arg[]=pkg2&…
 /// See: https://wiki.archlinux.org/index.php/Aurweb_RPC_interface#info_2
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug)]
 pub struct AurPackageInfoItem {
     #[serde(rename(deserialize = "Name"))]
     pub name: String,
 
     #[serde(rename(deserialize = "Version"))]
     pub version: String,
-}
 
-pub type AurPackageInfo = Vec<AurPackageInfoItem>;
+    #[serde(rename(deserialize = "PackageBase"))]
+    pub package_base: String,
 
-pub trait AurInfoQuery<T> {
-    fn info_query(pkgs: &[String]) -> Result<T>;
+    /// Unix timestamp
+    #[serde(rename(deserialize = "FirstSubmitted"))]
+    pub first_submitted: i64,
+
+    /// Unix timestamp
+    #[serde(rename(deserialize = "LastModified"))]
+    pub last_modified: i64,
+
+    /// Run-time dependencies, may have version constraints, e.g. `glibc>=2.33`
+    #[serde(
+        rename(deserialize = "Depends"),
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub depends: Vec<String>,
 }
 
-impl AurInfoQuery<AurPackageInfo> for AurPackageInfo {
-    fn info_query(pkgs: &[std::string::String]) -> Result<AurPackageInfo> {
-        let client = Client::builder()
-            .user_agent(APP_USER_AGENT)
-            .gzip(true)
-            .http2_prior_knowledge()
-            .use_rustls_tls()
-            .build()?;
+pub type AurPackageInfo = Vec<AurPackageInfoItem>;
 
+// RPC queries are not part of a session, but they are counted against `max_requests` like
+// requests of session
+impl Authentication {
+    /// Query info of packages, chunks are queried concurrently, results are in order of chunks.
+    /// Each chunk is still paced by `request_delay`. Any failed chunk fails the whole query.
+    /// See `benches/info_query.rs` for concurrent vs. sequential queries.
+    pub fn info_query(&self, pkgs: &[String]) -> Result<AurPackageInfo> {
+        let client = self.client.build_client()?;
+        let client = &client;
         let mut results: AurPackageInfo = Vec::new();
+        let chunks: Vec<&[String]> = pkgs.chunks(PACKAGE_QUERY_LIMIT).collect();
+        for batch in chunks.chunks(CONCURRENT_INFO_QUERIES) {
+            let batch_results: Vec<Result<AurPackageInfo>> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|chunk| scope.spawn(move || self.fetch_info_chunk(client, chunk)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("Join info query thread"))
+                    .collect()
+            });
+            for chunk in batch_results {
+                results.append(&mut chunk?);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Query packages in chunks, `on_chunk` is called with result of each chunk
+    /// as soon as it is fetched, without holding results of all chunks
+    pub fn info_query_streaming<F>(&self, pkgs: &[String], mut on_chunk: F) -> Result<()>
+    where
+        F: FnMut(AurPackageInfo) -> Result<()>,
+    {
+        let client = self.client.build_client()?;
+
         for chunk in pkgs.chunks(PACKAGE_QUERY_LIMIT) {
-            let queries: Vec<(&str, &str)> =
-                chunk.iter().map(|pkg| ("arg[]", pkg.as_str())).collect();
-            let url = Url::parse_with_params(&AUR_URL_PKG_INFO, &queries)?;
-            let response = client.get(url).send()?;
-            let mut info_results: AurPackageInfoResult = response.json()?;
-            results.append(&mut info_results.results);
+            on_chunk(self.fetch_info_chunk(&client, chunk)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Query info of a chunk of up to `PACKAGE_QUERY_LIMIT` packages
+    fn fetch_info_chunk(&self, client: &Client, chunk: &[String]) -> Result<AurPackageInfo> {
+        let queries: Vec<(&str, &str)> = chunk.iter().map(|pkg| ("arg[]", pkg.as_str())).collect();
+        let url = self.rpc_url(AUR_PATH_RPC_INFO, &queries)?;
+        self.pace();
+        self.count_request();
+        let response = self.send_with_retry(client.get(url))?;
+        let info_results: AurPackageInfoResult = response.json()?;
+        self.record_pkg_bases(&info_results.results);
+        Ok(info_results.results)
+    }
+
+    /// Keep package bases of queried packages, so (un)vote needs not query them again
+    fn record_pkg_bases(&self, pkgs_info: &[AurPackageInfoItem]) {
+        let mut pkg_bases = self.pkg_bases.lock().expect("Lock package bases");
+        for info in pkgs_info {
+            pkg_bases.insert(info.name.to_owned(), info.package_base.to_owned());
+        }
+    }
+
+    /// URL of RPC query at `path` on AUR of this session, with `queries` appended
+    fn rpc_url(&self, path: &str, queries: &[(&str, &str)]) -> Result<Url> {
+        let mut url = self.aur_url.join(path)?;
+        url.query_pairs_mut().extend_pairs(queries);
+        Ok(url)
+    }
+
+    /// Packages maintained by `maintainer`, sorted by name
+    pub fn search_maintained_pkgs(&self, maintainer: &str) -> Result<Vec<AurPackageSearchItem>> {
+        let client = self.client.build_client()?;
+        let url = self.rpc_url(AUR_PATH_RPC_SEARCH_MAINTAINER, &[("arg", maintainer)])?;
+        self.count_request();
+        let response = self.send_with_retry(client.get(url))?;
+        let search_result: AurPackageSearchResult = response.json()?;
+        let mut pkgs = search_result.results;
+        pkgs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(pkgs)
+    }
+
+    /// Suggest package names starting with `prefix`
+    /// See: https://wiki.archlinux.org/index.php/Aurweb_RPC_interface
+    pub fn suggest_pkgs(&self, prefix: &str) -> Result<Vec<String>> {
+        if prefix.trim().chars().count() < SUGGEST_MIN_PREFIX {
+            return Ok(Vec::new());
         }
 
-        Ok(results)
-    }
+        let client = self.client.build_client()?;
+        let url = self.rpc_url(AUR_PATH_RPC_SUGGEST, &[("arg", prefix.trim())])?;
+        self.count_request();
+        let response = self.send_with_retry(client.get(url))?;
+        let suggestions: Vec<String> = response.json()?;
+        Ok(suggestions)
+    }
+}
+
+/// For data from https://aur.archlinux.org/rpc?v=5&type=search&by=maintainer&arg=user
+/// See: https://wiki.archlinux.org/index.php/Aurweb_RPC_interface#search
+#[derive(Deserialize, Serialize, Default, PartialEq, Debug)]
+pub struct AurPackageSearchItem {
+    #[serde(rename(deserialize = "Name"))]
+    pub name: String,
+
+    #[serde(rename(deserialize = "Version"))]
+    pub version: String,
+
+    #[serde(rename(deserialize = "NumVotes"))]
+    pub votes: u64,
+
+    #[serde(rename(deserialize = "Popularity"))]
+    pub popularity: f64,
+
+    /// Timestamp when package was flagged out-of-date
+    #[serde(rename(deserialize = "OutOfDate"))]
+    pub out_of_date: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct AurPackageSearchResult {
+    results: Vec<AurPackageSearchItem>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_extract_aur_pkgs_no_sort_voted() {
@@ -714,6 +3017,29 @@ mod tests {
         assert_eq!(aur_packages.into_iter().filter(|pkg| pkg.voted).count(), 12);
     }
 
+    #[test]
+    fn test_extract_aur_pkgs_html_entities() {
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-aur-pkgs-html-entities.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        let aur_packages = AurPackageResults::from_html(&page).expect("Paring AUR package results");
+        assert_eq!(aur_packages.len(), 2);
+
+        assert_eq!(aur_packages[0].name, "ttf-m+");
+        assert_eq!(
+            aur_packages[0].description,
+            "Fonts & glyphs for <M+> that \"just work\" in user's terminal"
+        );
+        assert_eq!(aur_packages[0].maintainer, "o'brien");
+
+        assert_eq!(aur_packages[1].name, "foo&bar");
+        assert_eq!(aur_packages[1].description, "A -> B");
+        assert!(aur_packages[1].is_orphan());
+    }
+
     #[test]
     fn test_extract_aur_pkgs_sort_voted_with_orphan() {
         // Extract package list from html
@@ -730,12 +3056,48 @@ mod tests {
         assert_eq!(
             aur_packages
                 .into_iter()
-                .filter(|pkg| pkg.maintainer == "orphan")
+                .filter(|pkg| pkg.is_orphan())
                 .count(),
             12
         );
     }
 
+    #[test]
+    fn test_extract_aur_pkgs_truncated_rows() {
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-aur-pkgs-truncated-rows.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        let aur_packages = AurPackageResults::from_html(&page).expect("Paring AUR package results");
+        assert_eq!(aur_packages.len(), 3);
+
+        // Complete row
+        assert_eq!(aur_packages[0].name, "pacman-mirrorup");
+        assert_eq!(aur_packages[0].maintainer, "bpetlert");
+
+        // No maintainer column
+        assert_eq!(aur_packages[1].name, "networkd-broker");
+        assert!(aur_packages[1].voted);
+        assert!(aur_packages[1].notify);
+        assert_eq!(
+            aur_packages[1].description,
+            "An event broker daemon for systemd-networkd"
+        );
+        assert_eq!(aur_packages[1].maintainer, "");
+
+        // Only name and version columns
+        assert_eq!(
+            aur_packages[2],
+            AurPackageResultItem {
+                name: "aur-thumbsup".to_owned(),
+                version: "0.4.0-1".to_owned(),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn test_extract_login_error_page() {
         // Login success
@@ -758,51 +3120,496 @@ mod tests {
         let error_list = LoginErrorList::from_html(&page).expect("Paring login error");
         assert_eq!(error_list.errors.len(), 1);
         assert_eq!(error_list.errors[0], "Bad username or password.");
+        assert!(!error_list.is_suspended());
+        assert!(!error_list.challenge);
+        assert_eq!(
+            error_list.login_error().to_string(),
+            "Login failed: Bad username or password."
+        );
+
+        // Challenge, credentials not rejected
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-challenge.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        let error_list = LoginErrorList::from_html(&page).expect("Paring login error");
+        assert!(error_list.errors.is_empty());
+        assert!(error_list.challenge);
+        assert_eq!(
+            error_list.notices,
+            vec!["Too many login attempts, please complete the challenge below."]
+        );
+        let err = error_list.login_error();
+        assert!(is_invalid_session_error(&err));
+        assert_eq!(
+            err.to_string(),
+            "Login failed: AUR asks to solve a CAPTCHA or another challenge: \
+             Too many login attempts, please complete the challenge below. \
+             Credentials were not rejected, try later or login using a browser."
+        );
+
+        // Neither errors nor challenge
+        assert_eq!(
+            LoginErrorList::default().login_error().to_string(),
+            "Login failed: unexpected login page, possibly a rate limit or challenge. \
+             Credentials were not rejected, try later or login using a browser."
+        );
+    }
+
+    #[test]
+    fn test_maintenance_page() {
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-maintenance.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        assert!(is_maintenance_html(&page).unwrap());
+        let err = check_maintenance(&page).unwrap_err();
+        assert!(err.is::<Maintenance>(), "{}", err);
+
+        let auth = Authentication::new();
+        let err = auth.is_login_html(&page).unwrap_err();
+        assert!(err.is::<Maintenance>(), "{}", err);
+
+        // Package page is not
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        assert!(!is_maintenance_html(&page).unwrap());
+
+        // Nor package page with maintenance in its name
+        let page = Html::parse_document(&html_raw.replace("yay", "foo-maintenance-tools"));
+        assert!(page
+            .root_element()
+            .html()
+            .contains("Package Details: foo-maintenance-tools"));
+        assert!(!is_maintenance_html(&page).unwrap());
+        check_maintenance(&page).unwrap();
+    }
+
+    #[test]
+    fn test_pace() {
+        let mut auth = Authentication::new();
+        let start = Instant::now();
+        auth.pace();
+        auth.pace();
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        auth.set_request_delay(Duration::from_millis(50));
+        let start = Instant::now();
+        auth.pace();
+        auth.pace();
+        auth.pace();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_login_with_insecure_cookie_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.cookie");
+        fs::write(&path, "AURSID=abc; Path=/\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        // Refused before any request
+        let mut auth = Authentication::new();
+        let err = auth.login_with_cookie_file(&path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Cookie file `{}` is not secure", path.display())
+        );
+        assert!(!is_invalid_session_error(&err));
+        assert!(auth.cookie_jar.get("AURSID").is_none());
+    }
+
+    #[test]
+    fn test_login_suspended_page() {
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-suspended.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        let error_list = LoginErrorList::from_html(&page).expect("Paring login error");
+        assert!(error_list.is_suspended());
+
+        let auth = Authentication::new();
+        let err = auth.is_login_html(&page).unwrap_err();
+        assert!(err.is::<AccountSuspended>(), "{}", err);
+
+        // Not logged in, but not suspended
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-error.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        let err = auth.is_login_html(&page).unwrap_err();
+        assert_eq!(err.to_string(), "Not logged in.");
+    }
+
+    #[test]
+    fn test_check_login_page() {
+        let html_raw = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-in-page.html"
+        ));
+        let page = Html::parse_document(html_raw);
+        let auth = Authentication::new();
+        assert!(auth.is_login_html(&page).is_ok());
+    }
+
+    #[test]
+    fn test_current_user_html() {
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-in-page.html"
+        )));
+        assert_eq!(
+            current_user_html(&page).unwrap(),
+            Some("bpetlert".to_owned())
+        );
+
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-error.html"
+        )));
+        assert_eq!(current_user_html(&page).unwrap(), None);
+    }
+
+    #[test]
+    fn test_is_vote_html() {
+        // Voted package
+        let voted_pkg_page = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        ));
+        let page = Html::parse_document(voted_pkg_page);
+        let auth = Authentication::new();
+        assert_eq!(auth.is_vote_html(&page).unwrap(), Some(true));
+
+        // Unvoted package
+        let unvoted_pkg_page = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-unvoted.html"
+        ));
+        let page = Html::parse_document(unvoted_pkg_page);
+        let auth = Authentication::new();
+        assert_eq!(auth.is_vote_html(&page).unwrap(), Some(false));
+
+        // N/A
+        let not_pkg_info_page = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-in-page.html"
+        ));
+        let page = Html::parse_document(not_pkg_info_page);
+        let auth = Authentication::new();
+        assert_eq!(auth.is_vote_html(&page).unwrap(), None);
+    }
+
+    #[test]
+    fn test_verify_session_html() {
+        let auth = Authentication::new();
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        )));
+        assert!(auth.verify_session_html(&page).is_ok());
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-unvoted.html"
+        )));
+        assert!(auth.verify_session_html(&page).is_ok());
+
+        // Logged-in home page has no vote form
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-in-page.html"
+        )));
+        let err = auth.verify_session_html(&page).unwrap_err();
+        assert!(err.to_string().starts_with("Login verification failed"));
+        assert_eq!(
+            crate::error::exit_status(&err),
+            crate::error::AUTH_EXIT_STATUS
+        );
+
+        // Nor page of a session which is not accepted
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-error.html"
+        )));
+        let err = auth.verify_session_html(&page).unwrap_err();
+        assert!(err.to_string().starts_with("Login verification failed"));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let mut auth = Authentication::new();
+        assert!(!auth.is_past_deadline());
+        auth.set_deadline(Duration::from_secs(300));
+        assert!(!auth.is_past_deadline());
+        auth.set_deadline(Duration::ZERO);
+        assert!(auth.is_past_deadline());
+    }
+
+    #[test]
+    fn test_should_stop() {
+        let mut auth = Authentication::new();
+        assert!(!auth.should_stop("pkg1"));
+        assert_eq!(auth.stopped(), None);
+
+        auth.set_max_requests(2);
+        auth.count_request();
+        assert!(!auth.should_stop("pkg1"));
+        auth.count_request();
+        assert!(auth.should_stop("pkg2"));
+        assert_eq!(auth.stopped(), Some(Stop::RequestCap));
+
+        // Deadline takes precedence
+        auth.set_deadline(Duration::ZERO);
+        assert!(auth.should_stop("pkg2"));
+        assert_eq!(auth.stopped(), Some(Stop::Deadline));
+    }
+
+    #[test]
+    fn test_vote_response_html() {
+        let auth = Authentication::new();
+
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        )));
+        assert_eq!(
+            auth.vote_response_html(&page, true).unwrap(),
+            VoteResponse::Changed
+        );
+        assert_eq!(
+            auth.vote_response_html(&page, false).unwrap(),
+            VoteResponse::Unchanged(None)
+        );
+        assert_eq!(page_notice(&page).unwrap(), None);
+
+        // Page asks to login
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-error.html"
+        )));
+        assert_eq!(
+            auth.vote_response_html(&page, true).unwrap(),
+            VoteResponse::NotLoggedIn
+        );
+        assert_eq!(
+            page_notice(&page).unwrap(),
+            Some("Bad username or password.".to_owned())
+        );
+
+        // Voting too fast
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-vote-throttled.html"
+        )));
+        let advice =
+            "You are voting too fast. Please wait a few minutes before voting again.".to_owned();
+        assert_eq!(throttle_notice(&page).unwrap(), Some(advice.clone()));
+        assert_eq!(
+            auth.vote_response_html(&page, true).unwrap(),
+            VoteResponse::Throttled(advice)
+        );
+
+        // Login error is not a throttle notice
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-login-error.html"
+        )));
+        assert_eq!(throttle_notice(&page).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_search_result() {
+        let json = r#"{"resultcount":2,"results":[
+            {"ID":1,"Name":"pacman-mirrorup","PackageBaseID":1,"PackageBase":"pacman-mirrorup",
+             "Version":"0.3.0-1","Description":"","URL":null,"NumVotes":5,"Popularity":0.25,
+             "OutOfDate":null,"Maintainer":"bpetlert","FirstSubmitted":1,"LastModified":2},
+            {"ID":2,"Name":"aur-thumbsup","PackageBaseID":2,"PackageBase":"aur-thumbsup",
+             "Version":"0.4.0-1","Description":"","URL":null,"NumVotes":3,"Popularity":0,
+             "OutOfDate":1640995200,"Maintainer":"bpetlert","FirstSubmitted":1,"LastModified":2}
+            ],"type":"search","version":5}"#;
+        let result: AurPackageSearchResult = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            result.results,
+            vec![
+                AurPackageSearchItem {
+                    name: "pacman-mirrorup".to_owned(),
+                    version: "0.3.0-1".to_owned(),
+                    votes: 5,
+                    popularity: 0.25,
+                    out_of_date: None,
+                },
+                AurPackageSearchItem {
+                    name: "aur-thumbsup".to_owned(),
+                    version: "0.4.0-1".to_owned(),
+                    votes: 3,
+                    popularity: 0.0,
+                    out_of_date: Some(1640995200),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_requests_html() {
+        let auth = Authentication::new();
+
+        // Package with pending requests
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-pending-requests.html"
+        )));
+        assert_eq!(auth.pending_requests_html(&page).unwrap(), 2);
+
+        // No pending request
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        )));
+        assert_eq!(auth.pending_requests_html(&page).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_extract_form() {
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        )));
+        let auth = Authentication::new();
+        assert_eq!(
+            auth.extract_form(&page, "div#generic-form form").unwrap(),
+            Some(PageForm {
+                action: "/pkgbase/yay/".to_owned(),
+                fields: vec![
+                    ("action".to_owned(), "do_AddComment".to_owned()),
+                    ("ID".to_owned(), "115973".to_owned()),
+                    (
+                        "token".to_owned(),
+                        "FAKETOKENFAKETOKENFAKETOKENFAKET".to_owned()
+                    ),
+                ],
+            })
+        );
+
+        // Home page has no comment form
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-in-page.html"
+        )));
+        assert_eq!(
+            auth.extract_form(&page, "div#generic-form form").unwrap(),
+            None
+        );
     }
 
-    #[test]
-    fn test_check_login_page() {
-        let html_raw = include_str!(concat!(
+    #[test]
+    fn test_is_notify_html() {
+        let auth = Authentication::new();
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        )));
+        assert_eq!(auth.is_notify_html(&page).unwrap(), Some(false));
+        assert_eq!(
+            auth.extract_form(&page, "div#actionlist li form[action$=\"/notify/\"]")
+                .unwrap(),
+            Some(PageForm {
+                action: "/pkgbase/yay/notify/".to_owned(),
+                fields: vec![(
+                    "token".to_owned(),
+                    "FAKETOKENFAKETOKENFAKETOKENFAKET".to_owned()
+                )],
+            })
+        );
+
+        let page = Html::parse_document(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-unvoted.html"
+        )));
+        assert_eq!(auth.is_notify_html(&page).unwrap(), Some(true));
+
+        let page = Html::parse_document(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/tests/",
             "test-logged-in-page.html"
-        ));
-        let page = Html::parse_document(html_raw);
-        let auth = Authentication::new();
-        assert!(auth.is_login_html(&page).is_ok());
+        )));
+        assert_eq!(auth.is_notify_html(&page).unwrap(), None);
     }
 
     #[test]
-    fn test_is_vote_html() {
-        // Voted package
-        let voted_pkg_page = include_str!(concat!(
+    fn test_flag_html() {
+        let auth = Authentication::new();
+        let page = Html::parse_document(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/tests/",
             "test-logged-pkg-info-voted.html"
-        ));
-        let page = Html::parse_document(voted_pkg_page);
-        let auth = Authentication::new();
-        assert_eq!(auth.is_vote_html(&page).unwrap(), Some(true));
+        )));
+        assert!(!auth.is_flagged_html(&page).unwrap());
 
-        // Unvoted package
-        let unvoted_pkg_page = include_str!(concat!(
+        // Flagged by others, unflag is not allowed
+        let page = Html::parse_document(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/tests/",
-            "test-logged-pkg-info-unvoted.html"
-        ));
-        let page = Html::parse_document(unvoted_pkg_page);
-        let auth = Authentication::new();
-        assert_eq!(auth.is_vote_html(&page).unwrap(), Some(false));
+            "test-logged-pkg-info-flagged.html"
+        )));
+        assert!(auth.is_flagged_html(&page).unwrap());
+        assert_eq!(
+            auth.extract_form(&page, "div#actionlist li form[action$=\"/unflag/\"]")
+                .unwrap(),
+            None
+        );
 
-        // N/A
-        let not_pkg_info_page = include_str!(concat!(
+        let page = Html::parse_document(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/tests/",
-            "test-logged-in-page.html"
-        ));
-        let page = Html::parse_document(not_pkg_info_page);
-        let auth = Authentication::new();
-        assert_eq!(auth.is_vote_html(&page).unwrap(), None);
+            "test-logged-pkg-flag-form.html"
+        )));
+        assert_eq!(
+            auth.extract_form(&page, "form[action$=\"/flag/\"]")
+                .unwrap(),
+            Some(PageForm {
+                action: "/pkgbase/yay/flag/".to_owned(),
+                fields: vec![
+                    ("IDs[115973]".to_owned(), "1".to_owned()),
+                    ("ID".to_owned(), "115973".to_owned()),
+                    (
+                        "token".to_owned(),
+                        "FAKETOKENFAKETOKENFAKETOKENFAKET".to_owned()
+                    ),
+                ],
+            })
+        );
     }
 
     #[test]
@@ -844,12 +3651,769 @@ mod tests {
         assert_eq!(token, expect, "`{}` != `{}`", token, expect);
     }
 
+    #[test]
+    fn test_is_transient_error() {
+        // Stale session
+        assert!(!is_transient_error(&anyhow!("Not logged in.")));
+
+        // Connection refused
+        let err = Client::new()
+            .get("http://127.0.0.1:1")
+            .send()
+            .expect_err("Connection refused");
+        assert!(is_transient_error(&err.into()));
+    }
+
+    #[test]
+    fn test_is_login_redirect() {
+        for target in [
+            "https://aur.archlinux.org/",
+            "https://aur.archlinux.org/packages/",
+            "https://aur.archlinux.org/?next=%2Fpackages%2F",
+        ] {
+            assert!(
+                is_login_redirect(
+                    "aur.archlinux.org",
+                    StatusCode::FOUND,
+                    &Url::parse(target).unwrap()
+                ),
+                "{}",
+                target
+            );
+        }
+
+        // Other host or status
+        assert!(!is_login_redirect(
+            "aur.archlinux.org",
+            StatusCode::FOUND,
+            &Url::parse("https://archlinux.org/").unwrap()
+        ));
+        assert!(!is_login_redirect(
+            "aur.archlinux.org",
+            StatusCode::MOVED_PERMANENTLY,
+            &Url::parse("https://aur.archlinux.org/").unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_find_aursid() {
+        let mut headers = header::HeaderMap::new();
+        assert!(find_aursid(&headers).is_none());
+
+        // AURSID is not necessarily the first cookie
+        headers.append(header::SET_COOKIE, "AURLANG=en; Path=/".parse().unwrap());
+        headers.append(
+            header::SET_COOKIE,
+            "AURSID=0123456789abcdef; Path=/; HttpOnly".parse().unwrap(),
+        );
+        let cookie = find_aursid(&headers).expect("AURSID");
+        assert_eq!(cookie.value(), "0123456789abcdef");
+    }
+
+    /// Mock server answering each connection with next status of `statuses`,
+    /// joined to the number of requests it answered
+    fn mock_server(statuses: Vec<u16>) -> (String, thread::JoinHandle<usize>) {
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/packages/yay", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            for status in statuses.iter() {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                let body = format!("<html>{}</html>", status);
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+            statuses.len()
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn test_send_retrying() {
+        let retry = Retry {
+            retries: 3,
+            base_delay: Duration::from_millis(1),
+        };
+
+        // Succeed after server errors, each retry is counted
+        let requests = AtomicUsize::new(0);
+        let (url, server) = mock_server(vec![503, 503, 200]);
+        let response = send_retrying(Client::new().get(&url), &retry, &requests).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().unwrap(), "<html>200</html>");
+        assert_eq!(server.join().unwrap(), 3);
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+
+        // Not retried
+        let requests = AtomicUsize::new(0);
+        let (url, server) = mock_server(vec![404]);
+        let response = send_retrying(Client::new().get(&url), &retry, &requests).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(server.join().unwrap(), 1);
+        assert_eq!(requests.load(Ordering::Relaxed), 0);
+
+        // Response of the last retry
+        let requests = AtomicUsize::new(0);
+        let (url, server) = mock_server(vec![502, 503, 503, 500]);
+        let response = send_retrying(Client::new().get(&url), &retry, &requests).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(server.join().unwrap(), 4);
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+
+        // Connection refused
+        let requests = AtomicUsize::new(0);
+        let err =
+            send_retrying(Client::new().get("http://127.0.0.1:1"), &retry, &requests).unwrap_err();
+        assert!(is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_response_text_bad_gzip() {
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        // Mock server returns a body labeled as gzip, but not gzip
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let body = "<html>not gzip</html>";
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let client = Client::builder().gzip(true).build().unwrap();
+        let response = client
+            .get(format!("http://{}/packages/pacman-mirrorup", addr))
+            .send()
+            .unwrap();
+        let err = response_text(response).unwrap_err();
+        server.join().unwrap();
+        assert!(
+            err.to_string().starts_with(&format!(
+                "Failed to decode response body of `http://{}/packages/pacman-mirrorup` (is a proxy mangling it?)",
+                addr
+            )),
+            "{}",
+            err
+        );
+        assert_eq!(
+            crate::error::exit_status(&err),
+            crate::error::NETWORK_EXIT_STATUS
+        );
+    }
+
+    #[test]
+    fn test_is_invalid_session_error() {
+        assert!(is_invalid_session_error(&AurError::auth(anyhow!(
+            "Not logged in."
+        ))));
+        assert!(!is_invalid_session_error(&AurError::network(anyhow!(
+            "Unable to access"
+        ))));
+        assert!(!is_invalid_session_error(&Maintenance.into()));
+        assert!(!is_invalid_session_error(&AccountSuspended.into()));
+        let not_found = std::io::Error::from(ErrorKind::NotFound);
+        assert!(!is_invalid_session_error(&not_found.into()));
+    }
+
+    #[test]
+    fn test_login_url() {
+        let aur_url = AurUrl::default();
+        assert_eq!(
+            login_url(&aur_url, "foo", "bar", true).unwrap().as_str(),
+            "https://aur.archlinux.org/login?next=/&user=foo&passwd=bar&remember_me=on"
+        );
+        assert_eq!(
+            login_url(&aur_url, "foo", "bar", false).unwrap().as_str(),
+            "https://aur.archlinux.org/login?next=/&user=foo&passwd=bar"
+        );
+
+        let aur_url = AurUrl::parse("http://localhost:8080").unwrap();
+        assert_eq!(
+            login_url(&aur_url, "foo", "bar", false).unwrap().as_str(),
+            "http://localhost:8080/login?next=/&user=foo&passwd=bar"
+        );
+    }
+
+    #[test]
+    fn test_account_password() {
+        let mut account = Account {
+            pass: "bar".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(account.password().unwrap(), "bar");
+
+        account.pass = String::new();
+        account.pass_cmd = Some("printf '  s3cr3t p@ss\\n'".to_owned());
+        assert!(account.has_pass());
+        assert_eq!(account.password().unwrap(), "s3cr3t p@ss");
+
+        account.pass_cmd = Some("echo bar; exit 3".to_owned());
+        let err = account.password().unwrap_err();
+        assert_eq!(err.to_string(), "`pass_cmd` failed with exit status: 3");
+        assert!(
+            matches!(err.downcast_ref::<AurError>(), Some(err) if err.kind == AurErrorKind::Config)
+        );
+
+        account.pass_cmd = Some("true".to_owned());
+        assert!(account.password().is_err());
+    }
+
+    #[test]
+    fn test_aur_url() {
+        let aur_url = AurUrl::parse("https://aur.example.org/").unwrap();
+        assert_eq!(aur_url.host(), "aur.example.org");
+        assert_eq!(
+            aur_url.join("/pkgbase/yay/vote/").unwrap().as_str(),
+            "https://aur.example.org/pkgbase/yay/vote/"
+        );
+        assert_eq!(AurUrl::default().host(), "aur.archlinux.org");
+
+        assert!(AurUrl::parse("aur.example.org").is_err());
+        assert!(AurUrl::parse("ftp://aur.example.org").is_err());
+        assert!(AurUrl::parse("https://example.org/aur").is_err());
+        assert!(AurUrl::parse("https://aur.example.org/?next=/").is_err());
+    }
+
+    #[test]
+    fn test_redact_url() {
+        let url = Url::parse_with_params(
+            "https://aur.archlinux.org/login?next=/",
+            &[
+                ("user", "foo"),
+                ("passwd", "s3cr3t-p@ss"),
+                ("remember_me", "on"),
+            ],
+        )
+        .unwrap();
+        let redacted = redact_url(&url).to_string();
+        assert!(!redacted.contains("s3cr3t"), "{}", redacted);
+        assert!(!redacted.contains("foo"), "{}", redacted);
+        assert_eq!(
+            redacted,
+            "https://aur.archlinux.org/login?next=%2F&user=***&passwd=***&remember_me=on"
+        );
+
+        // No query
+        let url = Url::parse(DEFAULT_AUR_URL).unwrap();
+        assert_eq!(redact_url(&url).to_string(), "https://aur.archlinux.org/");
+    }
+
+    #[test]
+    fn test_rpc_url() {
+        let mut auth = Authentication::new();
+        assert_eq!(
+            auth.rpc_url(AUR_PATH_RPC_INFO, &[("arg[]", "yay"), ("arg[]", "gtk2+")])
+                .unwrap()
+                .as_str(),
+            "https://aur.archlinux.org/rpc?v=5&type=info&arg%5B%5D=yay&arg%5B%5D=gtk2%2B"
+        );
+
+        // RPC of self-hosted AUR
+        auth.set_aur_url("http://localhost:8080").unwrap();
+        assert_eq!(
+            auth.rpc_url(AUR_PATH_RPC_SUGGEST, &[("arg", "pacman")])
+                .unwrap()
+                .as_str(),
+            "http://localhost:8080/rpc?v=5&type=suggest&arg=pacman"
+        );
+    }
+
+    #[test]
+    fn test_pkg_page_url() {
+        assert_eq!(
+            pkg_page_url(&AurUrl::default(), "pacman-mirrorup")
+                .unwrap()
+                .as_str(),
+            "https://aur.archlinux.org/packages/pacman-mirrorup"
+        );
+        assert_eq!(
+            pkg_page_url(&AurUrl::default(), "gtk2+").unwrap().as_str(),
+            "https://aur.archlinux.org/packages/gtk2%2B"
+        );
+        assert_eq!(
+            pkg_page_url(&AurUrl::default(), "python-foo@1.0")
+                .unwrap()
+                .as_str(),
+            "https://aur.archlinux.org/packages/python-foo%401.0"
+        );
+    }
+
+    #[test]
+    fn test_voted_pkgs_page_url() {
+        assert_eq!(
+            voted_pkgs_page_url(&AurUrl::default(), 0, 250),
+            "https://aur.archlinux.org/packages/?O=0&SeB=nd&SB=w&SO=d&PP=250&do_Search=Go"
+        );
+        assert_eq!(
+            voted_pkgs_page_url(&AurUrl::default(), 1, 250),
+            "https://aur.archlinux.org/packages/?O=250&SeB=nd&SB=w&SO=d&PP=250&do_Search=Go"
+        );
+        assert_eq!(
+            voted_pkgs_page_url(&AurUrl::default(), 3, 50),
+            "https://aur.archlinux.org/packages/?O=150&SeB=nd&SB=w&SO=d&PP=50&do_Search=Go"
+        );
+    }
+
+    #[test]
+    fn test_set_page_size() {
+        let mut auth = Authentication::new();
+        assert!(auth.set_page_size(100).is_ok());
+        assert_eq!(auth.page_size, 100);
+        assert!(auth.set_page_size(120).is_err());
+        assert_eq!(auth.page_size, 100);
+    }
+
+    #[test]
+    fn test_set_min_tls_version() {
+        let mut auth = Authentication::new();
+        assert_eq!(auth.client.min_tls_version, None);
+        assert!(auth.set_min_tls_version("1.3").is_ok());
+        assert_eq!(auth.client.min_tls_version, Some("1.3".to_owned()));
+        assert!(auth.set_min_tls_version("1.1").is_err());
+        assert_eq!(auth.client.min_tls_version, Some("1.3".to_owned()));
+        assert_eq!(tls_version("1.2").unwrap(), tls::Version::TLS_1_2);
+    }
+
+    #[test]
+    fn test_set_proxy() {
+        let mut auth = Authentication::new();
+        assert_eq!(auth.client.proxy, None);
+        auth.set_proxy("http://proxy.example.org:3128").unwrap();
+        assert_eq!(
+            auth.client.proxy.as_deref(),
+            Some("http://proxy.example.org:3128")
+        );
+        assert!(auth.client.build_client().is_ok());
+        assert!(auth.set_proxy("ftp://proxy.example.org").is_err());
+        assert_eq!(
+            auth.client.proxy.as_deref(),
+            Some("http://proxy.example.org:3128")
+        );
+    }
+
+    #[test]
+    fn test_logout_without_cookie_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let account = Account {
+            user: "foo".to_owned(),
+            cookie_file: tempdir.path().join("aur-thumbsup-foo.cookie"),
+            ..Default::default()
+        };
+        let mut auth = Authentication::new();
+        assert_eq!(auth.logout(&account).unwrap(), LogoutResult::NoCookieFile);
+    }
+
+    #[test]
+    fn test_group_by_base() {
+        let packages: Vec<String> = ["foo", "bar", "foo-docs", "gone", "foo-git", "gone"]
+            .iter()
+            .map(|pkg| pkg.to_string())
+            .collect();
+        let bases: HashMap<String, String> = [
+            ("foo", "foo"),
+            ("foo-docs", "foo"),
+            ("bar", "bar"),
+            ("foo-git", "foo-git"),
+        ]
+        .iter()
+        .map(|(pkg, base)| (pkg.to_string(), base.to_string()))
+        .collect();
+        let (firsts, group_of) = group_by_base(&packages, &bases);
+        assert_eq!(firsts, vec!["foo", "bar", "gone", "foo-git", "gone"]);
+        assert_eq!(group_of, vec![0, 1, 0, 2, 3, 4]);
+
+        // Without known bases, each package is voted
+        let (firsts, group_of) = group_by_base(&packages, &HashMap::new());
+        assert_eq!(firsts, packages);
+        assert_eq!(group_of, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_record_pkg_bases() {
+        let auth = Authentication::new();
+        let info = |name: &str, base: &str| AurPackageInfoItem {
+            name: name.to_owned(),
+            package_base: base.to_owned(),
+            ..Default::default()
+        };
+        auth.record_pkg_bases(&[info("foo", "foo"), info("foo-docs", "foo")]);
+        auth.record_pkg_bases(&[info("bar", "bar")]);
+
+        let packages: Vec<String> = ["foo-docs", "bar", "foo"]
+            .iter()
+            .map(|pkg| pkg.to_string())
+            .collect();
+        let (firsts, group_of) = group_by_base(&packages, &auth.pkg_bases.lock().unwrap());
+        assert_eq!(firsts, vec!["foo-docs", "bar"]);
+        assert_eq!(group_of, vec![0, 1, 0]);
+    }
+
+    /// Mock AUR over HTTP/2: RPC info query answers `rpc_body`, any other path is a voted
+    /// package page. Return its base URL and paths of requests it received.
+    fn mock_aur_h2(rpc_body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        use hyper::{
+            service::{make_service_fn, service_fn},
+            Body, Request, Response, Server,
+        };
+        use std::{convert::Infallible, net::TcpListener};
+
+        let pkg_page: &'static str = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-logged-pkg-info-voted.html"
+        ));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&paths);
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let received = Arc::clone(&received);
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                            let path = request.uri().path().to_owned();
+                            received.lock().unwrap().push(path.clone());
+                            let body = match path.as_str() {
+                                "/rpc" => rpc_body,
+                                _ => pkg_page,
+                            };
+                            async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+                        }))
+                    }
+                });
+                Server::from_tcp(listener)
+                    .unwrap()
+                    .http2_only(true)
+                    .serve(make_service)
+                    .await
+                    .unwrap();
+            });
+        });
+        (url, paths)
+    }
+
+    #[test]
+    fn test_vote_resolves_pkg_bases() {
+        let (url, paths) = mock_aur_h2(
+            r#"{"results":[
+                {"Name":"foo","Version":"1-1","PackageBase":"foo","FirstSubmitted":0,"LastModified":0},
+                {"Name":"foo-git","Version":"1-1","PackageBase":"foo-git","FirstSubmitted":0,"LastModified":0},
+                {"Name":"foo-docs","Version":"1-1","PackageBase":"foo","FirstSubmitted":0,"LastModified":0}
+            ]}"#,
+        );
+        let mut auth = Authentication::new();
+        auth.set_aur_url(&url).unwrap();
+        auth.session = Some(auth.client.build_client().unwrap());
+
+        // Bases are not known before vote
+        let packages: Vec<String> = ["foo", "foo-git", "foo-docs"]
+            .iter()
+            .map(|pkg| pkg.to_string())
+            .collect();
+        let results = auth.vote(&packages, |_| Ok(())).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                ("foo".to_owned(), VoteResult::AlreadyVoted),
+                ("foo-git".to_owned(), VoteResult::AlreadyVoted),
+                ("foo-docs".to_owned(), VoteResult::AlreadyVoted),
+            ]
+        );
+
+        // One info query, then pages of first package of each base only
+        let mut paths = paths.lock().unwrap().clone();
+        assert_eq!(paths.remove(0), "/rpc");
+        paths.sort();
+        assert_eq!(paths, vec!["/packages/foo", "/packages/foo-git"]);
+        assert_eq!(auth.requests.load(Ordering::Relaxed), 3);
+
+        // Known bases are not queried again
+        auth.vote(&packages, |_| Ok(())).unwrap();
+        assert_eq!(auth.requests.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_set_timeouts() {
+        let mut auth = Authentication::new();
+        assert_eq!(auth.client.connect_timeout, Duration::from_secs(10));
+        assert_eq!(auth.client.request_timeout, Duration::from_secs(30));
+        auth.set_timeouts(5, 60).unwrap();
+        assert_eq!(auth.client.connect_timeout, Duration::from_secs(5));
+        assert_eq!(auth.client.request_timeout, Duration::from_secs(60));
+        assert!(auth.set_timeouts(0, 60).is_err());
+        assert_eq!(auth.client.connect_timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_send_error() {
+        use std::net::TcpListener;
+
+        // Accept connection, never respond
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/packages/foo", listener.local_addr().unwrap());
+        let client = Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let err = send_error(client.get(&url).send().unwrap_err());
+        assert_eq!(err.to_string(), format!("Request to `{}` timed out", url));
+        assert!(is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_set_concurrent_pages() {
+        let mut auth = Authentication::new();
+        assert_eq!(auth.concurrent_pages, 1);
+        assert!(auth.set_concurrent_pages(4).is_ok());
+        assert_eq!(auth.concurrent_pages, 4);
+        assert!(auth.set_concurrent_pages(0).is_err());
+        assert!(auth.set_concurrent_pages(MAX_CONCURRENT_PAGES + 1).is_err());
+        assert_eq!(auth.concurrent_pages, 4);
+    }
+
+    #[test]
+    fn test_report_progress() {
+        static DONE: AtomicUsize = AtomicUsize::new(0);
+        let mut auth = Authentication::new();
+        auth.report_progress(1, 2);
+        auth.set_progress(|done, _| DONE.store(done, Ordering::Relaxed));
+        auth.report_progress(2, 3);
+        assert_eq!(DONE.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_set_concurrent_votes() {
+        let mut auth = Authentication::new();
+        assert_eq!(auth.concurrent_votes, DEFAULT_CONCURRENT_VOTES);
+        assert!(auth.set_concurrent_votes(MAX_CONCURRENT_VOTES).is_ok());
+        assert_eq!(auth.concurrent_votes, MAX_CONCURRENT_VOTES);
+        assert!(auth.set_concurrent_votes(0).is_err());
+        assert!(auth.set_concurrent_votes(MAX_CONCURRENT_VOTES + 1).is_err());
+        assert_eq!(auth.concurrent_votes, MAX_CONCURRENT_VOTES);
+    }
+
+    #[test]
+    fn test_map_concurrently() {
+        let pkgs = vec!["slow".to_owned(), "yay".to_owned(), "paru".to_owned()];
+        // Results keep order of packages, not the order they finish
+        let results = map_concurrently(&pkgs, |pkg| {
+            if pkg == "slow" {
+                thread::sleep(Duration::from_millis(50));
+            }
+            pkg.len()
+        });
+        assert_eq!(results, vec![4, 3, 4]);
+    }
+
+    #[test]
+    fn test_collect_voted_pkgs() {
+        let page = |pkgs: &[(&str, bool)]| -> Result<AurPackageResults> {
+            Ok(pkgs
+                .iter()
+                .map(|(name, voted)| AurPackageResultItem {
+                    name: name.to_string(),
+                    voted: *voted,
+                    ..Default::default()
+                })
+                .collect())
+        };
+        let names = |pkgs: &AurPackageResults| -> Vec<String> {
+            pkgs.iter().map(|pkg| pkg.name.to_owned()).collect()
+        };
+
+        let mut no_op = |_: &AurPackageResultItem| Ok(());
+
+        // All voted, need more pages
+        let mut voted_pkgs = AurPackageResults::new();
+        let mut streamed: Vec<String> = Vec::new();
+        let done = collect_voted_pkgs(
+            &mut voted_pkgs,
+            vec![page(&[("a", true), ("b", true)]), page(&[("c", true)])],
+            false,
+            &mut |pkg: &AurPackageResultItem| {
+                streamed.push(pkg.name.to_owned());
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(streamed, vec!["a", "b", "c"]);
+        assert!(!done);
+        assert_eq!(names(&voted_pkgs), vec!["a", "b", "c"]);
+
+        // Stop at the first not voted package, ignore later pages
+        let done = collect_voted_pkgs(
+            &mut voted_pkgs,
+            vec![
+                page(&[("d", true), ("e", false)]),
+                page(&[("f", false)]),
+                Err(anyhow!("Failed page after the end")),
+            ],
+            false,
+            &mut no_op,
+        )
+        .unwrap();
+        assert!(done);
+        assert_eq!(names(&voted_pkgs), vec!["a", "b", "c", "d"]);
+
+        // Stop at an empty page
+        let mut voted_pkgs = AurPackageResults::new();
+        let done = collect_voted_pkgs(
+            &mut voted_pkgs,
+            vec![page(&[]), page(&[("a", true)])],
+            false,
+            &mut no_op,
+        );
+        assert!(done.unwrap());
+        assert!(voted_pkgs.is_empty());
+
+        // Failed page before the end
+        let mut voted_pkgs = AurPackageResults::new();
+        let done = collect_voted_pkgs(
+            &mut voted_pkgs,
+            vec![page(&[("a", true)]), Err(anyhow!("Failed page"))],
+            false,
+            &mut no_op,
+        );
+        assert!(done.is_err());
+
+        // Full scan skips not voted packages until an empty page
+        let mut voted_pkgs = AurPackageResults::new();
+        let done = collect_voted_pkgs(
+            &mut voted_pkgs,
+            vec![
+                page(&[("a", true), ("b", false)]),
+                page(&[("c", true)]),
+                page(&[]),
+            ],
+            true,
+            &mut no_op,
+        )
+        .unwrap();
+        assert!(done);
+        assert_eq!(names(&voted_pkgs), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_parse_cookies_txt() {
+        let content = "# Netscape HTTP Cookie File\n\
+            \n\
+            aur.archlinux.org\tFALSE\t/\tTRUE\t1893456000\tAURTZ\tUTC\n\
+            #HttpOnly_aur.archlinux.org\tFALSE\t/\tTRUE\t0\tAURSID\tabc123\n\
+            .archlinux.org\tTRUE\t/\tFALSE\t1893456000\tOTHER\tfoo\n\
+            wiki.archlinux.org\tFALSE\t/\tTRUE\t1893456000\tAURSID\tnot-aur\n";
+        let cookies = parse_cookies_txt(content, "aur.archlinux.org").unwrap();
+        assert_eq!(cookies.iter().count(), 2);
+
+        let aursid = cookies.get("AURSID").unwrap();
+        assert_eq!(aursid.value(), "abc123");
+        assert_eq!(aursid.http_only(), Some(true));
+        assert_eq!(aursid.expires(), None);
+
+        let aurtz = cookies.get("AURTZ").unwrap();
+        assert_eq!(aurtz.value(), "UTC");
+        assert_eq!(
+            aurtz.expires_datetime().map(|d| d.unix_timestamp()),
+            Some(1893456000)
+        );
+
+        assert!(
+            parse_cookies_txt("aur.archlinux.org\tAURSID\tabc123", "aur.archlinux.org").is_err()
+        );
+    }
+
+    #[test]
+    fn test_cookie_headers() {
+        let mut auth = Authentication::new();
+        auth.cookie_jar.add(Cookie::new("AURLANG", "de"));
+        auth.cookie_jar.add(Cookie::new("AURSID", "abc123"));
+
+        let cookies = |auth: &Authentication| -> Vec<String> {
+            auth.cookie_headers()
+                .unwrap()
+                .get_all(header::COOKIE)
+                .iter()
+                .map(|value| value.to_str().unwrap().to_owned())
+                .collect()
+        };
+        // Saved AURLANG is replaced
+        assert_eq!(cookies(&auth), vec!["AURLANG=en", "AURSID=abc123"]);
+
+        auth.set_language("fr");
+        assert_eq!(cookies(&auth), vec!["AURLANG=fr", "AURSID=abc123"]);
+    }
+
+    #[test]
+    fn test_cookie_expiration() {
+        let now = OffsetDateTime::now_utc();
+        let expired = Cookie::build("AURTZ", "UTC")
+            .expires(now - time::Duration::days(1))
+            .finish();
+        assert!(is_cookie_expired(&expired, now));
+
+        let valid = Cookie::build("AURTZ", "UTC")
+            .expires(now + time::Duration::days(1))
+            .finish();
+        assert!(!is_cookie_expired(&valid, now));
+
+        let session = Cookie::new("AURTZ", "UTC");
+        assert!(!is_cookie_expired(&session, now));
+    }
+
+    #[test]
+    fn test_load_cookie_file() {
+        let mut cookie_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            cookie_file,
+            "AURTZ=UTC; Path=/; Expires=Wed, 01 Jan 2020 00:00:00 GMT"
+        )
+        .unwrap();
+        writeln!(cookie_file, "AURSID=FAKESID; Path=/; HttpOnly").unwrap();
+
+        let cookie_jar = load_cookie_file(cookie_file.path()).unwrap();
+        assert_eq!(cookie_jar.get("AURSID").unwrap().value(), "FAKESID");
+        let aurtz = cookie_jar.get("AURTZ").unwrap();
+        assert!(is_cookie_expired(aurtz, OffsetDateTime::now_utc()));
+    }
+
+    #[test]
+    fn test_suggest_pkgs_short_prefix() {
+        // No request for too short prefix
+        let auth = Authentication::new();
+        assert!(auth.suggest_pkgs("").unwrap().is_empty());
+        assert!(auth.suggest_pkgs(" p ").unwrap().is_empty());
+        assert_eq!(auth.requests.load(Ordering::Relaxed), 0);
+    }
+
     #[test]
     fn test_aur_info_query() {
         let pkgs = vec!["pacman-mirrorup".to_owned(), "networkd-broker".to_owned()];
-        let aur_pkg_info: AurPackageInfo = AurPackageInfo::info_query(&pkgs).unwrap();
+        let auth = Authentication::new();
+        let aur_pkg_info: AurPackageInfo = auth.info_query(&pkgs).unwrap();
+        assert_eq!(auth.requests.load(Ordering::Relaxed), 1);
         assert_eq!(aur_pkg_info.len(), 2);
         assert_eq!(aur_pkg_info[0].name, "networkd-broker");
         assert_eq!(aur_pkg_info[1].name, "pacman-mirrorup");
+        assert_eq!(aur_pkg_info[1].package_base, "pacman-mirrorup");
     }
 }
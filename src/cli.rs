@@ -0,0 +1,480 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Instant,
+};
+use tracing::{debug, error};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+use crate::args::{Arguments, Commands, OutputFormat};
+use crate::aur::SessionOptions;
+use crate::cmds::autovote::{autovote, autovote_hook, AutovoteOptions};
+use crate::cmds::check::{check, CheckOptions};
+use crate::cmds::checkconfig::check_config;
+use crate::cmds::comment::comment;
+use crate::cmds::completepackages::complete_packages;
+use crate::cmds::completions::completions;
+use crate::cmds::createconfig::create_config;
+use crate::cmds::diff::diff;
+use crate::cmds::doctor::doctor;
+use crate::cmds::ensure::ensure;
+use crate::cmds::export::export;
+use crate::cmds::flag::flag;
+use crate::cmds::import::import;
+use crate::cmds::importsession::import_session;
+use crate::cmds::info::info;
+use crate::cmds::list::{list, ListOptions, Pagination};
+use crate::cmds::logout::logout;
+use crate::cmds::maintained::maintained;
+use crate::cmds::notify::notify;
+use crate::cmds::renewcookie::renew_cookie;
+use crate::cmds::revote::revote;
+use crate::cmds::stats::stats;
+use crate::cmds::unvote::{unvote, UnvoteOptions};
+use crate::cmds::unvoteall::{unvote_all, UnvoteAllOptions};
+use crate::cmds::verifysession::verify_session;
+use crate::cmds::vote::{vote, VoteOptions};
+use crate::cmds::voteall::vote_all;
+use crate::config::{self, Configuration};
+use crate::error::{self, ExitStatus};
+use crate::helper::{self, load_exclusions, load_packages, read_pkgs};
+use crate::theme::{self, ColorMode, Style, Symbols, Theme};
+use crate::{progress, stats, timing};
+
+fn init_log(log_file: Option<File>) -> Result<()> {
+    let filter = match EnvFilter::try_from_env("RUST_LOG") {
+        Ok(f) => f,
+        Err(_) => EnvFilter::try_new("aur_thumbsup=warn")?,
+    };
+    let file_layer =
+        log_file.map(|file| fmt::layer().with_ansi(false).with_writer(Mutex::new(file)));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().without_time())
+        .with(file_layer)
+        .try_init()
+        .expect("Initialize tracing-subscriber");
+    Ok(())
+}
+
+fn open_log_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|err| anyhow!("Unable to open log file `{}`: {}", path.display(), err))
+}
+
+fn run_cmd(
+    config: PathBuf,
+    cmd: Commands,
+    format: OutputFormat,
+    options: &SessionOptions,
+    cache_dir: Option<&Path>,
+    style: &Style,
+) -> Result<()> {
+    if !cmd.supports_format(format) {
+        return Err(anyhow!(
+            "`{}` does not support `--format {}`",
+            cmd.name(),
+            format
+        ));
+    }
+
+    match cmd {
+        Commands::Vote {
+            packages,
+            package,
+            from_file,
+            if_installed,
+            pick,
+            dry_run,
+            ignore_missing,
+        } => vote(
+            config,
+            load_packages(packages, package, from_file, io::stdin())?,
+            &VoteOptions {
+                if_installed,
+                pick,
+                dry_run,
+                ignore_missing,
+            },
+            options,
+            format,
+            style,
+        )?,
+        Commands::Unvote {
+            packages,
+            package,
+            from_file,
+            only_orphaned,
+            stale,
+            dry_run,
+            ignore_missing,
+        } => unvote(
+            config,
+            load_packages(packages, package, from_file, io::stdin())?,
+            &UnvoteOptions {
+                only_orphaned,
+                stale,
+                dry_run,
+                ignore_missing,
+            },
+            options,
+            format,
+            style,
+        )?,
+        Commands::VoteAll { dry_run } => vote_all(config, dry_run, options, format, style)?,
+        Commands::UnvoteAll {
+            exclude,
+            exclude_file,
+            resume,
+            dry_run,
+            yes,
+        } => unvote_all(
+            config,
+            &load_exclusions(&exclude, exclude_file)?,
+            &UnvoteAllOptions {
+                resume,
+                dry_run,
+                yes,
+            },
+            options,
+            cache_dir,
+            format,
+            style,
+        )?,
+        Commands::Check {
+            packages,
+            package,
+            from_file,
+            exit_code,
+            expect,
+            via_voted_list,
+            summary,
+        } => check(
+            config,
+            load_packages(packages, package, from_file, io::stdin())?,
+            &CheckOptions {
+                exit_code,
+                expect,
+                via_voted_list,
+                summary,
+            },
+            format,
+            options,
+            style,
+        )?,
+        Commands::List {
+            group_by_base,
+            only_orphaned,
+            installed,
+            outdated,
+            show_requests,
+            template,
+            no_summary,
+            user,
+            page,
+            per_page,
+            head,
+            tail,
+            graph,
+            sort,
+            reverse,
+            verbose,
+        } => list(
+            config,
+            &ListOptions {
+                group_by_base,
+                only_orphaned,
+                installed,
+                outdated,
+                show_requests,
+                template: template.as_deref(),
+                no_summary,
+                user: user.as_deref(),
+                pagination: Pagination::from_args(page, per_page, head, tail),
+                graph,
+                sort,
+                reverse,
+                verbose,
+            },
+            options,
+            format,
+            style,
+        )?,
+        Commands::Autovote {
+            exclude,
+            exclude_file,
+            vote_only,
+            unvote_only,
+            skip_newer,
+            additive,
+            hook: false,
+            count_only,
+            yes,
+            scan_mode,
+            explicit_only,
+            dry_run,
+        } => autovote(
+            config,
+            &load_exclusions(&exclude, exclude_file)?,
+            &AutovoteOptions {
+                vote_only,
+                unvote_only,
+                additive,
+                count_only,
+                yes,
+                skip_newer,
+                scan_mode,
+                explicit_only,
+                dry_run,
+            },
+            options,
+            format,
+            style,
+        )?,
+        Commands::Autovote {
+            exclude,
+            exclude_file,
+            vote_only,
+            unvote_only,
+            additive,
+            hook: true,
+            ..
+        } => autovote_hook(
+            config,
+            &read_pkgs(io::stdin())?,
+            &load_exclusions(&exclude, exclude_file)?,
+            &AutovoteOptions {
+                vote_only,
+                unvote_only,
+                additive,
+                ..Default::default()
+            },
+            options,
+            style,
+        )?,
+        Commands::CreateConfig {
+            path,
+            dry_run,
+            show_secrets,
+        } => create_config(path, dry_run, show_secrets)?,
+        Commands::CheckConfig { path } => check_config(path)?,
+        Commands::CompletePackages { prefix } => complete_packages(config, &prefix, options)?,
+        Commands::Completions { shell } => completions(shell)?,
+        Commands::VerifySession { path } => verify_session(path)?,
+        Commands::RenewCookie => renew_cookie(config)?,
+        Commands::Logout => logout(config)?,
+        Commands::Maintained => maintained(config, options, format, style)?,
+        Commands::Stats => stats(config, options, format, style)?,
+        Commands::Diff {
+            exclude,
+            exclude_file,
+            scan_mode,
+            explicit_only,
+            skip_newer,
+        } => diff(
+            config,
+            &load_exclusions(&exclude, exclude_file)?,
+            &AutovoteOptions {
+                scan_mode,
+                explicit_only,
+                skip_newer,
+                ..Default::default()
+            },
+            options,
+            format,
+            style,
+        )?,
+        Commands::Import {
+            path,
+            resume,
+            dry_run,
+        } => import(config, path, resume, dry_run, options, cache_dir, style)?,
+        Commands::Export { path, force } => export(config, path, force, options, format)?,
+        Commands::Doctor => doctor(config, options, format, style)?,
+        Commands::Comment { package, text } => comment(config, &package, &text, options, style)?,
+        Commands::ImportSession {
+            aursid,
+            cookies_txt,
+        } => import_session(
+            config,
+            aursid.as_deref(),
+            cookies_txt.as_deref(),
+            options,
+            style,
+        )?,
+        Commands::Info { packages } => info(config, packages, options, format, style)?,
+        Commands::Flag { package, reason } => {
+            flag(config, &package, Some(&reason), options, style)?
+        }
+        Commands::Unflag { package } => flag(config, &package, None, options, style)?,
+        Commands::Notify { packages, off } => notify(config, &packages, !off, options, style)?,
+        Commands::EnsureVoted { packages } => ensure(config, packages, true, options, style)?,
+        Commands::EnsureUnvoted { packages } => ensure(config, packages, false, options, style)?,
+        Commands::Revote { dry_run } => revote(config, dry_run, options, style)?,
+    }
+
+    Ok(())
+}
+
+/// Sub-command from `default_command` in configuration, if configuration exists
+fn default_command(config: &Path) -> Result<Option<Commands>> {
+    if !config.exists() {
+        return Ok(None);
+    }
+
+    let cmd = Configuration::from_file(config)?.default_command()?;
+    if let Some(cmd) = &cmd {
+        debug!("Run default command `{}`", cmd.name());
+    }
+    Ok(cmd)
+}
+
+/// Verify configuration and login for `--check`, without running sub-command
+fn preflight(config: &Path, options: &SessionOptions, format: OutputFormat) -> Result<()> {
+    if !matches!(format, OutputFormat::Human | OutputFormat::Json) {
+        return Err(anyhow!("`--check` does not support `--format {}`", format));
+    }
+
+    let config = Configuration::load_and_verify_config(config)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+
+    match format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({"status": "ok", "user": config.account.user})
+        ),
+        _ => println!("OK"),
+    }
+    Ok(())
+}
+
+/// Theme from `--theme`, otherwise from configuration, if configuration is readable
+fn configured_theme(theme: Option<Theme>, config: Option<&Configuration>) -> Theme {
+    theme.or_else(|| config?.theme).unwrap_or_default()
+}
+
+fn configured_symbols(symbols: bool, no_unicode: bool, config: Option<&Configuration>) -> Symbols {
+    let symbols = symbols || config.and_then(|config| config.symbols).unwrap_or(false);
+    match (symbols, no_unicode) {
+        (false, _) => Symbols::Off,
+        (true, false) => Symbols::Unicode,
+        (true, true) => Symbols::Ascii,
+    }
+}
+
+fn run_app() -> Result<()> {
+    let arguments = Arguments::parse();
+    let (log_file, log_file_err) = match arguments.log_file.as_deref().map(open_log_file) {
+        Some(Ok(file)) => (Some(file), None),
+        Some(Err(err)) => (None, Some(err)),
+        None => (None, None),
+    };
+    init_log(log_file).expect("Initialize logging");
+    if let Some(err) = log_file_err {
+        return Err(err);
+    }
+    debug!("Run with {:?}", arguments);
+    config::set_overrides(&arguments.overrides);
+    config::set_account(arguments.account.as_deref());
+    // Settings applied before any sub-command, a broken file is reported by the sub-command itself
+    let file_config = Configuration::from_file(&arguments.config).ok();
+    let style = Style::new(
+        configured_theme(arguments.theme, file_config.as_ref()),
+        configured_symbols(
+            arguments.symbols,
+            arguments.no_unicode,
+            file_config.as_ref(),
+        ),
+        arguments.plain,
+    );
+    // Plain output is never colored, even text colored outside of `Style`
+    theme::set_color(match arguments.plain {
+        true => ColorMode::Never,
+        false => arguments.color,
+    });
+    if let Some(secs) = file_config
+        .as_ref()
+        .and_then(|config| config.pacman_timeout)
+    {
+        helper::set_pacman_timeout(secs);
+    }
+
+    let options = SessionOptions {
+        page_size: arguments.page_size,
+        concurrent_pages: arguments.concurrent_pages,
+        concurrent_votes: arguments.concurrent_votes,
+        no_login_cache: arguments.no_login_cache,
+        verify_login: arguments.verify_login,
+        deadline: arguments.deadline,
+        request_delay_ms: arguments.delay,
+        progress: progress::enabled(arguments.format) && !arguments.quiet,
+        refresh: arguments.refresh,
+    };
+
+    if arguments.check {
+        return preflight(&arguments.config, &options, arguments.format);
+    }
+
+    let cmd = match arguments.cmd {
+        Some(cmd) => Some(cmd),
+        None => default_command(&arguments.config)?,
+    };
+
+    if let Some(cmd) = cmd {
+        let cmd_name = cmd.name();
+        helper::set_quiet(arguments.quiet && cmd.supports_quiet());
+        let start = Instant::now();
+        timing::start();
+
+        let result = run_cmd(
+            arguments.config,
+            cmd,
+            arguments.format,
+            &options,
+            arguments.cache_dir.as_deref(),
+            &style,
+        );
+        // Bar of a run stopped before all packages are processed
+        progress::clear();
+
+        if arguments.timing {
+            eprintln!("{}", timing::report(cmd_name, start.elapsed()));
+        }
+        if arguments.stats {
+            let retry_stats = stats::retry_stats();
+            match arguments.format {
+                OutputFormat::Json => {
+                    eprintln!("{}", serde_json::json!({ "retry_stats": retry_stats }))
+                }
+                _ => eprintln!("{}", stats::report(&retry_stats)),
+            }
+        }
+
+        return result;
+    }
+
+    Ok(())
+}
+
+/// Run the command line application, returning its exit status
+pub fn run() -> i32 {
+    match run_app() {
+        Ok(_) => 0,
+        Err(err) => {
+            if !err.is::<ExitStatus>() {
+                error!("{}", err);
+            }
+            error::exit_status(&err)
+        }
+    }
+}
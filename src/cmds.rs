@@ -1,8 +1,27 @@
 pub mod autovote;
 pub mod check;
 pub mod checkconfig;
+pub mod comment;
+pub mod completepackages;
+pub mod completions;
 pub mod createconfig;
+pub mod diff;
+pub mod doctor;
+pub mod ensure;
+pub mod export;
+pub mod flag;
+pub mod import;
+pub mod importsession;
+pub mod info;
 pub mod list;
+pub mod logout;
+pub mod maintained;
+pub mod notify;
+pub mod renewcookie;
+pub mod revote;
+pub mod stats;
 pub mod unvote;
 pub mod unvoteall;
+pub mod verifysession;
 pub mod vote;
+pub mod voteall;
@@ -1,33 +1,258 @@
 use anyhow::Result;
-use std::{collections::HashMap, fmt::Write, path::Path};
+use dialoguer::Confirm;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    thread,
+    time::Duration,
+};
+use tracing::{debug, warn};
 
 use crate::{
-    aur::{AurInfoQuery, AurPackageInfo, Authentication},
-    cmds::{unvote, vote},
+    args::{OutputFormat, ScanMode},
+    aur::{AurPackageInfoItem, Authentication, SessionOptions, VoteResult},
+    cmds::{
+        unvote,
+        vote::{self, print_entries, VoteEntry},
+    },
     config::Configuration,
-    helper::{list_installed_pkgs_repo, list_repos, PkgName, PkgVersion, SelectRepository},
+    error::{check_failures, check_stopped},
+    helper::{
+        is_interactive, list_explicitly_installed_pkgs, list_foreign_pkgs, list_installed_pkgs,
+        list_installed_pkgs_repo, list_repos, print_flush, vercmp, PkgName, PkgVersion,
+        SelectRepository, Versioning,
+    },
+    theme::Style,
+    timing,
 };
 
-pub fn autovote<P: AsRef<Path>>(config_path: P) -> Result<()> {
-    // [1] Get non-official repositories
-    let non_official = list_repos(SelectRepository::NonOfficial)?;
+/// Number of packages autovote would vote/unvote, for `--count-only`
+#[derive(Serialize, PartialEq, Debug)]
+struct AutovoteCount {
+    to_vote: usize,
+    to_unvote: usize,
+}
 
-    // [2] Get installed packages from all non-official repositories.
-    let mut installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
-    for repo in non_official.iter() {
-        let pkgs_in_repo = list_installed_pkgs_repo(repo)?;
-        for pkg in pkgs_in_repo.iter() {
-            if !installed_pkgs.contains_key(pkg.0) {
-                installed_pkgs.insert(pkg.0.to_owned(), pkg.1.to_owned());
+/// Packages autovote would vote/unvote, for `--dry-run` and `diff`
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AutovotePlan<'a> {
+    pub to_vote: &'a [PkgName],
+    pub to_unvote: &'a [PkgName],
+}
+
+/// JSON output of autovote, (un)vote results with their summary
+#[derive(Serialize, PartialEq, Debug)]
+struct AutovoteOutput {
+    results: Vec<VoteEntry>,
+    summary: AutovoteSummary,
+}
+
+/// Numbers of (un)vote results of autovote, printed after them
+#[derive(Serialize, Default, PartialEq, Debug)]
+struct AutovoteSummary {
+    voted: usize,
+    unvoted: usize,
+    already_voted: usize,
+    already_unvoted: usize,
+    not_available: usize,
+    failed: usize,
+    throttled: usize,
+}
+
+impl AutovoteSummary {
+    fn from_results(results: &[(PkgName, VoteResult)]) -> AutovoteSummary {
+        let mut summary = AutovoteSummary::default();
+        for (_, result) in results {
+            let count = match result {
+                VoteResult::Voted => &mut summary.voted,
+                VoteResult::UnVoted => &mut summary.unvoted,
+                VoteResult::AlreadyVoted => &mut summary.already_voted,
+                VoteResult::AlreadyUnVoted => &mut summary.already_unvoted,
+                VoteResult::NotAvailable => &mut summary.not_available,
+                VoteResult::Failed => &mut summary.failed,
+                VoteResult::Throttled(_) => &mut summary.throttled,
+            };
+            *count += 1;
+        }
+        summary
+    }
+
+    /// e.g. `Voted: 5, Unvoted: 3, Already voted: 42, Failed: 1`, rare results only if any
+    fn fancy(&self) -> String {
+        let mut counts = vec![
+            format!("Voted: {}", self.voted),
+            format!("Unvoted: {}", self.unvoted),
+            format!("Already voted: {}", self.already_voted),
+        ];
+        for (label, count) in [
+            ("Already unvoted", self.already_unvoted),
+            ("Not available", self.not_available),
+            ("Throttled", self.throttled),
+        ] {
+            if count > 0 {
+                counts.push(format!("{}: {}", label, count));
             }
         }
+        counts.push(format!("Failed: {}", self.failed));
+        counts.join(", ")
     }
+}
+
+/// Options of `autovote` from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct AutovoteOptions {
+    /// Only vote for installed packages, never unvote
+    pub vote_only: bool,
+
+    /// Only unvote for uninstalled packages, never vote
+    pub unvote_only: bool,
+
+    /// Only add votes for installed packages, never unvote any package
+    pub additive: bool,
+
+    /// Print only number of packages to vote/unvote, without voting/unvoting
+    pub count_only: bool,
+
+    /// Unvote without confirmation
+    pub yes: bool,
+
+    /// Do not vote for packages installed in a newer version than AUR version, e.g. local builds
+    pub skip_newer: bool,
+
+    /// How to find installed AUR packages
+    pub scan_mode: ScanMode,
+
+    /// Only vote for explicitly installed packages, keep votes of packages installed as dependencies
+    pub explicit_only: bool,
+
+    /// Only print packages to vote/unvote, without voting/unvoting
+    pub dry_run: bool,
+}
+
+/// Default number of packages to vote at once
+const DEFAULT_AUTOVOTE_BATCH_SIZE: usize = 50;
+
+/// Default pause in seconds between batches of votes
+const DEFAULT_AUTOVOTE_BATCH_PAUSE: u64 = 10;
+
+/// Minimum pause before voting again packages throttled by AUR
+const THROTTLE_PAUSE: Duration = Duration::from_secs(60);
+
+pub fn autovote<P: AsRef<Path>>(
+    config_path: P,
+    exclusions: &HashSet<PkgName>,
+    autovote_options: &AutovoteOptions,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let AutovoteOptions {
+        count_only,
+        dry_run,
+        ..
+    } = *autovote_options;
 
-    // [3] Get voted packages
     let config = Configuration::load_and_verify_config(&config_path)?;
-    let mut auth = Authentication::new();
+    let mut auth = config.authentication(options)?;
     auth.login(&config.account)?;
-    let mut voted_pkgs = auth.list_voted_pkgs()?;
+    timing::phase("login");
+    let (to_vote, to_unvote) = plan(&config, &mut auth, exclusions, autovote_options)?;
+
+    if count_only {
+        let count = AutovoteCount {
+            to_vote: to_vote.len(),
+            to_unvote: to_unvote.len(),
+        };
+        return match format {
+            OutputFormat::Json => print_flush(serde_json::to_string(&count)?),
+            _ => print_flush(format!(
+                "Would vote for {}, would unvote {}",
+                count.to_vote, count.to_unvote
+            )),
+        };
+    }
+
+    if dry_run {
+        return print_plan(&to_vote, &to_unvote, format, style);
+    }
+
+    // Listing and querying packages may outlast the session
+    auth.ensure_logged_in(&config.account)?;
+
+    // Results so far are printed as JSON even if (un)vote stops early
+    let mut results = Vec::new();
+    let status = vote_and_unvote(
+        &mut auth,
+        &config,
+        &AutovotePlan {
+            to_vote: &to_vote,
+            to_unvote: &to_unvote,
+        },
+        autovote_options,
+        format,
+        &mut results,
+        style,
+    );
+    print_results(results, format, style)?;
+    status
+}
+
+/// Print summary after results, or a JSON object of both
+fn print_results(
+    results: Vec<(PkgName, VoteResult)>,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let summary = AutovoteSummary::from_results(&results);
+    match format {
+        OutputFormat::Json => print_flush(serde_json::to_string_pretty(&AutovoteOutput {
+            results: VoteEntry::from_results(results),
+            summary,
+        })?),
+        OutputFormat::Human => print_flush(style.label(&summary.fancy())),
+        _ => print_entries(&VoteEntry::from_results(results), format),
+    }
+}
+
+/// Packages autovote would vote for and unvote, without voting/unvoting. Used by `diff`.
+pub fn plan(
+    config: &Configuration,
+    auth: &mut Authentication,
+    exclusions: &HashSet<PkgName>,
+    autovote_options: &AutovoteOptions,
+) -> Result<(Vec<PkgName>, Vec<PkgName>)> {
+    let AutovoteOptions {
+        vote_only,
+        unvote_only,
+        additive,
+        scan_mode,
+        explicit_only,
+        skip_newer,
+        ..
+    } = *autovote_options;
+
+    // [1], [2] Get foreign packages, installed packages from all non-official repositories,
+    // or all explicitly installed packages to be verified by RPC.
+    // With RPC scan, packages installed as dependencies are kept voted but never voted.
+    // Same for `--explicit-only` with foreign packages or packages from non-official repositories.
+    let (mut installed_pkgs, dependencies) = match scan_mode {
+        ScanMode::Foreign if explicit_only => {
+            split_explicit(list_foreign_pkgs()?, &list_explicitly_installed_pkgs()?)
+        }
+        ScanMode::Foreign => (list_foreign_pkgs()?, HashMap::new()),
+        ScanMode::Repo if explicit_only => split_explicit(
+            list_installed_non_official_pkgs()?,
+            &list_explicitly_installed_pkgs()?,
+        ),
+        ScanMode::Repo => (list_installed_non_official_pkgs()?, HashMap::new()),
+        ScanMode::Rpc => split_explicit(list_installed_pkgs()?, &list_explicitly_installed_pkgs()?),
+    };
+    timing::phase("list installed packages");
+
+    // [3] Get voted packages
+    let mut voted_pkgs = auth.list_voted_pkgs_cached()?;
+    timing::phase("fetch voted list");
 
     // [4] Remove voted packages from installed_pkgs and also remove already voted packages from voted_pkgs
     voted_pkgs.retain(|pkg| {
@@ -36,6 +261,9 @@ pub fn autovote<P: AsRef<Path>>(config_path: P) -> Result<()> {
 
             // also remove from voted_pkgs
             false
+        } else if dependencies.contains_key(&pkg.name) {
+            // installed as dependency, keep vote
+            false
         } else {
             // keep it
             true
@@ -43,31 +271,630 @@ pub fn autovote<P: AsRef<Path>>(config_path: P) -> Result<()> {
     });
 
     // [5] Verify if installed packages are AUR package.
-    let pkgs: Vec<PkgName> = installed_pkgs.iter().map(|pkg| pkg.0.to_owned()).collect();
-    let verified_pkgs = AurPackageInfo::info_query(&pkgs)?;
+    // Keep only names of verified packages, chunk by chunk
+    let mut to_vote: Vec<PkgName> = Vec::new();
+    let mut newer: Vec<PkgName> = Vec::new();
+    if !unvote_only {
+        let installed: Vec<PkgName> = installed_pkgs.iter().map(|pkg| pkg.0.to_owned()).collect();
+        auth.info_query_streaming(&installed, |chunk| {
+            newer.extend(newer_than_aur(&chunk, &installed_pkgs, |local, aur| {
+                vercmp(local, aur)
+            })?);
+            to_vote.extend(chunk.into_iter().map(|pkg| pkg.name));
+            Ok(())
+        })?;
+        timing::phase("query package info");
+    }
+
+    // Installed version newer than AUR usually means a local build
+    let skip_newer = skip_newer || config.autovote_skip_newer == Some(true);
+    for pkg in &newer {
+        match skip_newer {
+            true => warn!(
+                "Skip vote for `{}`, installed version is newer than AUR version",
+                pkg
+            ),
+            false => warn!(
+                "Installed version of `{}` is newer than AUR version, use `--skip-newer` to not vote for it",
+                pkg
+            ),
+        }
+    }
+    if skip_newer {
+        to_vote.retain(|pkg| !newer.contains(pkg));
+    }
+
+    // The left packages in voted_pkgs, except excluded packages
+    let to_unvote: Vec<PkgName> = match vote_only || additive {
+        true => Vec::new(),
+        false => without_excluded(
+            voted_pkgs.iter().map(|pkg| pkg.name.to_owned()).collect(),
+            exclusions,
+        ),
+    };
+
+    let ignore = config.ignore.as_deref().unwrap_or_default();
+    Ok((
+        without_ignored(to_vote, ignore),
+        without_ignored(to_unvote, ignore),
+    ))
+}
+
+/// Print packages [6] would vote and [7] would unvote, without voting/unvoting
+fn print_plan(
+    to_vote: &[PkgName],
+    to_unvote: &[PkgName],
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    if format == OutputFormat::Json {
+        let plan = AutovotePlan { to_vote, to_unvote };
+        return print_flush(serde_json::to_string_pretty(&plan)?);
+    }
+    for pkg in to_vote {
+        print_flush(vote::fancy_dry_run(pkg, style))?;
+    }
+    for pkg in to_unvote {
+        print_flush(unvote::fancy_dry_run(pkg, style))?;
+    }
+    Ok(())
+}
+
+/// [6] Vote for `to_vote` of `plan` in batches, then [7] unvote `to_unvote`, collecting `results`.
+/// Only human output is printed as soon as each package is (un)voted.
+fn vote_and_unvote(
+    auth: &mut Authentication,
+    config: &Configuration,
+    plan: &AutovotePlan,
+    autovote_options: &AutovoteOptions,
+    format: OutputFormat,
+    results: &mut Vec<(PkgName, VoteResult)>,
+    style: &Style,
+) -> Result<()> {
+    let AutovoteOptions {
+        vote_only,
+        unvote_only,
+        additive,
+        yes,
+        ..
+    } = *autovote_options;
+    let AutovotePlan { to_vote, to_unvote } = *plan;
+    let human = format == OutputFormat::Human;
+    let on_vote = |result: &(String, VoteResult)| match human {
+        true => print_flush(vote::fancy(result, style)?),
+        false => Ok(()),
+    };
+    let on_unvote = |result: &(String, VoteResult)| match human {
+        true => print_flush(unvote::fancy(result, style)?),
+        false => Ok(()),
+    };
+
+    if unvote_only {
+        if human {
+            print_flush(skipped("vote", "--unvote-only", style))?;
+        }
+    } else {
+        // [6] Vote verified packages
+        // in batches, pause between batches to avoid rate limit
+        let batch_size = config
+            .autovote_batch_size
+            .unwrap_or(DEFAULT_AUTOVOTE_BATCH_SIZE);
+        let batch_pause = Duration::from_secs(
+            config
+                .autovote_batch_pause
+                .unwrap_or(DEFAULT_AUTOVOTE_BATCH_PAUSE),
+        );
+        for (i, batch) in to_vote.chunks(batch_size).enumerate() {
+            if i > 0 {
+                debug!("Pause {:?} before next batch of votes", batch_pause);
+                thread::sleep(batch_pause);
+            }
+            let mut batch_results = auth.vote(batch, on_vote)?;
+            retry_throttled(
+                auth,
+                &mut batch_results,
+                THROTTLE_PAUSE.max(batch_pause),
+                on_vote,
+            )?;
+            let stopped = batch_results.len() < batch.len();
+            results.extend(batch_results);
+            if stopped {
+                break;
+            }
+        }
+        timing::phase("vote");
+        check_stopped(auth.stopped(), to_vote, results.len())?;
+    }
+
+    if let Some(flag) = unvote_skipped_by(vote_only, additive) {
+        if human {
+            print_flush(skipped("unvote", flag, style))?;
+        }
+        return check_failures(results);
+    }
+
+    // [7] Unvote the left packages in voted_pkgs, except excluded packages,
+    // after confirmation since packages may be uninstalled only temporarily
+    if !to_unvote.is_empty() && !yes && !confirm_unvote(to_unvote, format, style)? {
+        return check_failures(results);
+    }
+    let unvote_results = auth.unvote(to_unvote, on_unvote)?;
+    timing::phase("unvote");
+
+    let unvoted = unvote_results.len();
+    results.extend(unvote_results);
+    check_stopped(auth.stopped(), to_unvote, unvoted)?;
+    check_failures(results)
+}
+
+/// Vote again, once, for packages throttled by AUR, after `pause`
+fn retry_throttled<F>(
+    auth: &mut Authentication,
+    results: &mut [(PkgName, VoteResult)],
+    pause: Duration,
+    on_result: F,
+) -> Result<()>
+where
+    F: FnMut(&(String, VoteResult)) -> Result<()>,
+{
+    let throttled = throttled_pkgs(results);
+    if throttled.is_empty() {
+        return Ok(());
+    }
+    warn!(
+        "{} packages throttled by AUR, vote again after {:?}",
+        throttled.len(),
+        pause
+    );
+    thread::sleep(pause);
+    let retried = auth.vote(&throttled, on_result)?;
+    for (pkg, result) in retried {
+        if let Some(status) = results.iter_mut().find(|(name, _)| *name == pkg) {
+            status.1 = result;
+        }
+    }
+    Ok(())
+}
 
-    // [6] Vote verified packages
-    let pkgs: Vec<PkgName> = verified_pkgs
+/// Packages that AUR refused to vote for, because of voting too fast
+fn throttled_pkgs(results: &[(PkgName, VoteResult)]) -> Vec<PkgName> {
+    results
         .iter()
-        .map(|pkg| pkg.name.to_owned())
+        .filter(|(_, result)| matches!(result, VoteResult::Throttled(_)))
+        .map(|(pkg, _)| pkg.to_owned())
+        .collect()
+}
+
+/// Packages not in `ignore` of configuration, which autovote never votes for nor unvotes
+pub fn without_ignored(pkgs: Vec<PkgName>, ignore: &[PkgName]) -> Vec<PkgName> {
+    pkgs.into_iter()
+        .filter(|pkg| {
+            let ignored = ignore.contains(pkg);
+            if ignored {
+                debug!("Ignore package `{}`", pkg);
+            }
+            !ignored
+        })
+        .collect()
+}
+
+/// Packages not in `exclusions` of command line, whose votes are kept
+pub fn without_excluded(pkgs: Vec<PkgName>, exclusions: &HashSet<PkgName>) -> Vec<PkgName> {
+    pkgs.into_iter()
+        .filter(|pkg| {
+            let excluded = exclusions.contains(pkg);
+            if excluded {
+                debug!("Keep vote for excluded package `{}`", pkg);
+            }
+            !excluded
+        })
+        .collect()
+}
+
+/// Split packages into explicitly installed ones and the ones installed as dependencies
+fn split_explicit(
+    pkgs: HashMap<PkgName, PkgVersion>,
+    explicit_pkgs: &HashMap<PkgName, PkgVersion>,
+) -> (HashMap<PkgName, PkgVersion>, HashMap<PkgName, PkgVersion>) {
+    pkgs.into_iter()
+        .partition(|(name, _)| explicit_pkgs.contains_key(name))
+}
+
+/// Vote/Unvote only packages affected by pacman transaction, e.g. from `NeedsTargets` of pacman hook
+pub fn autovote_hook<P: AsRef<Path>>(
+    config_path: P,
+    targets: &[PkgName],
+    exclusions: &HashSet<PkgName>,
+    autovote_options: &AutovoteOptions,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let AutovoteOptions {
+        vote_only,
+        unvote_only,
+        additive,
+        yes,
+        ..
+    } = *autovote_options;
+
+    // Targets still installed after transaction are installed or upgraded, the others are removed.
+    // Only foreign packages, i.e. not in any sync database, are voted, e.g. built from AUR.
+    let installed_pkgs = list_installed_pkgs()?;
+    let foreign_pkgs = list_foreign_pkgs()?;
+    let (to_vote, to_unvote) = split_hook_targets(targets, &installed_pkgs, &foreign_pkgs);
+    timing::phase("list installed packages");
+
+    let to_vote: Vec<PkgName> = if unvote_only {
+        print_flush(skipped("vote", "--unvote-only", style))?;
+        Vec::new()
+    } else {
+        to_vote
+    };
+    let to_unvote: Vec<PkgName> = if let Some(flag) = unvote_skipped_by(vote_only, additive) {
+        print_flush(skipped("unvote", flag, style))?;
+        Vec::new()
+    } else {
+        without_excluded(to_unvote, exclusions)
+    };
+
+    // Verify if targets are AUR package.
+    let pkgs: Vec<PkgName> = to_vote.iter().chain(to_unvote.iter()).cloned().collect();
+    if pkgs.is_empty() {
+        debug!("No AUR package to vote/unvote in pacman transaction");
+        return Ok(());
+    }
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    let aur_pkgs: HashSet<PkgName> = auth
+        .info_query(&pkgs)?
+        .into_iter()
+        .map(|pkg| pkg.name)
         .collect();
-    let results = auth.vote(&pkgs)?;
+    timing::phase("query package info");
+    let ignore = config.ignore.as_deref().unwrap_or_default();
+    let to_vote: Vec<PkgName> = to_vote
+        .into_iter()
+        .filter(|pkg| aur_pkgs.contains(pkg))
+        .collect();
+    let to_unvote: Vec<PkgName> = to_unvote
+        .into_iter()
+        .filter(|pkg| aur_pkgs.contains(pkg))
+        .collect();
+    let to_vote = without_ignored(to_vote, ignore);
+    let to_unvote = without_ignored(to_unvote, ignore);
+    let to_unvote = match hook_unvote_confirmed(yes, &to_unvote) {
+        true => to_unvote,
+        false => Vec::new(),
+    };
+    if to_vote.is_empty() && to_unvote.is_empty() {
+        debug!("No AUR package to vote/unvote in pacman transaction");
+        return Ok(());
+    }
+
+    auth.login(&config.account)?;
+    timing::phase("login");
 
-    let mut output = String::new();
-    for result in results.iter() {
-        writeln!(output, "{}", vote::fancy(result)?)?;
+    let mut results = auth.vote(&to_vote, |result| print_flush(vote::fancy(result, style)?))?;
+    timing::phase("vote");
+    check_stopped(auth.stopped(), &to_vote, results.len())?;
+    let unvote_results = auth.unvote(&to_unvote, |result| {
+        print_flush(unvote::fancy(result, style)?)
+    })?;
+    timing::phase("unvote");
+
+    check_stopped(auth.stopped(), &to_unvote, unvote_results.len())?;
+    results.extend(unvote_results);
+    check_failures(&results)
+}
+
+/// Split targets of pacman transaction into packages to vote and packages to unvote
+fn split_hook_targets(
+    targets: &[PkgName],
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    foreign_pkgs: &HashMap<PkgName, PkgVersion>,
+) -> (Vec<PkgName>, Vec<PkgName>) {
+    let mut to_vote: Vec<PkgName> = Vec::new();
+    let mut to_unvote: Vec<PkgName> = Vec::new();
+    for pkg in targets.iter() {
+        if foreign_pkgs.contains_key(pkg) {
+            to_vote.push(pkg.to_owned());
+        } else if !installed_pkgs.contains_key(pkg) {
+            to_unvote.push(pkg.to_owned());
+        }
     }
-    print!("{}", output);
+    (to_vote, to_unvote)
+}
 
-    // [7] Unvote the left packages in voted_pkgs
-    let pkgs: Vec<PkgName> = voted_pkgs.iter().map(|pkg| pkg.name.to_owned()).collect();
-    let results = auth.unvote(&pkgs)?;
+/// Installed foreign packages (`pacman -Qm`) which are AUR packages, sorted by name
+pub fn list_installed_aur_pkgs(auth: &Authentication) -> Result<Vec<PkgName>> {
+    let installed: Vec<PkgName> = list_foreign_pkgs()?.into_keys().collect();
+    let mut aur_pkgs: Vec<PkgName> = auth
+        .info_query(&installed)?
+        .into_iter()
+        .map(|pkg| pkg.name)
+        .collect();
+    aur_pkgs.sort();
+    Ok(aur_pkgs)
+}
+
+/// Get installed packages from all non-official repositories
+fn list_installed_non_official_pkgs() -> Result<HashMap<PkgName, PkgVersion>> {
+    let non_official = list_repos(SelectRepository::NonOfficial)?;
 
-    let mut output = String::new();
-    for result in results.iter() {
-        writeln!(output, "{}", unvote::fancy(result)?)?;
+    let mut installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+    for repo in non_official.iter() {
+        let pkgs_in_repo = list_installed_pkgs_repo(repo)?;
+        for pkg in pkgs_in_repo.iter() {
+            if !installed_pkgs.contains_key(pkg.0) {
+                installed_pkgs.insert(pkg.0.to_owned(), pkg.1.to_owned());
+            }
+        }
     }
-    print!("{}", output);
+    Ok(installed_pkgs)
+}
 
-    Ok(())
+/// Ask before unvoting packages. Unvote is skipped if it cannot be confirmed, i.e. not run
+/// from a terminal, or output is not human, e.g. JSON.
+fn confirm_unvote(to_unvote: &[PkgName], format: OutputFormat, style: &Style) -> Result<bool> {
+    if !is_interactive() || format != OutputFormat::Human {
+        warn!(
+            "Skip unvote of {} packages without confirmation, use `--yes` to unvote them: {}",
+            to_unvote.len(),
+            to_unvote.join(", ")
+        );
+        return Ok(false);
+    }
+
+    print_flush(format!(
+        "Packages to unvote ({}): {}",
+        to_unvote.len(),
+        to_unvote.join(", ")
+    ))?;
+    let confirmed = Confirm::new()
+        .with_prompt(format!("Unvote {} packages?", to_unvote.len()))
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        print_flush(skipped("unvote", "not confirmed", style))?;
+    }
+    Ok(confirmed)
+}
+
+/// Hook cannot ask from inside pacman, so removed packages are unvoted only with `--yes`,
+/// like `autovote` not run from a terminal
+fn hook_unvote_confirmed(yes: bool, to_unvote: &[PkgName]) -> bool {
+    if !yes && !to_unvote.is_empty() {
+        warn!(
+            "Skip unvote of {} packages without confirmation, use `--yes` to unvote them: {}",
+            to_unvote.len(),
+            to_unvote.join(", ")
+        );
+    }
+    yes
+}
+
+/// Flag which skips unvote phase, if any
+fn unvote_skipped_by(vote_only: bool, additive: bool) -> Option<&'static str> {
+    match (vote_only, additive) {
+        (_, true) => Some("--additive"),
+        (true, false) => Some("--vote-only"),
+        (false, false) => None,
+    }
+}
+
+fn skipped(phase: &str, flag: &str, style: &Style) -> String {
+    format!(
+        "{}",
+        style.warn(&format!("Skip {} phase ({})", phase, flag))
+    )
+}
+
+/// Packages of `aur_pkgs` installed in a newer version than AUR version, compared by `compare`,
+/// e.g. `vercmp`. Same versions are not compared.
+fn newer_than_aur<F>(
+    aur_pkgs: &[AurPackageInfoItem],
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    compare: F,
+) -> Result<Vec<PkgName>>
+where
+    F: Fn(&str, &str) -> Result<Versioning>,
+{
+    let mut newer = Vec::new();
+    for pkg in aur_pkgs {
+        let Some(local) = installed_pkgs.get(&pkg.name) else {
+            continue;
+        };
+        if *local != pkg.version && compare(local, &pkg.version)? == Versioning::Newer {
+            newer.push(pkg.name.to_owned());
+        }
+    }
+    Ok(newer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autovote_plan_json() {
+        let to_vote = vec!["yay".to_owned(), "paru".to_owned()];
+        let plan = AutovotePlan {
+            to_vote: &to_vote,
+            to_unvote: &[],
+        };
+        assert_eq!(
+            serde_json::to_string(&plan).unwrap(),
+            r#"{"to_vote":["yay","paru"],"to_unvote":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_autovote_summary() {
+        let results = vec![
+            ("yay".to_owned(), VoteResult::Voted),
+            ("paru".to_owned(), VoteResult::AlreadyVoted),
+            ("foo".to_owned(), VoteResult::Failed),
+            ("bar".to_owned(), VoteResult::UnVoted),
+            ("baz".to_owned(), VoteResult::UnVoted),
+        ];
+        let summary = AutovoteSummary::from_results(&results);
+        assert_eq!(
+            summary,
+            AutovoteSummary {
+                voted: 1,
+                unvoted: 2,
+                already_voted: 1,
+                failed: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            summary.fancy(),
+            "Voted: 1, Unvoted: 2, Already voted: 1, Failed: 1"
+        );
+        assert_eq!(
+            serde_json::to_string(&summary).unwrap(),
+            r#"{"voted":1,"unvoted":2,"already_voted":1,"already_unvoted":0,"not_available":0,"failed":1,"throttled":0}"#
+        );
+
+        let results = vec![("foo".to_owned(), VoteResult::NotAvailable)];
+        assert_eq!(
+            AutovoteSummary::from_results(&results).fancy(),
+            "Voted: 0, Unvoted: 0, Already voted: 0, Not available: 1, Failed: 0"
+        );
+
+        // JSON output is one object of results and summary
+        let output = AutovoteOutput {
+            summary: AutovoteSummary::from_results(&results),
+            results: VoteEntry::from_results(results),
+        };
+        let json: serde_json::Value = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["results"][0]["package"], "foo");
+        assert_eq!(json["results"][0]["result"], "not-available");
+        assert_eq!(json["summary"]["not_available"], 1);
+    }
+
+    #[test]
+    fn test_without_ignored() {
+        let ignore = vec!["foo-git".to_owned()];
+
+        // Installed AUR package on ignore list is never voted
+        let to_vote = vec!["yay".to_owned(), "foo-git".to_owned()];
+        assert_eq!(without_ignored(to_vote, &ignore), vec!["yay".to_owned()]);
+
+        // nor unvoted
+        let to_unvote = vec!["foo-git".to_owned()];
+        assert!(without_ignored(to_unvote, &ignore).is_empty());
+
+        let to_vote = vec!["yay".to_owned()];
+        assert_eq!(without_ignored(to_vote, &[]), vec!["yay".to_owned()]);
+    }
+
+    #[test]
+    fn test_without_excluded() {
+        let exclusions = HashSet::from(["foo-git".to_owned()]);
+        let voted = vec!["yay".to_owned(), "foo-git".to_owned()];
+        assert_eq!(
+            without_excluded(voted.clone(), &exclusions),
+            vec!["yay".to_owned()]
+        );
+        assert_eq!(without_excluded(voted.clone(), &HashSet::new()), voted);
+    }
+
+    #[test]
+    fn test_throttled_pkgs() {
+        let results = vec![
+            ("pkg1".to_owned(), VoteResult::Voted),
+            ("pkg2".to_owned(), VoteResult::Throttled("Wait".to_owned())),
+            ("pkg3".to_owned(), VoteResult::Failed),
+            ("pkg4".to_owned(), VoteResult::Throttled("Wait".to_owned())),
+        ];
+        assert_eq!(throttled_pkgs(&results), vec!["pkg2", "pkg4"]);
+        assert!(throttled_pkgs(&results[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_split_hook_targets() {
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::from([
+            ("linux".to_owned(), "5.16.8.arch1-1".to_owned()),
+            ("paru-bin".to_owned(), "1.9.3-1".to_owned()),
+        ]);
+        let foreign_pkgs: HashMap<PkgName, PkgVersion> =
+            HashMap::from([("paru-bin".to_owned(), "1.9.3-1".to_owned())]);
+        let targets: Vec<PkgName> = vec![
+            "linux".to_owned(),
+            "paru-bin".to_owned(),
+            "yay-bin".to_owned(),
+        ];
+        let (to_vote, to_unvote) = split_hook_targets(&targets, &installed_pkgs, &foreign_pkgs);
+        assert_eq!(to_vote, vec!["paru-bin"]);
+        assert_eq!(to_unvote, vec!["yay-bin"]);
+    }
+
+    #[test]
+    fn test_hook_unvote_confirmed() {
+        let to_unvote = vec!["yay-bin".to_owned()];
+        assert!(hook_unvote_confirmed(true, &to_unvote));
+        assert!(!hook_unvote_confirmed(false, &to_unvote));
+        assert!(!hook_unvote_confirmed(false, &[]));
+    }
+
+    #[test]
+    fn test_newer_than_aur() {
+        let aur_pkg = |name: &str, version: &str| AurPackageInfoItem {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            ..Default::default()
+        };
+        let aur_pkgs = vec![
+            aur_pkg("yay", "12.3.5-1"),
+            aur_pkg("paru", "2.0.3-1"),
+            aur_pkg("foo-git", "1.0-1"),
+        ];
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::from([
+            ("yay".to_owned(), "12.4.0-1".to_owned()),
+            ("paru".to_owned(), "2.0.3-1".to_owned()),
+            ("foo-git".to_owned(), "0.9-1".to_owned()),
+        ]);
+        // Only `yay` is newer, same versions are not compared
+        let compare = |local: &str, aur: &str| {
+            assert_ne!(local, aur);
+            Ok(match local > aur {
+                true => Versioning::Newer,
+                false => Versioning::Older,
+            })
+        };
+        assert_eq!(
+            newer_than_aur(&aur_pkgs, &installed_pkgs, compare).unwrap(),
+            vec!["yay"]
+        );
+    }
+
+    #[test]
+    fn test_unvote_skipped_by() {
+        assert_eq!(unvote_skipped_by(false, false), None);
+        assert_eq!(unvote_skipped_by(true, false), Some("--vote-only"));
+        assert_eq!(unvote_skipped_by(false, true), Some("--additive"));
+    }
+
+    #[test]
+    fn test_split_explicit() {
+        let pkgs: HashMap<PkgName, PkgVersion> = HashMap::from([
+            ("paru-bin".to_owned(), "1.9.3-1".to_owned()),
+            ("libfoo".to_owned(), "1.0-1".to_owned()),
+        ]);
+        let explicit_pkgs: HashMap<PkgName, PkgVersion> = HashMap::from([
+            ("linux".to_owned(), "5.16.8.arch1-1".to_owned()),
+            ("paru-bin".to_owned(), "1.9.3-1".to_owned()),
+        ]);
+        let (explicit, dependencies) = split_explicit(pkgs, &explicit_pkgs);
+        assert_eq!(
+            explicit,
+            HashMap::from([("paru-bin".to_owned(), "1.9.3-1".to_owned())])
+        );
+        assert_eq!(
+            dependencies,
+            HashMap::from([("libfoo".to_owned(), "1.0-1".to_owned())])
+        );
+    }
 }
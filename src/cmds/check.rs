@@ -1,34 +1,233 @@
 use anyhow::Result;
-use colored::Colorize;
-use std::{fmt::Write, path::Path};
+use comfy_table::{Cell, Table};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
 
-use crate::{aur::Authentication, config::Configuration};
+use crate::{
+    args::{Expectation, OutputFormat},
+    aur::SessionOptions,
+    config::Configuration,
+    error::{check_stopped, ExitStatus},
+    helper::{
+        list_installed_pkgs, print_csv, print_flush, vercmp, PkgName, PkgVersion, Versioning,
+    },
+    theme::{Mark, Style},
+    timing,
+};
+
+/// Number of packages above which voted list is fetched once, instead of each package page
+const VIA_VOTED_LIST_THRESHOLD: usize = 20;
+
+/// Vote status of package with installed and AUR versions, for JSON and CSV output
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+struct CheckResult {
+    package: PkgName,
+    voted: Option<bool>,
+    installed_version: Option<PkgVersion>,
+    aur_version: Option<PkgVersion>,
+
+    /// Installed version compared to AUR version
+    relation: Option<Versioning>,
+}
+
+/// Options of `check` from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct CheckOptions {
+    /// Exit with 2 if any package is not voted, 3 if any package is not available
+    pub exit_code: bool,
+
+    /// Exit with 0 only if all packages are in expected state, otherwise 2
+    pub expect: Option<Expectation>,
+
+    /// Fetch voted list once instead of each package page
+    pub via_voted_list: bool,
+
+    /// Print numbers of voted, not voted, and not available packages
+    pub summary: bool,
+}
+
+pub fn check<P: AsRef<Path>>(
+    config_path: P,
+    packages: Vec<String>,
+    check_options: &CheckOptions,
+    format: OutputFormat,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let CheckOptions {
+        exit_code,
+        expect,
+        via_voted_list,
+        summary,
+    } = *check_options;
 
-pub fn check<P: AsRef<Path>>(config_path: P, packages: Vec<String>) -> Result<()> {
     let config = Configuration::load_and_verify_config(&config_path)?;
-    let mut auth = Authentication::new();
+    let mut auth = config.authentication(options)?;
     auth.login(&config.account)?;
-    let voted = auth.check_vote(&packages)?;
+    timing::phase("login");
+    let on_result = |voted: &(String, Option<bool>)| match format {
+        OutputFormat::Human => print_flush(fancy(voted, style)?),
+        _ => Ok(()),
+    };
+    let voted = if use_voted_list(packages.len(), via_voted_list) {
+        auth.check_vote_via_voted_list(&packages, on_result)?
+    } else {
+        auth.check_vote(&packages, on_result)?
+    };
+    timing::phase("check");
 
-    let mut output = String::new();
-    for v in voted.iter() {
-        writeln!(output, "{}", fancy(v)?)?;
+    if matches!(format, OutputFormat::Json | OutputFormat::Csv) {
+        let installed_pkgs = list_installed_pkgs()?;
+        timing::phase("list installed packages");
+        let aur_versions: HashMap<PkgName, PkgVersion> = auth
+            .info_query(&packages)?
+            .into_iter()
+            .map(|info| (info.name, info.version))
+            .collect();
+        timing::phase("query package info");
+
+        let results = check_results(&voted, &installed_pkgs, &aur_versions)?;
+        match format {
+            OutputFormat::Json => print_flush(serde_json::to_string_pretty(&results)?)?,
+            _ => print_csv(&results)?,
+        }
+    }
+
+    if format == OutputFormat::Count {
+        print_flush(count_voted(&voted))?;
+    }
+
+    if format == OutputFormat::Table {
+        print_flush(table(&voted, style))?;
+    }
+
+    // Summary of checked packages, even if stopped before all packages
+    if summary && matches!(format, OutputFormat::Human | OutputFormat::Table) {
+        print_flush(fancy_summary(&voted, style))?;
+    }
+
+    check_stopped(auth.stopped(), &packages, voted.len())?;
+
+    if exit_code {
+        match voted_exit_code(&voted) {
+            0 => (),
+            code => return Err(ExitStatus(code).into()),
+        }
+    }
+
+    if let Some(expect) = expect {
+        if !is_expected(&voted, expect) {
+            return Err(ExitStatus(2).into());
+        }
     }
-    print!("{}", output);
 
     Ok(())
 }
 
-fn fancy(voted: &(String, Option<bool>)) -> Result<String> {
+/// Check via voted list if forced, or if there are too many packages to fetch one by one
+fn use_voted_list(count: usize, via_voted_list: bool) -> bool {
+    via_voted_list || count > VIA_VOTED_LIST_THRESHOLD
+}
+
+/// Number of voted packages
+fn count_voted(voted: &[(String, Option<bool>)]) -> usize {
+    voted
+        .iter()
+        .filter(|(_, status)| *status == Some(true))
+        .count()
+}
+
+/// Numbers of voted, not voted, and not available packages
+fn fancy_summary(voted: &[(String, Option<bool>)], style: &Style) -> String {
+    let count = |expected: Option<bool>| {
+        voted
+            .iter()
+            .filter(|(_, status)| *status == expected)
+            .count()
+    };
+    format!(
+        "{} {}, {} {}, {} {}",
+        style.label("Voted"),
+        count(Some(true)),
+        style.label("Not voted"),
+        count(Some(false)),
+        style.label("N/A"),
+        count(None)
+    )
+}
+
+/// Table of checked packages for `--format table`
+fn table(voted: &[(String, Option<bool>)], style: &Style) -> Table {
+    let mut table = style.table();
+    table.set_header(vec!["Name", "Voted"]);
+    for (pkg, status) in voted {
+        let status = match status {
+            Some(true) => Cell::new(style.mark("Yes", Mark::Good)).fg(style.good_cell()),
+            Some(false) => Cell::new(style.mark("No", Mark::Bad)).fg(style.bad_cell()),
+            None => Cell::new(style.mark("N/A", Mark::Unknown)).fg(style.warn_cell()),
+        };
+        table.add_row(vec![Cell::new(pkg), status]);
+    }
+    table
+}
+
+/// Check if all packages are in expected state
+fn is_expected(voted: &[(String, Option<bool>)], expect: Expectation) -> bool {
+    let expected_status = match expect {
+        Expectation::Voted => Some(true),
+        Expectation::Unvoted => Some(false),
+        Expectation::Absent => None,
+    };
+    voted.iter().all(|(_, status)| *status == expected_status)
+}
+
+/// Exit code for monitoring: 0 = all voted, 2 = some not voted, 3 = some not available
+fn voted_exit_code(voted: &[(String, Option<bool>)]) -> i32 {
+    if voted.iter().any(|(_, status)| status.is_none()) {
+        return 3;
+    }
+
+    if voted.iter().any(|(_, status)| *status == Some(false)) {
+        return 2;
+    }
+
+    0
+}
+
+fn check_results(
+    voted: &[(String, Option<bool>)],
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    aur_versions: &HashMap<PkgName, PkgVersion>,
+) -> Result<Vec<CheckResult>> {
+    let mut results: Vec<CheckResult> = Vec::new();
+    for (pkg, status) in voted {
+        let installed_version = installed_pkgs.get(pkg).cloned();
+        let aur_version = aur_versions.get(pkg).cloned();
+        let relation = match (&installed_version, &aur_version) {
+            (Some(local_ver), Some(aur_ver)) => Some(vercmp(local_ver, aur_ver)?),
+            _ => None,
+        };
+        results.push(CheckResult {
+            package: pkg.to_owned(),
+            voted: *status,
+            installed_version,
+            aur_version,
+            relation,
+        });
+    }
+    Ok(results)
+}
+
+fn fancy(voted: &(String, Option<bool>), style: &Style) -> Result<String> {
     Ok(format!(
         "{} {}",
-        voted.0.bold().white(),
+        style.name(&voted.0),
         match voted.1 {
             Some(status) => match status {
-                true => "Yes".bright_green(),
-                false => "No".bright_red(),
+                true => style.good(style.mark("Yes", Mark::Good)),
+                false => style.bad(style.mark("No", Mark::Bad)),
             },
-            None => "N/A".bright_yellow(),
+            None => style.warn(style.mark("N/A", Mark::Unknown)),
         }
     ))
 }
@@ -36,24 +235,161 @@ fn fancy(voted: &(String, Option<bool>)) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use colored::Colorize;
+
+    #[test]
+    fn test_fancy_summary() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("networkd-broker".to_owned(), Some(true)),
+            ("aur-thumbsup".to_owned(), Some(false)),
+            ("not-exist".to_owned(), None),
+        ];
+        let expect = format!(
+            "{} 2, {} 1, {} 1",
+            "Voted".cyan(),
+            "Not voted".cyan(),
+            "N/A".cyan()
+        );
+        assert_eq!(fancy_summary(&voted, &Style::default()), expect);
+    }
+
+    #[test]
+    fn test_table() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("not-exist".to_owned(), None),
+        ];
+        let output = table(&voted, &Style::default()).to_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].contains("Name") && lines[1].contains("Voted"));
+        assert!(lines[3].contains("pacman-mirrorup") && lines[3].contains("Yes"));
+        assert!(lines[5].contains("not-exist") && lines[5].contains("N/A"));
+    }
+
+    #[test]
+    fn test_check_results_json() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("not-exist".to_owned(), None),
+        ];
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+        let mut aur_versions: HashMap<PkgName, PkgVersion> = HashMap::new();
+        aur_versions.insert("pacman-mirrorup".to_owned(), "0.3.0-1".to_owned());
+
+        let results = check_results(&voted, &installed_pkgs, &aur_versions).unwrap();
+        let json: serde_json::Value = serde_json::to_value(&results).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "package": "pacman-mirrorup",
+                    "voted": true,
+                    "installed_version": null,
+                    "aur_version": "0.3.0-1",
+                    "relation": null
+                },
+                {
+                    "package": "not-exist",
+                    "voted": null,
+                    "installed_version": null,
+                    "aur_version": null,
+                    "relation": null
+                }
+            ])
+        );
+
+        // Deserialized back to the same results
+        let parsed: Vec<CheckResult> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, results);
+
+        // relation is serialized in lowercase
+        assert_eq!(
+            serde_json::to_value(Versioning::Older).unwrap(),
+            serde_json::json!("older")
+        );
+    }
+
+    #[test]
+    fn test_voted_exit_code() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("networkd-broker".to_owned(), Some(true)),
+        ];
+        assert_eq!(voted_exit_code(&voted), 0);
+
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("networkd-broker".to_owned(), Some(false)),
+        ];
+        assert_eq!(voted_exit_code(&voted), 2);
+
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), None),
+            ("networkd-broker".to_owned(), Some(false)),
+        ];
+        assert_eq!(voted_exit_code(&voted), 3);
+    }
+
+    #[test]
+    fn test_is_expected() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("networkd-broker".to_owned(), Some(true)),
+        ];
+        assert!(is_expected(&voted, Expectation::Voted));
+        assert!(!is_expected(&voted, Expectation::Unvoted));
+
+        let voted = vec![("pacman-mirrorup".to_owned(), Some(false))];
+        assert!(is_expected(&voted, Expectation::Unvoted));
+        assert!(!is_expected(&voted, Expectation::Voted));
+        assert!(!is_expected(&voted, Expectation::Absent));
+
+        let voted = vec![
+            ("not-exist".to_owned(), None),
+            ("pacman-mirrorup".to_owned(), Some(false)),
+        ];
+        assert!(!is_expected(&voted, Expectation::Absent));
+        assert!(is_expected(&voted[..1], Expectation::Absent));
+    }
+
+    #[test]
+    fn test_use_voted_list() {
+        assert!(!use_voted_list(1, false));
+        assert!(!use_voted_list(VIA_VOTED_LIST_THRESHOLD, false));
+        assert!(use_voted_list(VIA_VOTED_LIST_THRESHOLD + 1, false));
+        assert!(use_voted_list(1, true));
+    }
+
+    #[test]
+    fn test_count_voted() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("networkd-broker".to_owned(), Some(false)),
+            ("aur-thumbsup".to_owned(), Some(true)),
+            ("not-exist".to_owned(), None),
+        ];
+        assert_eq!(count_voted(&voted), 2);
+        assert_eq!(count_voted(&[]), 0);
+    }
 
     #[test]
     fn test_fancy() {
         // Voted
         let voted = ("pacman-mirrorup".to_owned(), Some(true));
-        let result = fancy(&voted).unwrap();
+        let result = fancy(&voted, &Style::default()).unwrap();
         let expect = format!("{} {}", voted.0.bold().white(), "Yes".bright_green());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
 
         // Unvoted
         let voted = ("pacman-mirrorup".to_owned(), Some(false));
-        let result = fancy(&voted).unwrap();
+        let result = fancy(&voted, &Style::default()).unwrap();
         let expect = format!("{} {}", voted.0.bold().white(), "No".bright_red());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
 
         // N/A
         let voted = ("pacman-mirrorup".to_owned(), None);
-        let result = fancy(&voted).unwrap();
+        let result = fancy(&voted, &Style::default()).unwrap();
         let expect = format!("{} {}", voted.0.bold().white(), "N/A".bright_yellow());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
     }
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{
+    aur::SessionOptions, config::Configuration, helper::print_flush, theme::Style, timing,
+};
+
+/// Post comment on package page
+pub fn comment<P: AsRef<Path>>(
+    config_path: P,
+    package: &str,
+    text: &str,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    auth.comment(package, text)?;
+    timing::phase("comment");
+    print_flush(fancy(package, style))
+}
+
+fn fancy(pkg: &str, style: &Style) -> String {
+    format!("{}    {}", style.name(pkg), style.good("Commented"))
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{aur::SessionOptions, config::Configuration};
+
+/// Print package names starting with `prefix` for shell completion
+pub fn complete_packages<P: AsRef<Path>>(
+    config_path: P,
+    prefix: &str,
+    options: &SessionOptions,
+) -> Result<()> {
+    let auth = Configuration::rpc_authentication(config_path, options)?;
+    for pkg in auth.suggest_pkgs(prefix)? {
+        println!("{}", pkg);
+    }
+
+    Ok(())
+}
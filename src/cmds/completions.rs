@@ -0,0 +1,38 @@
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::io::{self, Write};
+
+use crate::args::Arguments;
+
+/// Print completion script of `shell` for subcommands and options
+pub fn completions(shell: Shell) -> Result<()> {
+    write_completions(shell, &mut io::stdout().lock())
+}
+
+fn write_completions<W: Write>(shell: Shell, writer: &mut W) -> Result<()> {
+    // Bash completion takes `__` as separator of subcommand path
+    let mut cmd = Arguments::command().mut_subcommand("__complete-packages", |subcmd| {
+        subcmd.name("complete-packages")
+    });
+    let bin_name = cmd.get_name().to_owned();
+    generate(shell, &mut cmd, bin_name, writer);
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_completions() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let mut script: Vec<u8> = Vec::new();
+            write_completions(shell, &mut script).unwrap();
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("aur-thumbsup"), "{:?}", shell);
+            assert!(script.contains("unvote-all"), "{:?}", shell);
+        }
+    }
+}
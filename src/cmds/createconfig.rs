@@ -2,9 +2,11 @@ use anyhow::{anyhow, Result};
 use dialoguer::{Input, Password};
 use std::path::{Path, PathBuf};
 
-use crate::config::Configuration;
+use crate::{config::Configuration, helper};
 
-pub fn create_config<P: AsRef<Path>>(path: P) -> Result<()> {
+/// Create configuration file from prompted user, pass.
+/// With `dry_run`, print it instead, with pass redacted unless `show_secrets`.
+pub fn create_config<P: AsRef<Path>>(path: P, dry_run: bool, show_secrets: bool) -> Result<()> {
     if path.as_ref().exists() {
         return Err(anyhow!("`{}` is exist.", path.as_ref().to_str().unwrap()));
     }
@@ -13,16 +15,70 @@ pub fn create_config<P: AsRef<Path>>(path: P) -> Result<()> {
         .with_prompt("AUR user name")
         .interact()?;
     let password = Password::new().with_prompt("Password").interact()?;
-    let sys_username = std::env::var("USER")?;
+
+    // Cookie file is kept in cache directory, private to its owner
+    let cache_dir = match dry_run {
+        true => helper::cache_dir_path(None)?,
+        false => helper::cache_dir(None)?,
+    };
 
     let mut config = Configuration::default();
+    config.account.cookie_file = cookie_file(&cache_dir, &aur_user);
     config.account.user = aur_user;
     config.account.pass = password;
-    config.account.cookie_file =
-        PathBuf::from(format!("/var/tmp/aur-thumbsup-{}.cookie", sys_username));
+
+    if dry_run {
+        print!("{}", preview(config, show_secrets)?);
+        return Ok(());
+    }
+
     config.to_file(&path)?;
 
     println!("Created `{}`", &path.as_ref().to_str().unwrap());
 
     Ok(())
 }
+
+/// Default cookie file of AUR `user`, e.g. `~/.cache/aur-thumbsup/foo.cookie`
+fn cookie_file(cache_dir: &Path, user: &str) -> PathBuf {
+    cache_dir.join(format!("{}.cookie", user))
+}
+
+/// TOML that would be written to configuration file
+fn preview(mut config: Configuration, show_secrets: bool) -> Result<String> {
+    if !show_secrets {
+        config.account.pass = "***".to_owned();
+    }
+    Ok(toml::to_string(&config)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        let mut config = Configuration::default();
+        config.account.user = "foo".to_owned();
+        config.account.pass = "bar".to_owned();
+        config.account.cookie_file = PathBuf::from("/var/tmp/aur-thumbsup-foo.cookie");
+
+        let toml = preview(config, false).unwrap();
+        assert!(toml.contains("[account]"));
+        assert!(toml.contains(r#"user = "foo""#));
+        assert!(toml.contains(r#"pass = "***""#));
+        assert!(toml.contains(r#"cookie_file = "/var/tmp/aur-thumbsup-foo.cookie""#));
+
+        let mut config = Configuration::default();
+        config.account.pass = "bar".to_owned();
+        assert!(preview(config, true).unwrap().contains(r#"pass = "bar""#));
+    }
+
+    #[test]
+    fn test_cookie_file() {
+        assert_eq!(
+            cookie_file(Path::new("/home/foo/.cache/aur-thumbsup"), "foo"),
+            PathBuf::from("/home/foo/.cache/aur-thumbsup/foo.cookie")
+        );
+    }
+}
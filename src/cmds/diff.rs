@@ -0,0 +1,77 @@
+use anyhow::Result;
+use std::{collections::HashSet, path::Path};
+
+use crate::{
+    args::OutputFormat,
+    aur::SessionOptions,
+    cmds::autovote::{self, AutovoteOptions, AutovotePlan},
+    config::Configuration,
+    helper::{print_flush, PkgName},
+    theme::Style,
+    timing,
+};
+
+/// Print installed AUR packages not voted yet and voted packages no longer installed,
+/// i.e. what autovote would vote for and unvote, without voting/unvoting
+pub fn diff<P: AsRef<Path>>(
+    config_path: P,
+    exclusions: &HashSet<PkgName>,
+    autovote_options: &AutovoteOptions,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let (to_vote, to_unvote) = autovote::plan(&config, &mut auth, exclusions, autovote_options)?;
+    match format {
+        OutputFormat::Json => {
+            let plan = AutovotePlan {
+                to_vote: &to_vote,
+                to_unvote: &to_unvote,
+            };
+            print_flush(serde_json::to_string_pretty(&plan)?)
+        }
+        _ => print_flush(fancy(&to_vote, &to_unvote, style)),
+    }
+}
+
+fn fancy(to_vote: &[PkgName], to_unvote: &[PkgName], style: &Style) -> String {
+    format!(
+        "{}\n\n{}",
+        section("Installed, not voted (would vote)", to_vote, style),
+        section("Voted, not installed (would unvote)", to_unvote, style)
+    )
+}
+
+/// Title with number of packages, followed by indented packages
+fn section(title: &str, pkgs: &[PkgName], style: &Style) -> String {
+    let mut lines = vec![style
+        .label(&format!("{} ({}):", title, pkgs.len()))
+        .to_string()];
+    match pkgs.is_empty() {
+        true => lines.push("  none".to_owned()),
+        false => lines.extend(pkgs.iter().map(|pkg| format!("  {}", pkg))),
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fancy() {
+        let style = Style::default();
+        let to_vote = vec!["paru-bin".to_owned(), "yay".to_owned()];
+        let expect = format!(
+            "{}\n  paru-bin\n  yay\n\n{}\n  none",
+            style.label("Installed, not voted (would vote) (2):"),
+            style.label("Voted, not installed (would unvote) (0):")
+        );
+        assert_eq!(fancy(&to_vote, &[], &style), expect);
+    }
+}
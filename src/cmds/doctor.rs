@@ -0,0 +1,427 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::Path;
+use time::OffsetDateTime;
+
+use crate::{
+    args::OutputFormat,
+    aur::{is_cookie_expired, load_cookie_file, SessionOptions},
+    config::Configuration,
+    error::ExitStatus,
+    helper::{is_file_secure, missing_programs, REQUIRED_PROGRAMS},
+    theme::Style,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Status {
+    Pass,
+
+    /// Not a problem by itself, e.g. stale cookie is renewed on next login
+    Warn,
+
+    Fail,
+
+    /// Not checked because an earlier check failed
+    Skip,
+}
+
+/// What is checked
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Check {
+    /// Stable name for monitoring, e.g. `config`
+    name: &'static str,
+
+    /// Human readable title
+    title: &'static str,
+
+    /// Whether its failure makes aur-thumbsup unhealthy
+    critical: bool,
+}
+
+const PROGRAMS: Check = Check {
+    name: "pacman",
+    title: "pacman is available",
+    critical: true,
+};
+const NETWORK: Check = Check {
+    name: "network",
+    title: "AUR is reachable",
+    critical: true,
+};
+const CONFIG_FILE: Check = Check {
+    name: "config_file",
+    title: "Configuration file exists",
+    critical: true,
+};
+const CONFIG_PERMISSION: Check = Check {
+    name: "config_permission",
+    title: "Configuration file is secure",
+    critical: true,
+};
+const CONFIG: Check = Check {
+    name: "config",
+    title: "Configuration is valid",
+    critical: true,
+};
+/// Stale cookie is renewed on next login, so it only warns
+const COOKIE: Check = Check {
+    name: "cookie",
+    title: "Cookie is valid",
+    critical: false,
+};
+const LOGIN: Check = Check {
+    name: "login",
+    title: "Login works",
+    critical: true,
+};
+
+/// Result of a check, with a hint how to fix it
+#[derive(PartialEq, Debug)]
+struct Diagnosis {
+    check: Check,
+    status: Status,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Diagnosis {
+    fn pass<D: ToString>(check: Check, detail: D) -> Diagnosis {
+        Diagnosis {
+            check,
+            status: Status::Pass,
+            detail: detail.to_string(),
+            hint: None,
+        }
+    }
+
+    fn problem<D: ToString, H: ToString>(
+        check: Check,
+        status: Status,
+        detail: D,
+        hint: H,
+    ) -> Diagnosis {
+        Diagnosis {
+            check,
+            status,
+            detail: detail.to_string(),
+            hint: Some(hint.to_string()),
+        }
+    }
+
+    fn skip(check: Check) -> Diagnosis {
+        Diagnosis {
+            check,
+            status: Status::Skip,
+            detail: "an earlier check failed".to_owned(),
+            hint: None,
+        }
+    }
+
+    /// Warning is not a problem by itself, skipped check is not known to work
+    fn is_ok(&self) -> bool {
+        matches!(self.status, Status::Pass | Status::Warn)
+    }
+}
+
+/// Health of aur-thumbsup for monitoring, printed by `doctor --format json`.
+/// Its fields are a stable contract, do not rename them.
+#[derive(Serialize, PartialEq, Debug)]
+struct HealthReport {
+    checks: Vec<HealthCheck>,
+
+    /// All critical checks are ok
+    ok: bool,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+struct HealthCheck {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+impl HealthReport {
+    fn new(diagnoses: &[Diagnosis]) -> HealthReport {
+        HealthReport {
+            checks: diagnoses
+                .iter()
+                .map(|diagnosis| HealthCheck {
+                    name: diagnosis.check.name,
+                    ok: diagnosis.is_ok(),
+                    detail: diagnosis.detail.to_owned(),
+                })
+                .collect(),
+            ok: diagnoses
+                .iter()
+                .filter(|diagnosis| diagnosis.check.critical)
+                .all(Diagnosis::is_ok),
+        }
+    }
+}
+
+/// Check configuration, pacman, network, cookie, and login, then print a checklist or `HealthReport`.
+/// Exit with status 1 if any critical check fails.
+pub fn doctor<P: AsRef<Path>>(
+    config_path: P,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let path = config_path.as_ref();
+    let path_str = path.display();
+    let mut diagnoses: Vec<Diagnosis> = Vec::new();
+
+    diagnoses.push(check_programs());
+
+    let aur_reachable = match Configuration::rpc_authentication(path, options)
+        .and_then(|auth| auth.suggest_pkgs("aur-thumbsup"))
+    {
+        Ok(_) => {
+            diagnoses.push(Diagnosis::pass(NETWORK, "RPC responds"));
+            true
+        }
+        Err(err) => {
+            diagnoses.push(Diagnosis::problem(
+                NETWORK,
+                Status::Fail,
+                err.root_cause(),
+                "Check network connection, proxy, and `min_tls_version`",
+            ));
+            false
+        }
+    };
+
+    let config = if !path.exists() {
+        diagnoses.push(Diagnosis::problem(
+            CONFIG_FILE,
+            Status::Fail,
+            format!("`{}` does not exist", path_str),
+            format!("Create it using `aur-thumbsup create-config {}`", path_str),
+        ));
+        None
+    } else {
+        diagnoses.push(Diagnosis::pass(CONFIG_FILE, format!("`{}`", path_str)));
+        diagnoses.push(match is_file_secure(path) {
+            Ok(true) => Diagnosis::pass(CONFIG_PERMISSION, "permission is 0600"),
+            Ok(false) => Diagnosis::problem(
+                CONFIG_PERMISSION,
+                // Accepted with a warning by `allow_insecure_config`
+                match Configuration::from_file(path) {
+                    Ok(config) if config.allow_insecure_config == Some(true) => Status::Warn,
+                    _ => Status::Fail,
+                },
+                "readable by other users",
+                format!("Run `chmod 600 {}`", path_str),
+            ),
+            Err(err) => Diagnosis::problem(
+                CONFIG_PERMISSION,
+                Status::Fail,
+                err,
+                "Check that the file is readable",
+            ),
+        });
+        match Configuration::load_and_verify_config(path) {
+            Ok(config) => {
+                diagnoses.push(Diagnosis::pass(CONFIG, "user, pass, and options"));
+                Some(config)
+            }
+            Err(err) => {
+                diagnoses.push(Diagnosis::problem(
+                    CONFIG,
+                    Status::Fail,
+                    err,
+                    format!(
+                        "Fix `{}`, or check it using `aur-thumbsup check-config`",
+                        path_str
+                    ),
+                ));
+                None
+            }
+        }
+    };
+
+    match &config {
+        Some(config) => diagnoses.push(check_cookie(&config.account.cookie_file)),
+        None => diagnoses.push(Diagnosis::skip(COOKIE)),
+    }
+
+    match &config {
+        Some(config) if aur_reachable => {
+            let login = config
+                .authentication(options)
+                .and_then(|mut auth| auth.login(&config.account));
+            diagnoses.push(match login {
+                Ok(_) => Diagnosis::pass(LOGIN, format!("as `{}`", config.account.user)),
+                Err(err) => Diagnosis::problem(
+                    LOGIN,
+                    Status::Fail,
+                    err,
+                    "Check user and pass in configuration, then run `aur-thumbsup renew-cookie`",
+                ),
+            });
+        }
+        _ => diagnoses.push(Diagnosis::skip(LOGIN)),
+    }
+
+    let report = HealthReport::new(&diagnoses);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&report)?),
+        _ => {
+            for diagnosis in &diagnoses {
+                println!("{}", fancy(diagnosis, style));
+            }
+        }
+    }
+
+    match report.ok {
+        true => Ok(()),
+        false => Err(ExitStatus(1).into()),
+    }
+}
+
+fn check_programs() -> Diagnosis {
+    let missing = missing_programs(&REQUIRED_PROGRAMS);
+    if missing.is_empty() {
+        return Diagnosis::pass(PROGRAMS, REQUIRED_PROGRAMS.join(", "));
+    }
+    Diagnosis::problem(
+        PROGRAMS,
+        Status::Fail,
+        format!("missing {}", missing.join(", ")),
+        "Install `pacman`; aur-thumbsup runs on Arch Linux",
+    )
+}
+
+fn check_cookie(cookie_file: &Path) -> Diagnosis {
+    const HINT: &str = "A new cookie is created on next login, or run `aur-thumbsup renew-cookie`";
+
+    let cookie_jar = match load_cookie_file(cookie_file) {
+        Ok(cookie_jar) => cookie_jar,
+        Err(err) => {
+            return Diagnosis::problem(
+                COOKIE,
+                Status::Warn,
+                format!("Unable to read `{}`: {}", cookie_file.display(), err),
+                HINT,
+            )
+        }
+    };
+    if cookie_jar.get("AURSID").is_none() {
+        return Diagnosis::problem(COOKIE, Status::Warn, "no session (AURSID)", HINT);
+    }
+    if !is_file_secure(cookie_file).unwrap_or(false) {
+        return Diagnosis::problem(
+            COOKIE,
+            Status::Warn,
+            "not secure, its session is not used",
+            "Set its permission to 0600, or run `aur-thumbsup renew-cookie`",
+        );
+    }
+    match cookie_jar.get("AURTZ") {
+        Some(aurtz) if is_cookie_expired(aurtz, OffsetDateTime::now_utc()) => {
+            Diagnosis::problem(COOKIE, Status::Warn, "session is expired", HINT)
+        }
+        _ => Diagnosis::pass(COOKIE, format!("`{}`", cookie_file.display())),
+    }
+}
+
+fn fancy(diagnosis: &Diagnosis, style: &Style) -> String {
+    let status = match diagnosis.status {
+        Status::Pass => style.good("PASS"),
+        Status::Warn => style.warn("WARN"),
+        Status::Fail => style.bad("FAIL"),
+        Status::Skip => "SKIP".dimmed(),
+    };
+    let line = format!(
+        "[{}] {}: {}",
+        status,
+        style.name(diagnosis.check.title),
+        diagnosis.detail
+    );
+    match &diagnosis.hint {
+        Some(hint) => format!("{}\n       {}", line, style.label(hint)),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn test_fancy() {
+        let diagnosis = Diagnosis::pass(LOGIN, "as `foo`");
+        let expect = format!(
+            "[{}] {}: as `foo`",
+            "PASS".bright_green(),
+            "Login works".bold().white()
+        );
+        assert_eq!(fancy(&diagnosis, &Style::default()), expect);
+
+        let diagnosis = Diagnosis::problem(
+            CONFIG_PERMISSION,
+            Status::Fail,
+            "readable by other users",
+            "Run `chmod 600 /etc/aur-thumbsup.toml`",
+        );
+        let expect = format!(
+            "[{}] {}: readable by other users\n       {}",
+            "FAIL".bright_red(),
+            "Configuration file is secure".bold().white(),
+            "Run `chmod 600 /etc/aur-thumbsup.toml`".cyan()
+        );
+        assert_eq!(fancy(&diagnosis, &Style::default()), expect);
+    }
+
+    #[test]
+    fn test_health_report() {
+        let diagnoses = vec![
+            Diagnosis::pass(CONFIG, "user, pass, and options"),
+            Diagnosis::problem(COOKIE, Status::Warn, "session is expired", "Renew it"),
+            Diagnosis::pass(LOGIN, "as `foo`"),
+        ];
+        let report = HealthReport::new(&diagnoses);
+        assert!(report.ok);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"checks":[{"name":"config","ok":true,"detail":"user, pass, and options"},{"name":"cookie","ok":true,"detail":"session is expired"},{"name":"login","ok":true,"detail":"as `foo`"}],"ok":true}"#
+        );
+
+        let diagnoses = vec![
+            Diagnosis::problem(CONFIG, Status::Fail, "missing pass", "Fix it"),
+            Diagnosis::skip(COOKIE),
+            Diagnosis::skip(LOGIN),
+        ];
+        let report = HealthReport::new(&diagnoses);
+        assert!(!report.ok);
+        assert!(report.checks.iter().all(|check| !check.ok));
+
+        // Non-critical check does not make it unhealthy
+        let diagnoses = vec![
+            Diagnosis::pass(CONFIG, "user, pass, and options"),
+            Diagnosis::skip(COOKIE),
+        ];
+        assert!(HealthReport::new(&diagnoses).ok);
+    }
+
+    #[test]
+    fn test_check_cookie() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let diagnosis = check_cookie(&tempdir.path().join("missing.cookie"));
+        assert_eq!(diagnosis.status, Status::Warn);
+
+        // Readable by other users
+        let path = tempdir.path().join("test.cookie");
+        fs::write(&path, "AURSID=abc; Path=/\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let diagnosis = check_cookie(&path);
+        assert_eq!(diagnosis.status, Status::Warn);
+        assert_eq!(diagnosis.detail, "not secure, its session is not used");
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(check_cookie(&path).status, Status::Pass);
+        tempdir.close().unwrap();
+    }
+}
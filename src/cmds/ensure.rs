@@ -0,0 +1,150 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{
+    aur::{SessionOptions, VoteResult},
+    cmds::{unvote, vote},
+    config::Configuration,
+    error::{check_failures, check_stopped, ExitStatus, CHANGED_EXIT_STATUS},
+    helper::{print_flush, PkgName},
+    theme::Style,
+    timing,
+};
+
+/// Ensure packages are voted (or unvoted): check them first, then (un)vote only those which are not.
+/// Exit with `CHANGED_EXIT_STATUS` if any package is changed.
+pub fn ensure<P: AsRef<Path>>(
+    config_path: P,
+    packages: Vec<String>,
+    voted: bool,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let status = auth.check_vote(&packages, |_| Ok(()))?;
+    timing::phase("check vote");
+    check_stopped(auth.stopped(), &packages, status.len())?;
+
+    let fancy = match voted {
+        true => vote::fancy,
+        false => unvote::fancy,
+    };
+    let (to_change, mut results) = plan(&status, voted);
+    for result in &results {
+        print_flush(fancy(result, style)?)?;
+    }
+
+    let changed = match voted {
+        true => auth.vote(&to_change, |result| print_flush(fancy(result, style)?))?,
+        false => auth.unvote(&to_change, |result| print_flush(fancy(result, style)?))?,
+    };
+    timing::phase(if voted { "vote" } else { "unvote" });
+    let done = changed.len();
+    results.extend(changed);
+    print_flush(summary(&results))?;
+
+    check_stopped(auth.stopped(), &to_change, done)?;
+
+    // Unavailable package can never be voted, but it is as good as unvoted
+    if voted {
+        for (_, result) in results.iter_mut() {
+            if *result == VoteResult::NotAvailable {
+                *result = VoteResult::Failed;
+            }
+        }
+    }
+    check_failures(&results)?;
+
+    match count_changed(&results) {
+        0 => Ok(()),
+        _ => Err(ExitStatus(CHANGED_EXIT_STATUS).into()),
+    }
+}
+
+/// Packages to (un)vote, and results of packages already in the desired state or not available
+fn plan(
+    status: &[(String, Option<bool>)],
+    voted: bool,
+) -> (Vec<PkgName>, Vec<(String, VoteResult)>) {
+    let mut to_change: Vec<PkgName> = Vec::new();
+    let mut results: Vec<(String, VoteResult)> = Vec::new();
+    for (pkg, is_voted) in status {
+        match (is_voted, voted) {
+            (None, _) => results.push((pkg.to_owned(), VoteResult::NotAvailable)),
+            (Some(true), true) => results.push((pkg.to_owned(), VoteResult::AlreadyVoted)),
+            (Some(false), false) => results.push((pkg.to_owned(), VoteResult::AlreadyUnVoted)),
+            _ => to_change.push(pkg.to_owned()),
+        }
+    }
+    (to_change, results)
+}
+
+fn count_changed(results: &[(String, VoteResult)]) -> usize {
+    results
+        .iter()
+        .filter(|(_, result)| matches!(result, VoteResult::Voted | VoteResult::UnVoted))
+        .count()
+}
+
+fn summary(results: &[(String, VoteResult)]) -> String {
+    let changed = count_changed(results);
+    let ok = results
+        .iter()
+        .filter(|(_, result)| {
+            matches!(
+                result,
+                VoteResult::AlreadyVoted | VoteResult::AlreadyUnVoted
+            )
+        })
+        .count();
+    format!("{} changed, {} ok", changed, ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let status = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("networkd-broker".to_owned(), Some(false)),
+            ("not-exist".to_owned(), None),
+        ];
+
+        let (to_change, results) = plan(&status, true);
+        assert_eq!(to_change, vec!["networkd-broker".to_owned()]);
+        assert_eq!(
+            results,
+            vec![
+                ("pacman-mirrorup".to_owned(), VoteResult::AlreadyVoted),
+                ("not-exist".to_owned(), VoteResult::NotAvailable),
+            ]
+        );
+
+        let (to_change, results) = plan(&status, false);
+        assert_eq!(to_change, vec!["pacman-mirrorup".to_owned()]);
+        assert_eq!(
+            results,
+            vec![
+                ("networkd-broker".to_owned(), VoteResult::AlreadyUnVoted),
+                ("not-exist".to_owned(), VoteResult::NotAvailable),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let results = vec![
+            ("pacman-mirrorup".to_owned(), VoteResult::AlreadyVoted),
+            ("networkd-broker".to_owned(), VoteResult::Voted),
+            ("aur-thumbsup".to_owned(), VoteResult::Failed),
+        ];
+        assert_eq!(count_changed(&results), 1);
+        assert_eq!(summary(&results), "1 changed, 1 ok");
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Result};
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::Path,
+};
+
+use crate::{
+    args::OutputFormat,
+    aur::{AurPackageResults, SessionOptions},
+    config::Configuration,
+    helper::print_flush,
+    timing,
+};
+
+/// Save voted packages to `path` as CSV or JSON, readable only by owner
+pub fn export<P: AsRef<Path>, Q: AsRef<Path>>(
+    config_path: P,
+    path: Q,
+    force: bool,
+    options: &SessionOptions,
+    format: OutputFormat,
+) -> Result<()> {
+    let path = path.as_ref();
+    let format = export_format(path, format)?;
+    // Fail before login
+    if !force && path.exists() {
+        return Err(already_exists(path));
+    }
+
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let voted_pkgs = auth.list_voted_pkgs()?;
+    timing::phase("fetch voted list");
+
+    write_voted_pkgs(path, &voted_pkgs, format, force)?;
+    print_flush(format!(
+        "Exported {} voted packages to `{}`",
+        voted_pkgs.len(),
+        path.display()
+    ))
+}
+
+/// Format of `--format`, or from extension of `path` if not given
+fn export_format(path: &Path, format: OutputFormat) -> Result<OutputFormat> {
+    match format {
+        OutputFormat::Json | OutputFormat::Csv => Ok(format),
+        _ => match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(OutputFormat::Json),
+            Some("csv") => Ok(OutputFormat::Csv),
+            _ => Err(anyhow!(
+                "Unknown format of `{}`, use `--format csv` or `--format json`",
+                path.display()
+            )),
+        },
+    }
+}
+
+fn already_exists(path: &Path) -> anyhow::Error {
+    anyhow!(
+        "`{}` already exists, use `--force` to overwrite it",
+        path.display()
+    )
+}
+
+/// Write packages to a new file, or overwrite existing one if `force`
+fn write_voted_pkgs(
+    path: &Path,
+    voted_pkgs: &AurPackageResults,
+    format: OutputFormat,
+    force: bool,
+) -> Result<()> {
+    let file = create_file(path, force)?;
+    match format {
+        OutputFormat::Json => {
+            let mut file = file;
+            serde_json::to_writer_pretty(&mut file, voted_pkgs)?;
+            writeln!(file)?;
+        }
+        _ => {
+            let mut writer = csv::Writer::from_writer(file);
+            for pkg in voted_pkgs {
+                writer.serialize(pkg)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn create_file(path: &Path, force: bool) -> Result<File> {
+    let mut open_options = fs::OpenOptions::new();
+    open_options.write(true).mode(0o600);
+    match force {
+        true => open_options.create(true).truncate(true),
+        false => open_options.create_new(true),
+    };
+    open_options.open(path).map_err(|err| match err.kind() {
+        ErrorKind::AlreadyExists => already_exists(path),
+        _ => anyhow!("Unable to create `{}`: {}", path.display(), err),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aur::AurPackageResultItem;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn voted_pkgs() -> AurPackageResults {
+        vec![AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.5.0-1".to_owned(),
+            votes: 12,
+            popularity: 0.25,
+            voted: true,
+            notify: false,
+            description: "A service to retrieve the best and latest Pacman mirror list".to_owned(),
+            maintainer: "bpetlert".to_owned(),
+        }]
+    }
+
+    #[test]
+    fn test_export_format() {
+        let format = |path: &str, format| export_format(Path::new(path), format).ok();
+        assert_eq!(
+            format("votes.csv", OutputFormat::Json),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(
+            format("votes.json", OutputFormat::Human),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(
+            format("votes.csv", OutputFormat::Human),
+            Some(OutputFormat::Csv)
+        );
+        assert_eq!(format("votes", OutputFormat::Human), None);
+    }
+
+    #[test]
+    fn test_write_voted_pkgs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("votes.csv");
+        write_voted_pkgs(&path, &voted_pkgs(), OutputFormat::Csv, false).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Read back as the voted list is parsed
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        let pkgs: AurPackageResults = reader.deserialize().map(|pkg| pkg.unwrap()).collect();
+        assert_eq!(pkgs, voted_pkgs());
+
+        // Existing file is kept without `force`
+        assert!(write_voted_pkgs(&path, &vec![], OutputFormat::Csv, false).is_err());
+        assert_eq!(csv::Reader::from_path(&path).unwrap().records().count(), 1);
+        write_voted_pkgs(&path, &vec![], OutputFormat::Json, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[]\n");
+    }
+}
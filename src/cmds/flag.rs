@@ -0,0 +1,44 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{
+    aur::{FlagResult, SessionOptions},
+    config::Configuration,
+    helper::print_flush,
+    theme::Style,
+    timing,
+};
+
+/// Flag package out-of-date, or unflag it if `reason` is `None`
+pub fn flag<P: AsRef<Path>>(
+    config_path: P,
+    package: &str,
+    reason: Option<&str>,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let result = match reason {
+        Some(reason) => auth.flag(package, reason)?,
+        None => auth.unflag(package)?,
+    };
+    timing::phase(if reason.is_some() { "flag" } else { "unflag" });
+    print_flush(fancy(package, &result, style))
+}
+
+fn fancy(pkg: &str, result: &FlagResult, style: &Style) -> String {
+    format!(
+        "{}    {}",
+        style.name(pkg),
+        match result {
+            FlagResult::Flagged => style.good("Flagged out-of-date"),
+            FlagResult::AlreadyFlagged => style.good("Already flagged out-of-date"),
+            FlagResult::Unflagged => style.good("Unflagged"),
+            FlagResult::NotFlagged => style.good("Not flagged"),
+        }
+    )
+}
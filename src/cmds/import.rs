@@ -0,0 +1,195 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::{fs, os::unix::ffi::OsStrExt, path::Path};
+use tracing::{info, warn};
+
+use crate::{
+    aur::{SessionOptions, VoteResult},
+    cmds::vote::{fancy, fancy_dry_run},
+    config::Configuration,
+    error::{check_failures, check_stopped},
+    helper::{print_flush, read_pkgs_from_file, PkgName},
+    httpcache::fnv1a,
+    state::{state_file_path, BatchState},
+    theme::Style,
+    timing,
+};
+
+/// Package of a file saved by `export`, other fields are ignored
+#[derive(Deserialize)]
+struct ExportedPkg {
+    #[serde(rename = "Name")]
+    name: PkgName,
+}
+
+/// Vote for packages listed in file, one per line, or saved by `export` as CSV or JSON
+pub fn import<P: AsRef<Path>, S: AsRef<Path>>(
+    config_path: P,
+    source: S,
+    resume: bool,
+    dry_run: bool,
+    options: &SessionOptions,
+    cache_dir: Option<&Path>,
+    style: &Style,
+) -> Result<()> {
+    // Nothing is voted, no need to login
+    if dry_run {
+        for pkg in read_import_pkgs(&source)? {
+            print_flush(fancy_dry_run(&pkg, style))?;
+        }
+        return Ok(());
+    }
+
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let state_path = state_file_path(
+        &config.cache_dir(cache_dir)?,
+        &config.account,
+        &state_name(&source),
+    );
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let saved_state = if resume {
+        let state = BatchState::load(&state_path)?;
+        if state.is_none() {
+            warn!("No interrupted import to resume, start a new one");
+        }
+        state
+    } else {
+        None
+    };
+
+    let mut state = match saved_state {
+        Some(state) => {
+            info!(
+                "Resume import, {} of {} packages left",
+                state.remaining().len(),
+                state.packages.len()
+            );
+            state
+        }
+        None => {
+            let state = BatchState::new(read_import_pkgs(&source)?);
+            state.save(&state_path)?;
+            state
+        }
+    };
+
+    let total = state.packages.len();
+    let packages = state.remaining();
+    let results = auth.vote(&packages, |result| {
+        // Failed packages are left for the next `--resume`
+        if !result.1.is_failed() {
+            state.mark_done(&result.0);
+            state.save(&state_path)?;
+        }
+        print_flush(format!(
+            "[{}/{}] {}",
+            state.done.len(),
+            total,
+            fancy(result, style)?
+        ))
+    })?;
+    timing::phase("vote");
+
+    if state.is_completed() {
+        BatchState::clear(&state_path)?;
+    } else {
+        warn!(
+            "Some packages failed to vote, retry them with `import --resume`: {}",
+            state.remaining().join(", ")
+        );
+    }
+
+    let not_available: Vec<&str> = results
+        .iter()
+        .filter(|(_, result)| *result == VoteResult::NotAvailable)
+        .map(|(pkg, _)| pkg.as_str())
+        .collect();
+    if !not_available.is_empty() {
+        warn!(
+            "{} packages no longer exist on AUR: {}",
+            not_available.len(),
+            not_available.join(", ")
+        );
+    }
+
+    check_stopped(auth.stopped(), &packages, results.len())?;
+    check_failures(&results)
+}
+
+/// Read packages by extension of `source`, `.csv` and `.json` as saved by `export`,
+/// otherwise one per line
+fn read_import_pkgs<P: AsRef<Path>>(source: P) -> Result<Vec<PkgName>> {
+    let source = source.as_ref();
+    let read =
+        || fs::read_to_string(source).map_err(|err| anyhow!("{} `{}`", err, source.display()));
+    let pkgs: Vec<ExportedPkg> = match source.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => csv::Reader::from_reader(read()?.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|err| anyhow!("Invalid CSV `{}`: {}", source.display(), err))?,
+        Some("json") => serde_json::from_str(&read()?)
+            .map_err(|err| anyhow!("Invalid JSON `{}`: {}", source.display(), err))?,
+        _ => return read_pkgs_from_file(source),
+    };
+    Ok(pkgs.into_iter().map(|pkg| pkg.name).collect())
+}
+
+/// Name of state file, keyed by path of import source
+fn state_name<P: AsRef<Path>>(source: P) -> String {
+    let source = fs::canonicalize(&source).unwrap_or_else(|_| source.as_ref().to_path_buf());
+    format!("import-{:016x}", fnv1a(source.as_os_str().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_import_pkgs() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("votes.csv");
+        fs::write(
+            &csv_path,
+            "Name,Version,Votes,Popularity?,Voted,Notify,Description,Maintainer\n\
+             pacman-mirrorup,0.5.0-1,12,0.25,Yes,No,\"Mirror list, fast\",bpetlert\n\
+             aur-thumbsup,0.4.0-1,3,0.01,Yes,No,Voting,bpetlert\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_import_pkgs(&csv_path).unwrap(),
+            vec!["pacman-mirrorup", "aur-thumbsup"]
+        );
+
+        let json_path = dir.path().join("votes.json");
+        fs::write(
+            &json_path,
+            r#"[{"Name":"pacman-mirrorup","Votes":12},{"Name":"aur-thumbsup"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            read_import_pkgs(&json_path).unwrap(),
+            vec!["pacman-mirrorup", "aur-thumbsup"]
+        );
+        fs::write(&json_path, "pacman-mirrorup").unwrap();
+        assert!(read_import_pkgs(&json_path).is_err());
+
+        let txt_path = dir.path().join("votes.txt");
+        fs::write(&txt_path, "pacman-mirrorup # mirror list\naur-thumbsup\n").unwrap();
+        assert_eq!(
+            read_import_pkgs(&txt_path).unwrap(),
+            vec!["pacman-mirrorup", "aur-thumbsup"]
+        );
+    }
+
+    #[test]
+    fn test_state_name() {
+        // Stable across runs, to resume with a later build
+        let name = state_name("/tmp/votes-a.txt");
+        assert_eq!(name, "import-a8cdf1be31d7a141");
+        assert_eq!(name, state_name("/tmp/votes-a.txt"));
+        assert_ne!(name, state_name("/tmp/votes-b.txt"));
+    }
+}
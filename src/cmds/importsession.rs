@@ -0,0 +1,46 @@
+use anyhow::{anyhow, Result};
+use cookie::{Cookie, CookieJar};
+use std::{fs, path::Path};
+
+use crate::{
+    aur::{parse_cookies_txt, SessionOptions},
+    config::Configuration,
+    helper::print_flush,
+    theme::Style,
+    timing,
+};
+
+/// Login using session of browser, either AURSID value or cookies.txt exported by browser,
+/// then save it to cookie file
+pub fn import_session<P: AsRef<Path>>(
+    config_path: P,
+    aursid: Option<&str>,
+    cookies_txt: Option<&Path>,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    let cookies = match (aursid, cookies_txt) {
+        (Some(aursid), _) => {
+            let mut cookies = CookieJar::new();
+            cookies.add(Cookie::new("AURSID", aursid.to_owned()));
+            cookies
+        }
+        (None, Some(path)) => {
+            let content =
+                fs::read_to_string(path).map_err(|err| anyhow!("{} `{}`", err, path.display()))?;
+            parse_cookies_txt(&content, auth.aur_url().host())?
+        }
+        (None, None) => return Err(anyhow!("Either AURSID or cookies.txt is required.")),
+    };
+
+    auth.import_session(&config.account, &cookies)?;
+    timing::phase("import session");
+
+    print_flush(format!(
+        "{} `{}`",
+        style.good("Session is saved to"),
+        config.account.cookie_file.display()
+    ))
+}
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use std::path::Path;
+use time::{format_description, OffsetDateTime};
+
+use crate::{
+    args::OutputFormat,
+    aur::{AurPackageInfoItem, SessionOptions},
+    config::Configuration,
+    theme::Style,
+    timing,
+};
+
+/// Show version, first submitted and last modified dates of packages
+pub fn info<P: AsRef<Path>>(
+    config_path: P,
+    packages: Vec<String>,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let auth = Configuration::rpc_authentication(config_path, options)?;
+    let pkgs_info = auth.info_query(&packages)?;
+    timing::phase("query package info");
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&pkgs_info)?);
+        return Ok(());
+    }
+
+    let now = OffsetDateTime::now_utc();
+    for pkg in &packages {
+        match pkgs_info.iter().find(|info| info.name == *pkg) {
+            Some(info) => println!("{}", fancy(info, now, style)?),
+            None => println!("{} [{}]", style.name(pkg), style.warn("N/A")),
+        }
+    }
+    Ok(())
+}
+
+fn fancy(info: &AurPackageInfoItem, now: OffsetDateTime, style: &Style) -> Result<String> {
+    let last_modified_days = (now.unix_timestamp() - info.last_modified) / (24 * 60 * 60);
+    Ok(format!(
+        "{} {} [{} {}, {} {} ({} days ago)]",
+        style.name(&info.name),
+        style.version(&info.version),
+        style.label("First submitted:"),
+        date(info.first_submitted)?,
+        style.label("Last modified:"),
+        date(info.last_modified)?,
+        last_modified_days
+    ))
+}
+
+/// Date of Unix timestamp, e.g. `2020-03-01`
+fn date(timestamp: i64) -> Result<String> {
+    let format = format_description::parse("[year]-[month]-[day]")?;
+    Ok(OffsetDateTime::from_unix_timestamp(timestamp)?.format(&format)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colored::Colorize;
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date(0).unwrap(), "1970-01-01");
+        assert_eq!(date(1583053140).unwrap(), "2020-03-01");
+    }
+
+    #[test]
+    fn test_fancy() {
+        let info = AurPackageInfoItem {
+            name: "yay".to_owned(),
+            version: "9.4.6-2".to_owned(),
+            package_base: "yay".to_owned(),
+            first_submitted: 1475688004,
+            last_modified: 1583053140,
+            depends: Vec::new(),
+        };
+        let now = OffsetDateTime::from_unix_timestamp(1583053140 + 30 * 24 * 60 * 60).unwrap();
+        let result = fancy(&info, now, &Style::default()).unwrap();
+        let expect = format!(
+            "{} {} [{} 2016-10-05, {} 2020-03-01 (30 days ago)]",
+            "yay".bold().white(),
+            "9.4.6-2".bold().bright_green(),
+            "First submitted:".cyan(),
+            "Last modified:".cyan()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Raw timestamps in JSON
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            serde_json::json!({
+                "name": "yay",
+                "version": "9.4.6-2",
+                "package_base": "yay",
+                "first_submitted": 1475688004,
+                "last_modified": 1583053140
+            })
+        );
+    }
+}
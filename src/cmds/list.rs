@@ -1,56 +1,911 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use colored::Colorize;
-use std::{collections::HashMap, fmt::Write, path::Path};
+use comfy_table::{Cell, Color, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    ops::Range,
+    path::Path,
+};
 
 use crate::{
-    aur::{AurPackageResultItem, Authentication},
+    args::{OutputFormat, SortKey},
+    aur::{
+        AurPackageInfo, AurPackageResultItem, AurPackageResults, AurPackageSearchItem,
+        Authentication, SessionOptions,
+    },
+    cmds::maintained,
     config::Configuration,
-    helper::{list_installed_pkgs, vercmp, PkgName, PkgVersion, Versioning},
+    helper::{
+        list_installed_pkgs, print_csv, print_flush, print_text, vercmp, PkgName, PkgVersion,
+        Versioning,
+    },
+    theme::Style,
+    timing,
 };
 
-pub fn list<P: AsRef<Path>>(config_path: P) -> Result<()> {
+/// Voted package with installed version, for NDJSON, JSON, and CSV output
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+struct ListEntry<'a> {
+    package: &'a str,
+    version: &'a str,
+    votes: u64,
+    popularity: f64,
+    maintainer: &'a str,
+    orphaned: bool,
+    #[serde(borrow)]
+    installed_version: Option<&'a str>,
+
+    /// Installed version compared to AUR version
+    relation: Option<Versioning>,
+
+    /// Number of pending deletion/merge requests, only with `--show-requests`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pending_requests: Option<u32>,
+}
+
+/// Number of packages per page of `--page`, if `--per-page` is not given
+const DEFAULT_PER_PAGE: usize = 25;
+
+/// Slice of voted packages to print, after filtering
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pagination {
+    /// Page number, starting from 1, and number of packages per page
+    Page { page: usize, per_page: usize },
+
+    /// First packages
+    Head(usize),
+
+    /// Last packages
+    Tail(usize),
+}
+
+impl Pagination {
+    /// Pagination from `--page`, `--per-page`, `--head`, and `--tail`
+    pub fn from_args(
+        page: Option<usize>,
+        per_page: Option<usize>,
+        head: Option<usize>,
+        tail: Option<usize>,
+    ) -> Option<Pagination> {
+        match (page, head, tail) {
+            (Some(page), _, _) => Some(Pagination::Page {
+                page,
+                per_page: per_page.unwrap_or(DEFAULT_PER_PAGE),
+            }),
+            (_, Some(head), _) => Some(Pagination::Head(head)),
+            (_, _, Some(tail)) => Some(Pagination::Tail(tail)),
+            _ => None,
+        }
+    }
+
+    /// Range of packages to print among `total` packages
+    fn range(&self, total: usize) -> Range<usize> {
+        match *self {
+            Pagination::Page { page, per_page } => {
+                let start = page.saturating_sub(1).saturating_mul(per_page).min(total);
+                start..start.saturating_add(per_page).min(total)
+            }
+            Pagination::Head(count) => 0..count.min(total),
+            Pagination::Tail(count) => total.saturating_sub(count)..total,
+        }
+    }
+}
+
+/// Footer of a slice of packages, e.g. `Showing 1–25 of 312`
+fn pagination_footer(range: &Range<usize>, total: usize, style: &Style) -> String {
+    if range.is_empty() {
+        return format!("Showing 0 of {}", total);
+    }
+    format!(
+        "Showing {}{}{} of {}",
+        range.start + 1,
+        style.range_dash(),
+        range.end,
+        total
+    )
+}
+
+/// Options of `list` from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct ListOptions<'a> {
+    /// Group split packages under their package base
+    pub group_by_base: bool,
+
+    /// List only orphaned packages
+    pub only_orphaned: bool,
+
+    /// List only installed packages
+    pub installed: bool,
+
+    /// List only installed packages older than AUR version
+    pub outdated: bool,
+
+    /// Show pending deletion/merge requests, one more request per package
+    pub show_requests: bool,
+
+    /// Format each package using placeholders, e.g. `{name} {version}`
+    pub template: Option<&'a str>,
+
+    /// Do not print summary at the end
+    pub no_summary: bool,
+
+    /// List packages maintained by this user instead of voted packages
+    pub user: Option<&'a str>,
+
+    /// Print only a slice of packages
+    pub pagination: Option<Pagination>,
+
+    /// Print dependencies among voted packages
+    pub graph: bool,
+
+    /// Sort voted packages, instead of order of AUR
+    pub sort: Option<SortKey>,
+
+    /// Reverse order of `sort`
+    pub reverse: bool,
+
+    /// Show votes and popularity of each package
+    pub verbose: bool,
+}
+
+/// Dependencies among voted packages, for `--graph`
+#[derive(Serialize, PartialEq, Debug)]
+struct DependencyGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+struct GraphNode {
+    name: String,
+
+    /// Not depended on by other voted packages, i.e. wanted for itself
+    leaf: bool,
+}
+
+/// `from` depends on `to`
+#[derive(Serialize, PartialEq, Debug)]
+struct GraphEdge {
+    from: String,
+    to: String,
+}
+
+impl DependencyGraph {
+    /// Graph of `voted_pkgs`, with dependencies from `pkgs_info`.
+    /// Dependencies which are not voted are left out.
+    fn new(voted_pkgs: &[PkgName], pkgs_info: &AurPackageInfo) -> DependencyGraph {
+        let voted: HashSet<&str> = voted_pkgs.iter().map(|pkg| pkg.as_str()).collect();
+        let mut edges: Vec<GraphEdge> = Vec::new();
+        for info in pkgs_info
+            .iter()
+            .filter(|info| voted.contains(info.name.as_str()))
+        {
+            for dep in info.depends.iter().map(|dep| dep_name(dep)) {
+                if voted.contains(dep) && dep != info.name {
+                    edges.push(GraphEdge {
+                        from: info.name.to_owned(),
+                        to: dep.to_owned(),
+                    });
+                }
+            }
+        }
+
+        let depended: HashSet<&str> = edges.iter().map(|edge| edge.to.as_str()).collect();
+        let nodes = voted_pkgs
+            .iter()
+            .map(|pkg| GraphNode {
+                name: pkg.to_owned(),
+                leaf: !depended.contains(pkg.as_str()),
+            })
+            .collect();
+        DependencyGraph { nodes, edges }
+    }
+
+    fn dependencies(&self, pkg: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.from == pkg)
+            .map(|edge| edge.to.as_str())
+            .collect()
+    }
+
+    /// Tree of dependencies under each leaf package, indented by depth.
+    /// Packages only in a dependency cycle are printed as roots.
+    fn fancy(&self, style: &Style) -> Result<String> {
+        let mut output = String::new();
+        let mut printed: HashSet<&str> = HashSet::new();
+        let roots = self
+            .nodes
+            .iter()
+            .filter(|node| node.leaf)
+            .chain(self.nodes.iter().filter(|node| !node.leaf));
+        for root in roots {
+            if root.leaf || !printed.contains(root.name.as_str()) {
+                self.fancy_subtree(
+                    &root.name,
+                    0,
+                    &mut Vec::new(),
+                    &mut printed,
+                    &mut output,
+                    style,
+                )?;
+            }
+        }
+        Ok(output)
+    }
+
+    fn fancy_subtree<'a>(
+        &'a self,
+        pkg: &'a str,
+        depth: usize,
+        path: &mut Vec<&'a str>,
+        printed: &mut HashSet<&'a str>,
+        output: &mut String,
+        style: &Style,
+    ) -> Result<()> {
+        let indent = "    ".repeat(depth);
+        if depth == 0 {
+            writeln!(output, "{}", style.name(pkg))?;
+        } else if path.contains(&pkg) {
+            writeln!(output, "{}{} {}", indent, pkg, style.warn("(cycle)"))?;
+            return Ok(());
+        } else {
+            writeln!(output, "{}{} {}", indent, pkg, style.label("(dependency)"))?;
+        }
+        printed.insert(pkg);
+        path.push(pkg);
+        for dep in self.dependencies(pkg) {
+            self.fancy_subtree(dep, depth + 1, path, printed, output, style)?;
+        }
+        path.pop();
+        Ok(())
+    }
+}
+
+/// Name of dependency without version constraint, e.g. `glibc` of `glibc>=2.33`
+fn dep_name(dep: &str) -> &str {
+    dep.split(['<', '>', '=']).next().unwrap_or(dep)
+}
+
+pub fn list<P: AsRef<Path>>(
+    config_path: P,
+    list_options: &ListOptions,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let ListOptions {
+        group_by_base,
+        show_requests,
+        template,
+        no_summary,
+        user,
+        pagination,
+        graph,
+        sort,
+        reverse,
+        verbose,
+        ..
+    } = *list_options;
+    if let Some(user) = user {
+        let auth = Configuration::rpc_authentication(&config_path, options)?;
+        return list_maintained_by(&auth, user, no_summary, format, style);
+    }
+    if !matches!(format, OutputFormat::Human | OutputFormat::Count) && group_by_base {
+        return Err(anyhow!(
+            "`--group-by-base` is not supported with `--format {}`",
+            format
+        ));
+    }
+    if sort.is_some() && format == OutputFormat::Ndjson {
+        return Err(anyhow!(
+            "`--sort` is not supported with `--format {}`",
+            format
+        ));
+    }
+    if pagination.is_some() && matches!(format, OutputFormat::Ndjson | OutputFormat::Count) {
+        return Err(anyhow!(
+            "`--page`, `--head`, and `--tail` are not supported with `--format {}`",
+            format
+        ));
+    }
+    if let Some(Pagination::Page { page: 0, .. }) = pagination {
+        return Err(anyhow!("`--page` starts from 1"));
+    }
+    if graph && !matches!(format, OutputFormat::Human | OutputFormat::Json) {
+        return Err(anyhow!(
+            "`--graph` is not supported with `--format {}`",
+            format
+        ));
+    }
+    if template.is_some() && format != OutputFormat::Human {
+        return Err(anyhow!(
+            "`--template` is not supported with `--format {}`",
+            format
+        ));
+    }
+    // Check template before login
+    let template = match template {
+        Some(template) => Some(Template::parse(template)?),
+        None => None,
+    };
+
     let config = Configuration::load_and_verify_config(&config_path)?;
-    let mut auth = Authentication::new();
+    let mut auth = config.authentication(options)?;
     auth.login(&config.account)?;
-    let voted_pkgs = auth.list_voted_pkgs()?;
-    let installed_pkgs: HashMap<PkgName, PkgVersion> = list_installed_pkgs()?;
+    timing::phase("login");
+
+    // Print each package as soon as it is fetched
+    if format == OutputFormat::Ndjson {
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = list_installed_pkgs()?;
+        timing::phase("list installed packages");
+        auth.list_voted_pkgs_cached_with(|pkg| {
+            if !is_listed(pkg, list_options, &installed_pkgs)? {
+                return Ok(());
+            }
+            let mut entry = list_entry(pkg, &installed_pkgs)?;
+            if show_requests {
+                entry.pending_requests = Some(auth.pending_requests(&pkg.name)?);
+            }
+            print_flush(serde_json::to_string(&entry)?)
+        })?;
+        timing::phase("fetch voted list");
+        return Ok(());
+    }
+
+    let voted_pkgs = auth.list_voted_pkgs_cached()?;
+    timing::phase("fetch voted list");
+
+    // Listed once, for filters and for output
+    let mut installed_pkgs: Option<HashMap<PkgName, PkgVersion>> = None;
+    if list_options.installed || list_options.outdated {
+        installed_pkgs = Some(list_installed_pkgs()?);
+        timing::phase("list installed packages");
+    }
+    let no_installed_pkgs = HashMap::new();
+    let mut listed_pkgs: AurPackageResults = Vec::new();
+    for pkg in voted_pkgs {
+        if is_listed(
+            &pkg,
+            list_options,
+            installed_pkgs.as_ref().unwrap_or(&no_installed_pkgs),
+        )? {
+            listed_pkgs.push(pkg);
+        }
+    }
+    let mut voted_pkgs = listed_pkgs;
+    if let Some(key) = sort {
+        sort_pkgs(&mut voted_pkgs, key, reverse);
+    }
+
+    if graph {
+        let pkgs: Vec<PkgName> = voted_pkgs.iter().map(|pkg| pkg.name.to_owned()).collect();
+        let pkgs_info = auth.info_query(&pkgs)?;
+        timing::phase("query package info");
+        let graph = DependencyGraph::new(&pkgs, &pkgs_info);
+        match format {
+            OutputFormat::Json => print_flush(serde_json::to_string(&graph)?)?,
+            _ => print_text(graph.fancy(style)?)?,
+        }
+        return Ok(());
+    }
+
+    // Count packages, not groups of package base
+    if format == OutputFormat::Count {
+        print_flush(voted_pkgs.len())?;
+        return Ok(());
+    }
+
+    let installed_pkgs: HashMap<PkgName, PkgVersion> = match installed_pkgs {
+        Some(installed_pkgs) => installed_pkgs,
+        None => {
+            let installed_pkgs = list_installed_pkgs()?;
+            timing::phase("list installed packages");
+            installed_pkgs
+        }
+    };
+
+    // Slice of packages to print, summary still covers all packages
+    let range = pagination.map(|pagination| pagination.range(voted_pkgs.len()));
+    let shown_pkgs = match &range {
+        Some(range) => &voted_pkgs[range.clone()],
+        None => &voted_pkgs[..],
+    };
+
+    // One more request per package
+    let mut pending_requests: HashMap<&str, u32> = HashMap::new();
+    if show_requests {
+        for pkg in shown_pkgs {
+            pending_requests.insert(&pkg.name, auth.pending_requests(&pkg.name)?);
+        }
+        timing::phase("fetch pending requests");
+    }
+    let requests = |pkg: &str| pending_requests.get(pkg).copied().unwrap_or(0);
+
+    if matches!(format, OutputFormat::Json | OutputFormat::Csv) {
+        let entries = shown_pkgs
+            .iter()
+            .map(|pkg| {
+                let mut entry = list_entry(pkg, &installed_pkgs)?;
+                if show_requests {
+                    entry.pending_requests = Some(requests(&pkg.name));
+                }
+                Ok(entry)
+            })
+            .collect::<Result<Vec<ListEntry>>>()?;
+        return match format {
+            OutputFormat::Json => print_flush(serde_json::to_string_pretty(&entries)?),
+            _ => print_csv(&entries),
+        };
+    }
+    let format_pkg = |pkg: &AurPackageResultItem| match &template {
+        Some(template) => template.render(pkg, &installed_pkgs),
+        None => fancy(pkg, &installed_pkgs, requests(&pkg.name), verbose, style),
+    };
 
     let mut output = String::new();
-    for pkg in &voted_pkgs {
-        writeln!(output, "{}", fancy(pkg, &installed_pkgs)?)?;
+    if format == OutputFormat::Table {
+        writeln!(
+            output,
+            "{}",
+            table(shown_pkgs, &installed_pkgs, requests, style)?
+        )?;
+    } else if group_by_base {
+        let pkgs: Vec<PkgName> = shown_pkgs.iter().map(|pkg| pkg.name.to_owned()).collect();
+        let pkgs_info = auth.info_query(&pkgs)?;
+        timing::phase("query package info");
+        for (base, members) in group_by_pkgbase(shown_pkgs, &pkgs_info) {
+            // Package is its own base, nothing to collapse
+            if members.len() == 1 && members[0].name == base {
+                writeln!(output, "{}", format_pkg(members[0])?)?;
+                continue;
+            }
+
+            writeln!(
+                output,
+                "{} {}",
+                style.label(&base).bold(),
+                style.label(&format!("({} packages)", members.len()))
+            )?;
+            for pkg in members {
+                writeln!(output, "    {}", format_pkg(pkg)?)?;
+            }
+        }
+    } else {
+        for pkg in shown_pkgs {
+            writeln!(output, "{}", format_pkg(pkg)?)?;
+        }
     }
-    print!("{}", output);
+    // Custom template may be parsed by scripts, keep output as is
+    if let (Some(range), None) = (&range, &template) {
+        writeln!(
+            output,
+            "{}",
+            pagination_footer(range, voted_pkgs.len(), style)
+        )?;
+    }
+    if !no_summary && template.is_none() {
+        writeln!(output, "{}", summary(&voted_pkgs, &installed_pkgs)?)?;
+    }
+    print_text(output)?;
 
     Ok(())
 }
 
+/// Whether voted package matches all filters of `list_options`, e.g. `--orphan --outdated`.
+/// Version is compared only for installed packages with `--outdated`.
+fn is_listed(
+    pkg: &AurPackageResultItem,
+    list_options: &ListOptions,
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+) -> Result<bool> {
+    if list_options.only_orphaned && !pkg.is_orphan() {
+        return Ok(false);
+    }
+    let local_ver = installed_pkgs.get(&pkg.name);
+    if (list_options.installed || list_options.outdated) && local_ver.is_none() {
+        return Ok(false);
+    }
+    match local_ver {
+        Some(local_ver) if list_options.outdated => {
+            Ok(vercmp(local_ver, &pkg.version)? == Versioning::Older)
+        }
+        _ => Ok(true),
+    }
+}
+
+/// Sort packages by `key`, ties are broken by name, so order is the same across runs.
+/// `reverse` reverses the whole order, including ties.
+fn sort_pkgs(pkgs: &mut AurPackageResults, key: SortKey, reverse: bool) {
+    pkgs.sort_by(|a, b| {
+        let order = match key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Votes => b.votes.cmp(&a.votes),
+            SortKey::Popularity => b.popularity.total_cmp(&a.popularity),
+        };
+        order.then_with(|| a.name.cmp(&b.name))
+    });
+    if reverse {
+        pkgs.reverse();
+    }
+}
+
+/// Group voted packages by their package base, keeping the order of first appearance.
+/// Packages unknown to RPC are treated as their own base.
+fn group_by_pkgbase<'a>(
+    voted_pkgs: &'a [AurPackageResultItem],
+    pkgs_info: &AurPackageInfo,
+) -> Vec<(String, Vec<&'a AurPackageResultItem>)> {
+    let pkgbases: HashMap<&str, &str> = pkgs_info
+        .iter()
+        .map(|info| (info.name.as_str(), info.package_base.as_str()))
+        .collect();
+
+    let mut groups: Vec<(String, Vec<&AurPackageResultItem>)> = Vec::new();
+    for pkg in voted_pkgs {
+        let base = pkgbases
+            .get(pkg.name.as_str())
+            .copied()
+            .unwrap_or(pkg.name.as_str());
+        match groups.iter_mut().find(|(b, _)| b == base) {
+            Some((_, members)) => members.push(pkg),
+            None => groups.push((base.to_owned(), vec![pkg])),
+        }
+    }
+
+    groups
+}
+
+fn list_entry<'a>(
+    aur_pkg: &'a AurPackageResultItem,
+    installed_pkgs: &'a HashMap<PkgName, PkgVersion>,
+) -> Result<ListEntry<'a>> {
+    let installed_version = installed_pkgs.get(&aur_pkg.name);
+    let relation = match installed_version {
+        Some(local_ver) => Some(vercmp(local_ver, &aur_pkg.version)?),
+        None => None,
+    };
+    Ok(ListEntry {
+        package: &aur_pkg.name,
+        version: &aur_pkg.version,
+        votes: aur_pkg.votes,
+        popularity: aur_pkg.popularity,
+        maintainer: &aur_pkg.maintainer,
+        orphaned: aur_pkg.is_orphan(),
+        installed_version: installed_version.map(|v| v.as_str()),
+        relation,
+        pending_requests: None,
+    })
+}
+
+/// Packages maintained by `user` and their votes, from public RPC.
+/// Voted packages of other users are private, so only their maintained packages are listed.
+fn list_maintained_by(
+    auth: &Authentication,
+    user: &str,
+    no_summary: bool,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    if !matches!(
+        format,
+        OutputFormat::Human | OutputFormat::Ndjson | OutputFormat::Count
+    ) {
+        return Err(anyhow!(
+            "`--user` is not supported with `--format {}`",
+            format
+        ));
+    }
+
+    let pkgs = auth.search_maintained_pkgs(user)?;
+    timing::phase("search maintained packages");
+
+    match format {
+        OutputFormat::Count => print_flush(pkgs.len())?,
+        OutputFormat::Ndjson => {
+            for pkg in &pkgs {
+                print_flush(serde_json::to_string(pkg)?)?;
+            }
+        }
+        _ => {
+            print_flush(maintained_by_header(user))?;
+            for pkg in &pkgs {
+                print_flush(maintained::fancy(pkg, style))?;
+            }
+            if !no_summary {
+                print_flush(maintained_by_summary(&pkgs))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn maintained_by_header(user: &str) -> String {
+    format!(
+        "Packages maintained by {} (voted packages of {} are private, not listed)",
+        user.bold(),
+        user
+    )
+}
+
+fn maintained_by_summary(pkgs: &[AurPackageSearchItem]) -> String {
+    format!(
+        "{} packages, {} votes in total",
+        pkgs.len(),
+        pkgs.iter().map(|pkg| pkg.votes).sum::<u64>()
+    )
+}
+
+/// Summary of voted packages, e.g. `10 packages, 5 installed, 1 outdated, 2 orphaned`
+fn summary(
+    voted_pkgs: &AurPackageResults,
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+) -> Result<String> {
+    let mut installed: usize = 0;
+    let mut outdated: usize = 0;
+    let mut orphaned: usize = 0;
+    for pkg in voted_pkgs {
+        if let Some(local_ver) = installed_pkgs.get(&pkg.name) {
+            installed += 1;
+            if vercmp(local_ver, &pkg.version)? == Versioning::Older {
+                outdated += 1;
+            }
+        }
+        if pkg.is_orphan() {
+            orphaned += 1;
+        }
+    }
+    Ok(format!(
+        "{} packages, {} installed, {} outdated, {} orphaned",
+        voted_pkgs.len(),
+        installed,
+        outdated,
+        orphaned
+    ))
+}
+
+/// Table of voted packages for `--format table`
+fn table<F>(
+    voted_pkgs: &[AurPackageResultItem],
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    pending_requests: F,
+    style: &Style,
+) -> Result<Table>
+where
+    F: Fn(&str) -> u32,
+{
+    let mut table = style.table();
+    table.set_header(vec![
+        "Name",
+        "Version",
+        "Installed",
+        "Status",
+        "Votes",
+        "Popularity",
+    ]);
+
+    for pkg in voted_pkgs {
+        let (status, color) =
+            table_status(pkg, installed_pkgs, pending_requests(&pkg.name), style)?;
+        let status = match color {
+            Some(color) => Cell::new(status).fg(color),
+            None => Cell::new(status),
+        };
+        table.add_row(vec![
+            Cell::new(&pkg.name),
+            Cell::new(&pkg.version),
+            Cell::new(installed_pkgs.get(&pkg.name).map_or("", |v| v.as_str())),
+            status,
+            Cell::new(pkg.votes),
+            Cell::new(pkg.popularity),
+        ]);
+    }
+
+    Ok(table)
+}
+
+/// Status cell of a package in table and its color, the most severe status wins
+fn table_status(
+    aur_pkg: &AurPackageResultItem,
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    pending_requests: u32,
+    style: &Style,
+) -> Result<(String, Option<Color>)> {
+    let mut status: Vec<String> = Vec::new();
+    let mut color: Option<Color> = None;
+
+    if let Some(local_ver) = installed_pkgs.get(&aur_pkg.name) {
+        match vercmp(local_ver, &aur_pkg.version)? {
+            Versioning::Older => {
+                status.push("Outdated".to_owned());
+                color = Some(style.bad_cell());
+            }
+            Versioning::Same => {
+                status.push("Up to date".to_owned());
+                color = Some(style.good_cell());
+            }
+            Versioning::Newer => {
+                status.push("Newer".to_owned());
+                color = Some(style.warn_cell());
+            }
+        }
+    }
+
+    if aur_pkg.is_orphan() {
+        status.push("Orphaned".to_owned());
+        color = Some(style.bad_cell());
+    }
+
+    if pending_requests > 0 {
+        status.push(format!("Pending requests: {}", pending_requests));
+        color = Some(style.bad_cell());
+    }
+
+    Ok((status.join(", "), color))
+}
+
+/// Placeholders of `--template`
+const TEMPLATE_FIELDS: [&str; 10] = [
+    "name",
+    "version",
+    "votes",
+    "popularity",
+    "voted",
+    "notify",
+    "description",
+    "maintainer",
+    "installed",
+    "status",
+];
+
+/// Output template of a package, e.g. `{name} {version} {votes}`. Use `{{` and `}}` for braces.
+#[derive(PartialEq, Debug)]
+struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(PartialEq, Debug)]
+enum TemplatePart {
+    Text(String),
+    Field(String),
+}
+
+impl Template {
+    fn parse(template: &str) -> Result<Template> {
+        let mut parts: Vec<TemplatePart> = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => {
+                                return Err(anyhow!("Unclosed `{{` in template `{}`", template))
+                            }
+                        }
+                    }
+                    if !TEMPLATE_FIELDS.contains(&field.as_str()) {
+                        return Err(anyhow!(
+                            "Unknown placeholder `{{{}}}` in template, must be one of {:?}",
+                            field,
+                            TEMPLATE_FIELDS
+                        ));
+                    }
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field(field));
+                }
+                '}' => return Err(anyhow!("Unmatched `}}` in template `{}`", template)),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+        Ok(Template { parts })
+    }
+
+    fn render(
+        &self,
+        aur_pkg: &AurPackageResultItem,
+        installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    ) -> Result<String> {
+        let installed_version = installed_pkgs.get(&aur_pkg.name);
+        let mut output = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Field(field) => match field.as_str() {
+                    "name" => output.push_str(&aur_pkg.name),
+                    "version" => output.push_str(&aur_pkg.version),
+                    "votes" => write!(output, "{}", aur_pkg.votes)?,
+                    "popularity" => write!(output, "{}", aur_pkg.popularity)?,
+                    "voted" => write!(output, "{}", aur_pkg.voted)?,
+                    "notify" => write!(output, "{}", aur_pkg.notify)?,
+                    "description" => output.push_str(&aur_pkg.description),
+                    "maintainer" => output.push_str(&aur_pkg.maintainer),
+                    "installed" => output.push_str(installed_version.map_or("", |v| v.as_str())),
+                    "status" => {
+                        if let Some(local_ver) = installed_version {
+                            output.push_str(match vercmp(local_ver, &aur_pkg.version)? {
+                                Versioning::Older => "outdated",
+                                Versioning::Same => "same",
+                                Versioning::Newer => "newer",
+                            });
+                        }
+                    }
+                    _ => unreachable!("Placeholder is checked when parsing template"),
+                },
+            }
+        }
+        Ok(output)
+    }
+}
+
 fn fancy(
     aur_pkg: &AurPackageResultItem,
     installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    pending_requests: u32,
+    verbose: bool,
+    style: &Style,
 ) -> Result<String> {
     let mut status: Vec<String> = Vec::new();
 
+    if verbose {
+        status.push(format!("{} {}", style.label("Votes:"), aur_pkg.votes));
+        status.push(format!(
+            "{} {:.2}",
+            style.label("Popularity:"),
+            aur_pkg.popularity
+        ));
+    }
+
     // Install?
     if let Some(local_ver) = installed_pkgs.get(&aur_pkg.name) {
-        let result: String = match vercmp(&local_ver, &aur_pkg.version)? {
-            Versioning::Older => format!("{}, {}", local_ver.bright_red(), "Outdated".bright_red()),
-            Versioning::Same => format!("{}", local_ver.bright_green()),
+        let result: String = match vercmp(local_ver, &aur_pkg.version)? {
+            Versioning::Older => format!("{}, {}", style.bad(local_ver), style.bad("Outdated")),
+            Versioning::Same => format!("{}", style.good(local_ver)),
             Versioning::Newer => {
-                format!("{}, {}", local_ver.bright_yellow(), "Newer".bright_yellow())
+                format!("{}, {}", style.warn(local_ver), style.warn("Newer"))
             }
         };
-        status.push(format!("{} {}", "Installed:".cyan(), result));
+        status.push(format!("{} {}", style.label("Installed:"), result));
     }
 
     // Orphan?
-    if aur_pkg.maintainer == "orphan" {
-        status.push(format!("{}", "Orphaned".bright_red()));
+    if aur_pkg.is_orphan() {
+        status.push(format!("{}", style.bad("Orphaned")));
+    }
+
+    // Pending deletion/merge requests?
+    if pending_requests > 0 {
+        status.push(format!(
+            "{}",
+            style.bad(&format!("Pending requests: {}", pending_requests))
+        ));
     }
 
     Ok(format!(
         "{} {}{}",
-        aur_pkg.name.bold().white(),
-        aur_pkg.version.bold().bright_green(),
+        style.name(&aur_pkg.name),
+        style.version(&aur_pkg.version),
         match status.is_empty() {
             true => "".to_owned(),
             false => format!(" [{}]", status.join(", ")),
@@ -61,6 +916,367 @@ fn fancy(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aur::AurPackageInfoItem;
+
+    #[test]
+    fn test_list_entry_json() {
+        let aur_pkg = AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            votes: 5,
+            popularity: 0.25,
+            voted: true,
+            maintainer: "orphan".to_owned(),
+            ..Default::default()
+        };
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+        let entry = list_entry(&aur_pkg, &installed_pkgs).unwrap();
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"package":"pacman-mirrorup","version":"0.3.0-1","votes":5,"popularity":0.25,"maintainer":"orphan","orphaned":true,"installed_version":null,"relation":null}"#
+        );
+
+        // Array of `--format json` is deserialized back to the same entries
+        let entries = vec![
+            entry,
+            ListEntry {
+                package: "paru-bin",
+                version: "1.9.3-1",
+                votes: 100,
+                popularity: 5.5,
+                maintainer: "Morganamilo",
+                orphaned: false,
+                installed_version: Some("1.9.2-1"),
+                relation: Some(Versioning::Older),
+                pending_requests: Some(1),
+            },
+        ];
+        let json = serde_json::to_string_pretty(&entries).unwrap();
+        let parsed: Vec<ListEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, entries);
+    }
+
+    #[test]
+    fn test_table_status() {
+        let aur_pkg = AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            maintainer: "orphan".to_owned(),
+            ..Default::default()
+        };
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+        assert_eq!(
+            table_status(&aur_pkg, &installed_pkgs, 0, &Style::default()).unwrap(),
+            ("Orphaned".to_owned(), Some(Color::Red))
+        );
+        assert_eq!(
+            table_status(&aur_pkg, &installed_pkgs, 2, &Style::default()).unwrap(),
+            ("Orphaned, Pending requests: 2".to_owned(), Some(Color::Red))
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let voted_pkgs: AurPackageResults = vec![AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            votes: 5,
+            popularity: 0.25,
+            maintainer: "bpetlert".to_owned(),
+            ..Default::default()
+        }];
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+        let output = table(&voted_pkgs, &installed_pkgs, |_| 0, &Style::default())
+            .unwrap()
+            .to_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].contains("Name") && lines[1].contains("Popularity"));
+        assert!(lines[3].contains("pacman-mirrorup") && lines[3].contains("0.25"));
+    }
+
+    #[test]
+    fn test_pagination() {
+        let page = |page, per_page| Pagination::Page { page, per_page };
+        assert_eq!(page(1, 25).range(312), 0..25);
+        assert_eq!(page(13, 25).range(312), 300..312);
+        assert_eq!(page(14, 25).range(312), 312..312);
+        assert_eq!(Pagination::Head(10).range(312), 0..10);
+        assert_eq!(Pagination::Head(10).range(5), 0..5);
+        assert_eq!(Pagination::Tail(10).range(312), 302..312);
+        assert_eq!(Pagination::Tail(10).range(5), 0..5);
+
+        assert_eq!(
+            Pagination::from_args(Some(2), None, None, None),
+            Some(page(2, DEFAULT_PER_PAGE))
+        );
+        assert_eq!(Pagination::from_args(None, None, None, None), None);
+
+        assert_eq!(
+            pagination_footer(&(0..25), 312, &Style::default()),
+            "Showing 1–25 of 312"
+        );
+        assert_eq!(
+            pagination_footer(&(312..312), 312, &Style::default()),
+            "Showing 0 of 312"
+        );
+    }
+
+    #[test]
+    fn test_maintained_by_summary() {
+        let pkgs = vec![
+            AurPackageSearchItem {
+                name: "pacman-mirrorup".to_owned(),
+                version: "0.3.0-1".to_owned(),
+                votes: 5,
+                popularity: 0.25,
+                out_of_date: None,
+            },
+            AurPackageSearchItem {
+                name: "systemd-guest-user".to_owned(),
+                version: "0.4.0-1".to_owned(),
+                votes: 3,
+                popularity: 0.1,
+                out_of_date: None,
+            },
+        ];
+        assert_eq!(maintained_by_summary(&pkgs), "2 packages, 8 votes in total");
+        assert_eq!(maintained_by_summary(&[]), "0 packages, 0 votes in total");
+    }
+
+    #[test]
+    fn test_summary() {
+        let voted_pkgs: AurPackageResults = vec![
+            AurPackageResultItem {
+                name: "pacman-mirrorup".to_owned(),
+                maintainer: "bpetlert".to_owned(),
+                ..Default::default()
+            },
+            AurPackageResultItem {
+                name: "networkd-broker".to_owned(),
+                maintainer: "orphan".to_owned(),
+                ..Default::default()
+            },
+        ];
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+        assert_eq!(
+            summary(&voted_pkgs, &installed_pkgs).unwrap(),
+            "2 packages, 0 installed, 0 outdated, 1 orphaned"
+        );
+    }
+
+    #[test]
+    fn test_template() {
+        let aur_pkg = AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            votes: 5,
+            maintainer: "bpetlert".to_owned(),
+            ..Default::default()
+        };
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = HashMap::new();
+
+        let template = Template::parse("{name} {version} {{{votes}}} by {maintainer}").unwrap();
+        assert_eq!(
+            template.render(&aur_pkg, &installed_pkgs).unwrap(),
+            "pacman-mirrorup 0.3.0-1 {5} by bpetlert"
+        );
+
+        // Not installed
+        let template = Template::parse("{name}:{installed}:{status}").unwrap();
+        assert_eq!(
+            template.render(&aur_pkg, &installed_pkgs).unwrap(),
+            "pacman-mirrorup::"
+        );
+
+        let err = Template::parse("{name} {size}").unwrap_err();
+        assert!(err.to_string().starts_with("Unknown placeholder `{size}`"));
+        assert!(Template::parse("{name").is_err());
+        assert!(Template::parse("name}").is_err());
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let voted_pkgs: Vec<PkgName> = ["app", "lib-a", "lib-b", "tool"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let pkgs_info: AurPackageInfo = [
+            ("app", vec!["lib-a>=1.0", "glibc"]),
+            ("lib-a", vec!["lib-b"]),
+            ("lib-b", vec![]),
+            ("tool", vec!["lib-b=2.0"]),
+        ]
+        .iter()
+        .map(|(name, depends)| AurPackageInfoItem {
+            name: name.to_string(),
+            depends: depends.iter().map(|dep| dep.to_string()).collect(),
+            ..Default::default()
+        })
+        .collect();
+
+        let graph = DependencyGraph::new(&voted_pkgs, &pkgs_info);
+        let leaves: Vec<&str> = graph
+            .nodes
+            .iter()
+            .filter(|node| node.leaf)
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(leaves, vec!["app", "tool"]);
+        assert_eq!(
+            serde_json::to_string(&graph.edges).unwrap(),
+            r#"[{"from":"app","to":"lib-a"},{"from":"lib-a","to":"lib-b"},{"from":"tool","to":"lib-b"}]"#
+        );
+
+        let expect = format!(
+            "{}\n    lib-a {}\n        lib-b {}\n{}\n    lib-b {}\n",
+            "app".bold().white(),
+            "(dependency)".cyan(),
+            "(dependency)".cyan(),
+            "tool".bold().white(),
+            "(dependency)".cyan()
+        );
+        assert_eq!(graph.fancy(&Style::default()).unwrap(), expect);
+
+        // Cycle without leaf is still printed
+        let pkgs_info: AurPackageInfo = [("a", "b"), ("b", "a")]
+            .iter()
+            .map(|(name, dep)| AurPackageInfoItem {
+                name: name.to_string(),
+                depends: vec![dep.to_string()],
+                ..Default::default()
+            })
+            .collect();
+        let graph = DependencyGraph::new(&["a".to_owned(), "b".to_owned()], &pkgs_info);
+        let expect = format!(
+            "{}\n    b {}\n        a {}\n",
+            "a".bold().white(),
+            "(dependency)".cyan(),
+            "(cycle)".bright_yellow()
+        );
+        assert_eq!(graph.fancy(&Style::default()).unwrap(), expect);
+    }
+
+    #[test]
+    fn test_sort_pkgs() {
+        let pkg = |name: &str, votes, popularity| AurPackageResultItem {
+            name: name.to_owned(),
+            votes,
+            popularity,
+            ..Default::default()
+        };
+        let sorted = |key, reverse| {
+            let mut pkgs: AurPackageResults = vec![
+                pkg("foo", 10, 0.5),
+                pkg("bar", 20, 0.1),
+                pkg("baz", 10, 1.2),
+            ];
+            sort_pkgs(&mut pkgs, key, reverse);
+            pkgs.into_iter()
+                .map(|pkg| pkg.name)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(sorted(SortKey::Name, false), vec!["bar", "baz", "foo"]);
+        assert_eq!(sorted(SortKey::Name, true), vec!["foo", "baz", "bar"]);
+        // Same votes of `baz` and `foo` are sorted by name
+        assert_eq!(sorted(SortKey::Votes, false), vec!["bar", "baz", "foo"]);
+        assert_eq!(sorted(SortKey::Votes, true), vec!["foo", "baz", "bar"]);
+        assert_eq!(
+            sorted(SortKey::Popularity, false),
+            vec!["baz", "foo", "bar"]
+        );
+        assert_eq!(sorted(SortKey::Popularity, true), vec!["bar", "foo", "baz"]);
+    }
+
+    #[test]
+    fn test_is_listed() {
+        let orphaned = AurPackageResultItem {
+            name: "foo".to_owned(),
+            maintainer: "orphan".to_owned(),
+            ..Default::default()
+        };
+        let maintained = AurPackageResultItem {
+            name: "bar".to_owned(),
+            maintainer: "bpetlert".to_owned(),
+            ..Default::default()
+        };
+        let installed_pkgs: HashMap<PkgName, PkgVersion> =
+            HashMap::from([("bar".to_owned(), "1.0.0-1".to_owned())]);
+        let is_listed = |pkg, list_options| is_listed(pkg, list_options, &installed_pkgs).unwrap();
+
+        let all = ListOptions::default();
+        assert!(is_listed(&orphaned, &all));
+        assert!(is_listed(&maintained, &all));
+
+        let only_orphaned = ListOptions {
+            only_orphaned: true,
+            ..Default::default()
+        };
+        assert!(is_listed(&orphaned, &only_orphaned));
+        assert!(!is_listed(&maintained, &only_orphaned));
+
+        let installed = ListOptions {
+            installed: true,
+            ..Default::default()
+        };
+        assert!(!is_listed(&orphaned, &installed));
+        assert!(is_listed(&maintained, &installed));
+
+        // Filters are combined
+        let orphaned_installed = ListOptions {
+            only_orphaned: true,
+            installed: true,
+            ..Default::default()
+        };
+        assert!(!is_listed(&orphaned, &orphaned_installed));
+        assert!(!is_listed(&maintained, &orphaned_installed));
+
+        // Not installed package is not outdated, without comparing versions
+        let outdated = ListOptions {
+            outdated: true,
+            ..Default::default()
+        };
+        assert!(!is_listed(&orphaned, &outdated));
+    }
+
+    #[test]
+    fn test_group_by_pkgbase() {
+        let voted_pkgs: AurPackageResults = ["foo", "bar", "foo-docs", "baz"]
+            .iter()
+            .map(|name| AurPackageResultItem {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let pkgs_info: AurPackageInfo = [("foo", "foo"), ("foo-docs", "foo"), ("bar", "bar")]
+            .iter()
+            .map(|(name, base)| AurPackageInfoItem {
+                name: name.to_string(),
+                package_base: base.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let groups = group_by_pkgbase(&voted_pkgs, &pkgs_info);
+        let groups: Vec<(&str, Vec<&str>)> = groups
+            .iter()
+            .map(|(base, members)| {
+                (
+                    base.as_str(),
+                    members.iter().map(|pkg| pkg.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("foo", vec!["foo", "foo-docs"]),
+                ("bar", vec!["bar"]),
+                // Not found by RPC
+                ("baz", vec!["baz"]),
+            ]
+        );
+    }
 
     #[test]
     fn test_fancy() {
@@ -78,7 +1294,7 @@ mod tests {
         installed_pkgs.insert("pacman-mirrorup".to_owned(), "0.3.0-1".to_owned());
 
         // Same version
-        let result = fancy(&aur_pkg, &installed_pkgs).unwrap();
+        let result = fancy(&aur_pkg, &installed_pkgs, 0, false, &Style::default()).unwrap();
         let expect = format!(
             "{} {} [{} {}]",
             aur_pkg.name.bold().white(),
@@ -90,7 +1306,7 @@ mod tests {
 
         // AUR is newer
         aur_pkg.version = "0.3.0.r5.ge7b1840-1".to_owned();
-        let result = fancy(&aur_pkg, &installed_pkgs).unwrap();
+        let result = fancy(&aur_pkg, &installed_pkgs, 0, false, &Style::default()).unwrap();
         let expect = format!(
             "{} {} [{} {}, {}]",
             aur_pkg.name.bold().white(),
@@ -104,7 +1320,7 @@ mod tests {
         // local is newer
         aur_pkg.version = "0.3.0-1".to_owned();
         *installed_pkgs.get_mut(&aur_pkg.name).unwrap() = "0.3.0.r5.ge7b1840-1".to_owned();
-        let result = fancy(&aur_pkg, &installed_pkgs).unwrap();
+        let result = fancy(&aur_pkg, &installed_pkgs, 0, false, &Style::default()).unwrap();
         let expect = format!(
             "{} {} [{} {}, {}]",
             aur_pkg.name.bold().white(),
@@ -119,7 +1335,7 @@ mod tests {
         aur_pkg.version = "0.3.0-1".to_owned();
         aur_pkg.maintainer = "orphan".to_owned();
         *installed_pkgs.get_mut(&aur_pkg.name).unwrap() = "0.3.0-1".to_owned();
-        let result = fancy(&aur_pkg, &installed_pkgs).unwrap();
+        let result = fancy(&aur_pkg, &installed_pkgs, 0, false, &Style::default()).unwrap();
         let expect = format!(
             "{} {} [{} {}, {}]",
             aur_pkg.name.bold().white(),
@@ -133,7 +1349,7 @@ mod tests {
         // Not install and orphan
         aur_pkg.maintainer = "orphan".to_owned();
         installed_pkgs.remove(&aur_pkg.name);
-        let result = fancy(&aur_pkg, &installed_pkgs).unwrap();
+        let result = fancy(&aur_pkg, &installed_pkgs, 0, false, &Style::default()).unwrap();
         let expect = format!(
             "{} {} [{}]",
             aur_pkg.name.bold().white(),
@@ -145,7 +1361,7 @@ mod tests {
         // Not install and not orphan
         aur_pkg.maintainer = "bpetlert".to_owned();
         installed_pkgs.remove(&aur_pkg.name);
-        let result = fancy(&aur_pkg, &installed_pkgs).unwrap();
+        let result = fancy(&aur_pkg, &installed_pkgs, 0, false, &Style::default()).unwrap();
         let expect = format!(
             "{} {}",
             aur_pkg.name.bold().white(),
@@ -153,4 +1369,56 @@ mod tests {
         );
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
     }
+
+    #[test]
+    fn test_fancy_pending_requests() {
+        let aur_pkg = AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            voted: true,
+            maintainer: "bpetlert".to_owned(),
+            ..Default::default()
+        };
+        let result = fancy(&aur_pkg, &HashMap::new(), 2, false, &Style::default()).unwrap();
+        let expect = format!(
+            "{} {} [{}]",
+            aur_pkg.name.bold().white(),
+            aur_pkg.version.bold().bright_green(),
+            "Pending requests: 2".bright_red()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+    }
+
+    #[test]
+    fn test_fancy_verbose() {
+        let aur_pkg = AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            votes: 1234,
+            popularity: 8.7,
+            voted: true,
+            maintainer: "orphan".to_owned(),
+            ..Default::default()
+        };
+        let result = fancy(&aur_pkg, &HashMap::new(), 0, true, &Style::default()).unwrap();
+        let expect = format!(
+            "{} {} [{} 1234, {} 8.70, {}]",
+            aur_pkg.name.bold().white(),
+            aur_pkg.version.bold().bright_green(),
+            "Votes:".cyan(),
+            "Popularity:".cyan(),
+            "Orphaned".bright_red()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Compact by default
+        let result = fancy(&aur_pkg, &HashMap::new(), 0, false, &Style::default()).unwrap();
+        let expect = format!(
+            "{} {} [{}]",
+            aur_pkg.name.bold().white(),
+            aur_pkg.version.bold().bright_green(),
+            "Orphaned".bright_red()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+    }
 }
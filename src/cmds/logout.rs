@@ -0,0 +1,30 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{
+    aur::{LogoutResult, SessionOptions},
+    config::Configuration,
+    helper::print_flush,
+    timing,
+};
+
+/// Logout session in cookie file of account, then remove the cookie file
+pub fn logout<P: AsRef<Path>>(config_path: P) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(&SessionOptions::default())?;
+    let result = auth.logout(&config.account)?;
+    timing::phase("logout");
+
+    let cookie_file = config.account.cookie_file.display();
+    print_flush(match result {
+        LogoutResult::LoggedOut => format!("Logged out, removed cookie file `{}`.", cookie_file),
+        LogoutResult::SessionInvalid => format!(
+            "Session was no longer valid, removed cookie file `{}`.",
+            cookie_file
+        ),
+        LogoutResult::NoCookieFile => format!(
+            "Not logged in, cookie file `{}` does not exist.",
+            cookie_file
+        ),
+    })
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{
+    args::OutputFormat,
+    aur::{AurPackageSearchItem, SessionOptions},
+    config::Configuration,
+    theme::Style,
+    timing,
+};
+
+/// List packages maintained by the logged-in user.
+/// User name is taken from the session, since `user` of account may be an email.
+pub fn maintained<P: AsRef<Path>>(
+    config_path: P,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    let user = auth.current_user()?;
+    timing::phase("login");
+    let pkgs = auth.search_maintained_pkgs(&user)?;
+    timing::phase("search maintained packages");
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&pkgs)?),
+        _ => {
+            for pkg in &pkgs {
+                println!("{}", fancy(pkg, style));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn fancy(pkg: &AurPackageSearchItem, style: &Style) -> String {
+    let mut status: Vec<String> = vec![
+        format!("{} {}", style.label("Votes:"), pkg.votes),
+        format!("{} {:.2}", style.label("Popularity:"), pkg.popularity),
+    ];
+
+    if pkg.out_of_date.is_some() {
+        status.push(format!("{}", style.bad("Out-of-date")));
+    }
+
+    format!(
+        "{} {} [{}]",
+        style.name(&pkg.name),
+        style.version(&pkg.version),
+        status.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colored::Colorize;
+
+    #[test]
+    fn test_fancy() {
+        let mut pkg = AurPackageSearchItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.3.0-1".to_owned(),
+            votes: 5,
+            popularity: 0.25,
+            out_of_date: None,
+        };
+        let result = fancy(&pkg, &Style::default());
+        let expect = format!(
+            "{} {} [{} 5, {} 0.25]",
+            pkg.name.bold().white(),
+            pkg.version.bold().bright_green(),
+            "Votes:".cyan(),
+            "Popularity:".cyan()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Flagged out-of-date
+        pkg.out_of_date = Some(1640995200);
+        let result = fancy(&pkg, &Style::default());
+        let expect = format!(
+            "{} {} [{} 5, {} 0.25, {}]",
+            pkg.name.bold().white(),
+            pkg.version.bold().bright_green(),
+            "Votes:".cyan(),
+            "Popularity:".cyan(),
+            "Out-of-date".bright_red()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+    }
+}
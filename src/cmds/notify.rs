@@ -0,0 +1,74 @@
+use anyhow::Result;
+use std::path::Path;
+use tracing::warn;
+
+use crate::{
+    aur::{NotifyResult, SessionOptions},
+    config::Configuration,
+    error::{AurError, ExitStatus, Maintenance, PARTIAL_EXIT_STATUS},
+    helper::print_flush,
+    theme::{Mark, Style},
+    timing,
+};
+
+/// Enable notifications of new comments on packages, or disable them if `enable` is false
+pub fn notify<P: AsRef<Path>>(
+    config_path: P,
+    packages: &[String],
+    enable: bool,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let mut failed: Vec<&str> = Vec::new();
+    for pkg in packages {
+        match auth.set_notify(pkg, enable) {
+            Ok(result) => print_flush(fancy(pkg, &result, style))?,
+            // Next packages would fail the same way
+            Err(err) if err.is::<AurError>() || err.is::<Maintenance>() => return Err(err),
+            Err(err) => {
+                warn!("{:#}", err);
+                print_flush(fancy_failed(pkg, style))?;
+                failed.push(pkg);
+            }
+        }
+    }
+    timing::phase(if enable { "notify" } else { "unnotify" });
+
+    if !failed.is_empty() {
+        warn!("{} packages failed: {}", failed.len(), failed.join(", "));
+        return Err(ExitStatus(PARTIAL_EXIT_STATUS).into());
+    }
+    Ok(())
+}
+
+fn fancy(pkg: &str, result: &NotifyResult, style: &Style) -> String {
+    format!(
+        "{}    {}",
+        style.name(pkg),
+        match result {
+            NotifyResult::Enabled => style.good(style.mark("Notifications enabled", Mark::Good)),
+            NotifyResult::AlreadyEnabled => {
+                style.good(style.mark("Notifications already enabled", Mark::Good))
+            }
+            NotifyResult::Disabled => {
+                style.good(style.mark("Notifications disabled", Mark::Good))
+            }
+            NotifyResult::AlreadyDisabled => {
+                style.good(style.mark("Notifications already disabled", Mark::Good))
+            }
+        }
+    )
+}
+
+fn fancy_failed(pkg: &str, style: &Style) -> String {
+    format!(
+        "{}    {}",
+        style.name(pkg),
+        style.bad(style.mark("Failed", Mark::Bad))
+    )
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{aur::SessionOptions, config::Configuration, timing};
+
+pub fn renew_cookie<P: AsRef<Path>>(config_path: P) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(&SessionOptions::default())?;
+    auth.renew_login(&config.account)?;
+    timing::phase("login");
+
+    println!(
+        "Renewed cookie file `{}`.",
+        config.account.cookie_file.to_str().unwrap()
+    );
+
+    Ok(())
+}
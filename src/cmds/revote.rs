@@ -0,0 +1,110 @@
+use anyhow::Result;
+use std::path::Path;
+use tracing::warn;
+
+use crate::{
+    aur::{SessionOptions, VoteResult},
+    cmds::vote,
+    config::Configuration,
+    error::{check_failures, check_stopped},
+    helper::{print_flush, PkgName},
+    theme::Style,
+    timing,
+};
+
+/// Check vote of every voted package, then vote again for packages whose vote is lost
+pub fn revote<P: AsRef<Path>>(
+    config_path: P,
+    dry_run: bool,
+    options: &SessionOptions,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let voted_pkgs: Vec<PkgName> = auth
+        .list_voted_pkgs()?
+        .into_iter()
+        .map(|pkg| pkg.name)
+        .collect();
+    timing::phase("fetch voted list");
+
+    let voted = auth.check_vote(&voted_pkgs, |_| Ok(()))?;
+    timing::phase("check vote");
+    check_stopped(auth.stopped(), &voted_pkgs, voted.len())?;
+
+    let (lost, not_available) = lost_votes(&voted);
+    for pkg in &not_available {
+        warn!("`{}` is no longer available on AUR", pkg);
+    }
+
+    if dry_run {
+        for pkg in &lost {
+            print_flush(fancy_dry_run(pkg, style))?;
+        }
+        return print_flush(summary(lost.len(), voted.len(), dry_run));
+    }
+
+    let results = auth.vote(&lost, |result| print_flush(vote::fancy(result, style)?))?;
+    timing::phase("vote");
+    let revoted = results
+        .iter()
+        .filter(|(_, result)| matches!(result, VoteResult::Voted))
+        .count();
+    print_flush(summary(revoted, voted.len(), dry_run))?;
+
+    check_stopped(auth.stopped(), &lost, results.len())?;
+    check_failures(&results)
+}
+
+/// Packages whose vote is lost, and packages no longer available on AUR
+fn lost_votes(voted: &[(String, Option<bool>)]) -> (Vec<PkgName>, Vec<PkgName>) {
+    let lost = voted
+        .iter()
+        .filter(|(_, status)| *status == Some(false))
+        .map(|(pkg, _)| pkg.to_owned())
+        .collect();
+    let not_available = voted
+        .iter()
+        .filter(|(_, status)| status.is_none())
+        .map(|(pkg, _)| pkg.to_owned())
+        .collect();
+    (lost, not_available)
+}
+
+/// Summary of lost votes with `--dry-run`, otherwise of votes actually restored
+fn summary(count: usize, total: usize, dry_run: bool) -> String {
+    match dry_run {
+        true => format!("{} of {} voted packages need re-voting", count, total),
+        false => format!("Re-voted {} of {} voted packages", count, total),
+    }
+}
+
+fn fancy_dry_run(pkg: &str, style: &Style) -> String {
+    format!("{}    {}", style.name(pkg), style.warn("Would vote again"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lost_votes() {
+        let voted = vec![
+            ("pacman-mirrorup".to_owned(), Some(true)),
+            ("paru-bin".to_owned(), Some(false)),
+            ("yay-bin".to_owned(), None),
+        ];
+        let (lost, not_available) = lost_votes(&voted);
+        assert_eq!(lost, vec!["paru-bin"]);
+        assert_eq!(not_available, vec!["yay-bin"]);
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary(1, 3, true), "1 of 3 voted packages need re-voting");
+        assert_eq!(summary(1, 3, false), "Re-voted 1 of 3 voted packages");
+    }
+}
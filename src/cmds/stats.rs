@@ -0,0 +1,168 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    args::OutputFormat,
+    aur::{AurPackageResultItem, SessionOptions},
+    config::Configuration,
+    helper::{list_installed_pkgs, print_flush, vercmp, PkgName, PkgVersion, Versioning},
+    theme::Style,
+    timing,
+};
+
+/// Numbers of voted packages, for `--format json`
+#[derive(Serialize, Default, PartialEq, Debug)]
+struct VoteStats {
+    voted: usize,
+    installed: usize,
+
+    /// Installed packages older than AUR version
+    outdated: usize,
+
+    orphaned: usize,
+
+    /// Sum of votes of voted packages
+    total_votes: u64,
+
+    average_votes: f64,
+    average_popularity: f64,
+}
+
+/// Print numbers of voted packages: installed, outdated, orphaned, and their votes
+pub fn stats<P: AsRef<Path>>(
+    config_path: P,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+    auth.login(&config.account)?;
+    timing::phase("login");
+
+    let voted_pkgs = auth.list_voted_pkgs_cached()?;
+    timing::phase("fetch voted list");
+    let installed_pkgs = list_installed_pkgs()?;
+    timing::phase("list installed packages");
+
+    let stats = vote_stats(&voted_pkgs, &installed_pkgs, |local, aur| {
+        vercmp(local, aur)
+    })?;
+    match format {
+        OutputFormat::Json => print_flush(serde_json::to_string_pretty(&stats)?),
+        _ => print_flush(fancy(&stats, style)),
+    }
+}
+
+/// Numbers of `voted_pkgs`, versions are compared by `compare`, e.g. `vercmp`
+fn vote_stats<F>(
+    voted_pkgs: &[AurPackageResultItem],
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+    compare: F,
+) -> Result<VoteStats>
+where
+    F: Fn(&str, &str) -> Result<Versioning>,
+{
+    let mut stats = VoteStats {
+        voted: voted_pkgs.len(),
+        ..Default::default()
+    };
+    let mut total_popularity = 0.0;
+    for pkg in voted_pkgs {
+        if let Some(local_ver) = installed_pkgs.get(&pkg.name) {
+            stats.installed += 1;
+            if compare(local_ver, &pkg.version)? == Versioning::Older {
+                stats.outdated += 1;
+            }
+        }
+        if pkg.is_orphan() {
+            stats.orphaned += 1;
+        }
+        stats.total_votes += pkg.votes;
+        total_popularity += pkg.popularity;
+    }
+    if !voted_pkgs.is_empty() {
+        stats.average_votes = stats.total_votes as f64 / voted_pkgs.len() as f64;
+        stats.average_popularity = total_popularity / voted_pkgs.len() as f64;
+    }
+    Ok(stats)
+}
+
+fn fancy(stats: &VoteStats, style: &Style) -> String {
+    [
+        format!("{} {}", style.label("Voted:"), stats.voted),
+        format!("{} {}", style.label("Installed:"), stats.installed),
+        format!("{} {}", style.label("Outdated:"), stats.outdated),
+        format!("{} {}", style.label("Orphaned:"), stats.orphaned),
+        format!(
+            "{} {} total, {:.1} average",
+            style.label("Votes:"),
+            stats.total_votes,
+            stats.average_votes
+        ),
+        format!(
+            "{} {:.2} average",
+            style.label("Popularity:"),
+            stats.average_popularity
+        ),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vote_stats() {
+        let pkg =
+            |name: &str, version: &str, votes, popularity, maintainer: &str| AurPackageResultItem {
+                name: name.to_owned(),
+                version: version.to_owned(),
+                votes,
+                popularity,
+                voted: true,
+                maintainer: maintainer.to_owned(),
+                ..Default::default()
+            };
+        let voted_pkgs = vec![
+            pkg("pacman-mirrorup", "0.5.0-1", 12, 0.25, "bpetlert"),
+            pkg("yay", "12.3.5-1", 2000, 20.0, "jguer"),
+            pkg("foo-git", "1.0-1", 1, 0.0, "orphan"),
+            pkg("bar", "2.0-1", 3, 0.75, "orphan"),
+        ];
+        let installed_pkgs: HashMap<PkgName, PkgVersion> = [
+            ("pacman-mirrorup", "0.5.0-1"),
+            ("yay", "12.3.4-1"),
+            ("foo-git", "1.0-1"),
+        ]
+        .iter()
+        .map(|(name, version)| (name.to_string(), version.to_string()))
+        .collect();
+        // Only `yay` is older
+        let compare = |local: &str, aur: &str| {
+            Ok(match local == aur {
+                true => Versioning::Same,
+                false => Versioning::Older,
+            })
+        };
+
+        let stats = vote_stats(&voted_pkgs, &installed_pkgs, compare).unwrap();
+        assert_eq!(
+            stats,
+            VoteStats {
+                voted: 4,
+                installed: 3,
+                outdated: 1,
+                orphaned: 2,
+                total_votes: 2016,
+                average_votes: 504.0,
+                average_popularity: 5.25,
+            }
+        );
+
+        let stats = vote_stats(&[], &installed_pkgs, compare).unwrap();
+        assert_eq!(stats, VoteStats::default());
+    }
+}
@@ -1,36 +1,152 @@
 use anyhow::{anyhow, Result};
-use colored::Colorize;
-use std::{fmt::Write, path::Path};
+use std::{collections::HashMap, path::Path, time::Duration};
+use time::OffsetDateTime;
 
 use crate::{
-    aur::{Authentication, VoteResult},
+    args::OutputFormat,
+    aur::{AurPackageInfo, SessionOptions, VoteResult},
+    cmds::vote::{print_entries, VoteEntry},
     config::Configuration,
+    error::{check_failures, check_missing, check_stopped},
+    helper::print_flush,
+    theme::{Mark, Style},
+    timing,
 };
 
-pub fn unvote<P: AsRef<Path>>(config_path: P, packages: Vec<String>) -> Result<()> {
+/// Options of `unvote` from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct UnvoteOptions {
+    /// Unvote all voted packages which are orphaned, instead of given packages
+    pub only_orphaned: bool,
+
+    /// Unvote all voted packages not modified on AUR for this age, instead of given packages
+    pub stale: Option<Duration>,
+
+    /// Show packages to unvote without unvoting them
+    pub dry_run: bool,
+
+    /// Exit with 0 even if some packages are not available on AUR
+    pub ignore_missing: bool,
+}
+
+/// Exit with `PARTIAL_EXIT_STATUS` if any package failed,
+/// or is not available on AUR unless `ignore_missing`
+pub fn unvote<P: AsRef<Path>>(
+    config_path: P,
+    packages: Vec<String>,
+    unvote_options: &UnvoteOptions,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let UnvoteOptions {
+        only_orphaned,
+        stale,
+        dry_run,
+        ignore_missing,
+    } = *unvote_options;
     let config = Configuration::load_and_verify_config(&config_path)?;
-    let mut auth = Authentication::new();
+    let mut auth = config.authentication(options)?;
     auth.login(&config.account)?;
-    let results = auth.unvote(&packages)?;
+    timing::phase("login");
+
+    // Days since last modified of stale packages, only with `--stale`
+    let mut stale_ages: HashMap<String, i64> = HashMap::new();
+    let packages = if only_orphaned {
+        let voted_pkgs = auth.list_voted_pkgs()?;
+        timing::phase("fetch voted list");
+        voted_pkgs
+            .into_iter()
+            .filter(|pkg| pkg.is_orphan())
+            .map(|pkg| pkg.name)
+            .collect()
+    } else if let Some(age) = stale {
+        let voted_pkgs: Vec<String> = auth
+            .list_voted_pkgs()?
+            .into_iter()
+            .map(|pkg| pkg.name)
+            .collect();
+        timing::phase("fetch voted list");
+        let pkgs_info = auth.info_query(&voted_pkgs)?;
+        timing::phase("query package info");
+        stale_ages = stale_pkgs(&pkgs_info, age, OffsetDateTime::now_utc().unix_timestamp());
+        voted_pkgs
+            .into_iter()
+            .filter(|pkg| stale_ages.contains_key(pkg))
+            .collect()
+    } else {
+        packages
+    };
+    let age_of = |pkg: &str| stale_ages.get(pkg).map(|days| fancy_age(*days, style));
 
-    let mut output = String::new();
-    for result in results.iter() {
-        writeln!(output, "{}", fancy(result)?)?;
+    if dry_run {
+        for pkg in &packages {
+            match age_of(pkg) {
+                Some(age) => print_flush(format!("{} {}", fancy_dry_run(pkg, style), age))?,
+                None => print_flush(fancy_dry_run(pkg, style))?,
+            }
+        }
+        return Ok(());
     }
-    print!("{}", output);
 
-    Ok(())
+    let results = auth.unvote(&packages, |result| match (format, age_of(&result.0)) {
+        (OutputFormat::Human, Some(age)) => {
+            print_flush(format!("{} {}", fancy(result, style)?, age))
+        }
+        (OutputFormat::Human, None) => print_flush(fancy(result, style)?),
+        _ => Ok(()),
+    })?;
+    timing::phase("unvote");
+
+    let status = check_stopped(auth.stopped(), &packages, results.len())
+        .and_then(|_| check_failures(&results))
+        .and_then(|_| match ignore_missing {
+            true => Ok(()),
+            false => check_missing(&results),
+        });
+    print_entries(&VoteEntry::from_results(results), format)?;
+    status
+}
+
+/// Packages not modified on AUR for `age` until `now` (Unix timestamp), and days since last modified
+fn stale_pkgs(pkgs_info: &AurPackageInfo, age: Duration, now: i64) -> HashMap<String, i64> {
+    let threshold = now.saturating_sub(i64::try_from(age.as_secs()).unwrap_or(i64::MAX));
+    pkgs_info
+        .iter()
+        .filter(|info| info.last_modified < threshold)
+        .map(|info| {
+            (
+                info.name.to_owned(),
+                (now - info.last_modified) / (24 * 60 * 60),
+            )
+        })
+        .collect()
+}
+
+fn fancy_age(days: i64, style: &Style) -> String {
+    style
+        .label(&format!("(last modified {} days ago)", days))
+        .to_string()
+}
+
+pub fn fancy_dry_run(pkg: &str, style: &Style) -> String {
+    format!("{}    {}", style.name(pkg), style.warn("Would unvote"))
 }
 
-pub fn fancy(status: &(String, VoteResult)) -> Result<String> {
+pub fn fancy(status: &(String, VoteResult), style: &Style) -> Result<String> {
     Ok(format!(
         "{}    {}",
-        status.0.bold().white(),
-        match status.1 {
-            VoteResult::AlreadyUnVoted => "Already unvoted".bright_green(),
-            VoteResult::UnVoted => "Unvoted".bright_green(),
-            VoteResult::Failed => "Failed".bright_red(),
-            VoteResult::NotAvailable => "N/A".bright_red(),
+        style.name(&status.0),
+        match &status.1 {
+            VoteResult::AlreadyUnVoted => style.good(style.mark("Already unvoted", Mark::Good)),
+            VoteResult::UnVoted => style.good(style.mark("Unvoted", Mark::Good)),
+            VoteResult::Failed => style.bad(style.mark("Failed", Mark::Bad)),
+            VoteResult::NotAvailable => style.bad(style.mark("N/A", Mark::Unknown)),
+            VoteResult::Throttled(advice) => style.warn(&format!(
+                "{}: {}",
+                style.mark("Throttled", Mark::Bad),
+                advice
+            )),
             _ => return Err(anyhow!("Incorrect vote status")),
         }
     ))
@@ -39,12 +155,36 @@ pub fn fancy(status: &(String, VoteResult)) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aur::AurPackageInfoItem;
+    use colored::Colorize;
+
+    #[test]
+    fn test_stale_pkgs() {
+        let day = 24 * 60 * 60;
+        let now = 1000 * day;
+        let pkgs_info: AurPackageInfo = [("old", 100 * day), ("recent", 900 * day)]
+            .iter()
+            .map(|(name, last_modified)| AurPackageInfoItem {
+                name: name.to_string(),
+                last_modified: *last_modified,
+                ..Default::default()
+            })
+            .collect();
+        let stale = stale_pkgs(&pkgs_info, Duration::from_secs(365 * day as u64), now);
+        assert_eq!(stale, HashMap::from([("old".to_owned(), 900)]));
+        assert!(stale_pkgs(&pkgs_info, Duration::from_secs(1000 * day as u64), now).is_empty());
+
+        assert_eq!(
+            fancy_age(900, &Style::default()),
+            "(last modified 900 days ago)".cyan().to_string()
+        );
+    }
 
     #[test]
     fn test_fancy() {
         // Already unvoted
         let status = ("pacman-mirrorup".to_owned(), VoteResult::AlreadyUnVoted);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!(
             "{}    {}",
             status.0.bold().white(),
@@ -54,7 +194,7 @@ mod tests {
 
         // Unvoted
         let status = ("pacman-mirrorup".to_owned(), VoteResult::UnVoted);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!(
             "{}    {}",
             status.0.bold().white(),
@@ -64,14 +204,36 @@ mod tests {
 
         // Failed
         let status = ("pacman-mirrorup".to_owned(), VoteResult::Failed);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!("{}    {}", status.0.bold().white(), "Failed".bright_red());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
 
         // N/A
         let status = ("pacman-mirrorup".to_owned(), VoteResult::NotAvailable);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!("{}    {}", status.0.bold().white(), "N/A".bright_red());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Throttled
+        let status = (
+            "pacman-mirrorup".to_owned(),
+            VoteResult::Throttled("Please wait".to_owned()),
+        );
+        let result = fancy(&status, &Style::default()).unwrap();
+        let expect = format!(
+            "{}    {}",
+            status.0.bold().white(),
+            "Throttled: Please wait".bright_yellow()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Dry run
+        let result = fancy_dry_run("pacman-mirrorup", &Style::default());
+        let expect = format!(
+            "{}    {}",
+            "pacman-mirrorup".bold().white(),
+            "Would unvote".bright_yellow()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
     }
 }
@@ -1,21 +1,151 @@
-use anyhow::Result;
-use std::{fmt::Write, path::Path};
+use anyhow::{anyhow, Result};
+use dialoguer::Confirm;
+use std::{collections::HashSet, path::Path};
+use tracing::{info, warn};
 
-use crate::{aur::Authentication, cmds::unvote::fancy, config::Configuration};
+use crate::{
+    args::OutputFormat,
+    aur::SessionOptions,
+    cmds::{
+        autovote::without_excluded,
+        unvote::{fancy, fancy_dry_run},
+        vote::{print_entries, VoteEntry},
+    },
+    config::Configuration,
+    error::{check_failures, check_stopped},
+    helper::{is_interactive, print_flush, PkgName},
+    state::{state_file_path, BatchState},
+    theme::Style,
+    timing,
+};
 
-pub fn unvote_all<P: AsRef<Path>>(config_path: P) -> Result<()> {
+/// Options of `unvote-all` from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct UnvoteAllOptions {
+    /// Resume an interrupted run, skipping packages already unvoted
+    pub resume: bool,
+
+    /// Only print packages to unvote, without unvoting or saving a run to resume
+    pub dry_run: bool,
+
+    /// Unvote without confirmation
+    pub yes: bool,
+}
+
+pub fn unvote_all<P: AsRef<Path>>(
+    config_path: P,
+    exclusions: &HashSet<PkgName>,
+    unvote_all_options: &UnvoteAllOptions,
+    options: &SessionOptions,
+    cache_dir: Option<&Path>,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let UnvoteAllOptions {
+        resume,
+        dry_run,
+        yes,
+    } = *unvote_all_options;
     let config = Configuration::load_and_verify_config(&config_path)?;
-    let mut auth = Authentication::new();
+    let state_path = state_file_path(&config.cache_dir(cache_dir)?, &config.account, "unvote-all");
+    let mut auth = config.authentication(options)?;
     auth.login(&config.account)?;
-    let voted_pkgs = auth.list_voted_pkgs()?;
-    let packages: Vec<String> = voted_pkgs.iter().map(|pkg| pkg.name.to_owned()).collect();
-    let results = auth.unvote(&packages)?;
+    timing::phase("login");
 
-    let mut output = String::new();
-    for result in results.iter() {
-        writeln!(output, "{}", fancy(result)?)?;
+    let saved_state = if resume {
+        let state = BatchState::load(&state_path)?;
+        if state.is_none() {
+            warn!("No interrupted unvote-all to resume, start a new one");
+        }
+        state
+    } else {
+        None
+    };
+
+    let resumed = saved_state.is_some();
+    let mut state = match saved_state {
+        Some(mut state) => {
+            // Packages excluded since the interrupted run keep their votes
+            state.packages = without_excluded(state.packages, exclusions);
+            info!(
+                "Resume unvote-all, {} of {} packages left",
+                state.remaining().len(),
+                state.packages.len()
+            );
+            state
+        }
+        None => {
+            let voted_pkgs = auth.list_voted_pkgs()?;
+            timing::phase("fetch voted list");
+            let packages = without_excluded(
+                voted_pkgs.iter().map(|pkg| pkg.name.to_owned()).collect(),
+                exclusions,
+            );
+            BatchState::new(packages)
+        }
+    };
+
+    let packages = state.remaining();
+    if dry_run {
+        for pkg in &packages {
+            print_flush(fancy_dry_run(pkg, style))?;
+        }
+        return Ok(());
+    }
+    if !yes && !packages.is_empty() && !confirm_unvote_all(packages.len(), format)? {
+        return Ok(());
+    }
+    // Saved once confirmed, a declined run is not resumed
+    if !resumed {
+        state.save(&state_path)?;
     }
-    print!("{}", output);
+    // Waiting for confirmation may outlast the session
+    auth.ensure_logged_in(&config.account)?;
+    let results = auth.unvote(&packages, |result| {
+        if format == OutputFormat::Human {
+            print_flush(fancy(result, style)?)?;
+        }
+        // Failed packages are left for the next `--resume`
+        if !result.1.is_failed() {
+            state.mark_done(&result.0);
+            state.save(&state_path)?;
+        }
+        Ok(())
+    })?;
+    timing::phase("unvote");
 
-    Ok(())
+    if state.is_completed() {
+        BatchState::clear(&state_path)?;
+    } else {
+        warn!(
+            "Some packages failed to unvote, retry them with `unvote-all --resume`: {}",
+            state.remaining().join(", ")
+        );
+    }
+
+    let status = check_stopped(auth.stopped(), &packages, results.len())
+        .and_then(|_| check_failures(&results));
+    print_entries(&VoteEntry::from_results(results), format)?;
+    status
+}
+
+/// Ask before unvoting `count` packages, fail if user cannot answer, i.e. not run
+/// from a terminal, or output is not human, e.g. JSON
+fn confirm_unvote_all(count: usize, format: OutputFormat) -> Result<bool> {
+    if !is_interactive() || format != OutputFormat::Human {
+        return Err(anyhow!(
+            "Refuse to unvote {} packages without confirmation, use `--yes` to unvote them.",
+            count
+        ));
+    }
+
+    print_flush(format!("This will remove votes from {} packages", count))?;
+    let confirmed = Confirm::new()
+        .with_prompt(format!("Unvote {} packages?", count))
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        print_flush("Unvote-all is not confirmed, no package is unvoted")?;
+    }
+    Ok(confirmed)
 }
@@ -0,0 +1,42 @@
+use anyhow::{anyhow, Result};
+use cookie::Expiration;
+use std::path::Path;
+use time::{format_description::well_known::Rfc2822, OffsetDateTime};
+
+use crate::aur::{is_cookie_expired, load_cookie_file};
+
+pub fn verify_session<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path_str = path.as_ref().display();
+    let cookie_jar = load_cookie_file(&path)?;
+
+    if cookie_jar.get("AURSID").is_none() {
+        return Err(anyhow!("No session (AURSID) found in `{}`.", path_str));
+    }
+
+    let aurtz = match cookie_jar.get("AURTZ") {
+        Some(aurtz) => aurtz,
+        None => {
+            println!("Session in `{}` has no expiration.", path_str);
+            return Ok(());
+        }
+    };
+
+    match aurtz.expires() {
+        Some(Expiration::DateTime(d)) => {
+            let expire_time = d.format(&Rfc2822)?;
+            if is_cookie_expired(aurtz, OffsetDateTime::now_utc()) {
+                return Err(anyhow!(
+                    "Session in `{}` was expired at {}.",
+                    path_str,
+                    expire_time
+                ));
+            }
+            println!("Session in `{}` is valid until {}.", path_str, expire_time);
+        }
+        Some(Expiration::Session) | None => {
+            println!("Session in `{}` expires at end of session.", path_str)
+        }
+    }
+
+    Ok(())
+}
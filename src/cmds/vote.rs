@@ -1,36 +1,202 @@
 use anyhow::{anyhow, Result};
-use colored::Colorize;
-use std::{fmt::Write, path::Path};
+use dialoguer::MultiSelect;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
+use tracing::warn;
 
 use crate::{
-    aur::{Authentication, VoteResult},
+    args::OutputFormat,
+    aur::{Authentication, SessionOptions, VoteResult},
+    cmds::autovote::list_installed_aur_pkgs,
     config::Configuration,
+    error::{check_failures, check_missing, check_stopped},
+    helper::{is_interactive, list_installed_pkgs, print_csv, print_flush, PkgName, PkgVersion},
+    theme::{Mark, Style},
+    timing,
 };
 
-pub fn vote<P: AsRef<Path>>(config_path: P, packages: Vec<String>) -> Result<()> {
+/// (Un)vote result of a package, for JSON and CSV output
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct VoteEntry {
+    pub package: String,
+
+    /// `None` if package is skipped
+    pub result: Option<VoteResult>,
+
+    /// Reason of skipping package
+    pub skipped: Option<String>,
+}
+
+impl VoteEntry {
+    /// Entries of (un)vote results
+    pub fn from_results(results: Vec<(String, VoteResult)>) -> Vec<VoteEntry> {
+        results
+            .into_iter()
+            .map(|(package, result)| VoteEntry {
+                package,
+                result: Some(result),
+                skipped: None,
+            })
+            .collect()
+    }
+
+    fn skipped(package: &str, reason: &str) -> VoteEntry {
+        VoteEntry {
+            package: package.to_owned(),
+            result: None,
+            skipped: Some(reason.to_owned()),
+        }
+    }
+}
+
+/// Print (un)vote results as JSON array or CSV, nothing for other formats
+pub fn print_entries(entries: &[VoteEntry], format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => print_flush(serde_json::to_string_pretty(entries)?),
+        OutputFormat::Csv => print_csv(entries),
+        _ => Ok(()),
+    }
+}
+
+/// Options of `vote` from command line
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct VoteOptions {
+    /// Vote only for installed packages, skip the others
+    pub if_installed: bool,
+
+    /// Pick packages to vote for from installed AUR packages
+    pub pick: bool,
+
+    /// Only print packages to vote for, without voting
+    pub dry_run: bool,
+
+    /// Exit with 0 even if some packages are not available on AUR
+    pub ignore_missing: bool,
+}
+
+/// Exit with `PARTIAL_EXIT_STATUS` if any package failed,
+/// or is not available on AUR unless `ignore_missing`
+pub fn vote<P: AsRef<Path>>(
+    config_path: P,
+    packages: Vec<String>,
+    vote_options: &VoteOptions,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let VoteOptions {
+        if_installed,
+        pick,
+        dry_run,
+        ignore_missing,
+    } = *vote_options;
+    // Session options apply to RPC queries of `--pick` too
+    let mut auth = Configuration::rpc_authentication(&config_path, options)?;
+    let packages = if pick { pick_pkgs(&auth)? } else { packages };
+    let (packages, not_installed) = if if_installed {
+        let installed_pkgs = list_installed_pkgs()?;
+        timing::phase("list installed packages");
+        split_installed(packages, &installed_pkgs)
+    } else {
+        (packages, Vec::new())
+    };
+    if format == OutputFormat::Human {
+        for pkg in &not_installed {
+            print_flush(fancy_skipped(pkg, style))?;
+        }
+    }
+
+    if dry_run {
+        for pkg in &packages {
+            print_flush(fancy_dry_run(pkg, style))?;
+        }
+        return Ok(());
+    }
+
     let config = Configuration::load_and_verify_config(&config_path)?;
-    let mut auth = Authentication::new();
     auth.login(&config.account)?;
-    let results = auth.vote(&packages)?;
+    timing::phase("login");
+    let results = match format {
+        OutputFormat::Human => auth.vote(&packages, |result| print_flush(fancy(result, style)?))?,
+        _ => auth.vote(&packages, |_| Ok(()))?,
+    };
+    timing::phase("vote");
+    let status = check_stopped(auth.stopped(), &packages, results.len())
+        .and_then(|_| check_failures(&results))
+        .and_then(|_| match ignore_missing {
+            true => Ok(()),
+            false => check_missing(&results),
+        });
+
+    let mut entries: Vec<VoteEntry> = not_installed
+        .iter()
+        .map(|pkg| VoteEntry::skipped(pkg, "not installed"))
+        .collect();
+    entries.extend(VoteEntry::from_results(results));
+    print_entries(&entries, format)?;
 
-    let mut output = String::new();
-    for result in results.iter() {
-        writeln!(output, "{}", fancy(result)?)?;
+    status
+}
+
+/// Let user pick installed AUR packages to vote for
+fn pick_pkgs(auth: &Authentication) -> Result<Vec<PkgName>> {
+    if !is_interactive() {
+        return Err(anyhow!("`--pick` requires a terminal"));
     }
-    print!("{}", output);
 
-    Ok(())
+    let candidates = list_installed_aur_pkgs(auth)?;
+    timing::phase("list installed packages");
+    if candidates.is_empty() {
+        warn!("No installed AUR package to pick");
+        return Ok(Vec::new());
+    }
+
+    let picked = MultiSelect::new()
+        .with_prompt("Pick packages to vote for (space to select, enter to confirm)")
+        .items(&candidates)
+        .interact()?;
+    Ok(picked
+        .into_iter()
+        .map(|i| candidates[i].to_owned())
+        .collect())
+}
+
+pub fn fancy_dry_run(pkg: &str, style: &Style) -> String {
+    format!("{}    {}", style.name(pkg), style.warn("Would vote"))
+}
+
+/// Split packages into installed and not installed packages
+fn split_installed(
+    packages: Vec<PkgName>,
+    installed_pkgs: &HashMap<PkgName, PkgVersion>,
+) -> (Vec<PkgName>, Vec<PkgName>) {
+    packages
+        .into_iter()
+        .partition(|pkg| installed_pkgs.contains_key(pkg))
+}
+
+fn fancy_skipped(pkg: &str, style: &Style) -> String {
+    format!(
+        "{}    {}",
+        style.name(pkg),
+        style.warn("Not installed, skipped")
+    )
 }
 
-pub fn fancy(status: &(String, VoteResult)) -> Result<String> {
+pub fn fancy(status: &(String, VoteResult), style: &Style) -> Result<String> {
     Ok(format!(
         "{}    {}",
-        status.0.bold().white(),
-        match status.1 {
-            VoteResult::AlreadyVoted => "Already voted".bright_green(),
-            VoteResult::Voted => "Voted".bright_green(),
-            VoteResult::Failed => "Failed".bright_red(),
-            VoteResult::NotAvailable => "N/A".bright_red(),
+        style.name(&status.0),
+        match &status.1 {
+            VoteResult::AlreadyVoted => style.good(style.mark("Already voted", Mark::Good)),
+            VoteResult::Voted => style.good(style.mark("Voted", Mark::Good)),
+            VoteResult::Failed => style.bad(style.mark("Failed", Mark::Bad)),
+            VoteResult::NotAvailable => style.bad(style.mark("N/A", Mark::Unknown)),
+            VoteResult::Throttled(advice) => style.warn(&format!(
+                "{}: {}",
+                style.mark("Throttled", Mark::Bad),
+                advice
+            )),
             _ => return Err(anyhow!("Incorrect vote status")),
         }
     ))
@@ -39,12 +205,63 @@ pub fn fancy(status: &(String, VoteResult)) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::{Symbols, Theme};
+    use colored::Colorize;
+
+    #[test]
+    fn test_split_installed() {
+        let installed_pkgs: HashMap<PkgName, PkgVersion> =
+            HashMap::from([("paru-bin".to_owned(), "1.9.3-1".to_owned())]);
+        let (installed, not_installed) = split_installed(
+            vec!["yay-bin".to_owned(), "paru-bin".to_owned()],
+            &installed_pkgs,
+        );
+        assert_eq!(installed, vec!["paru-bin"]);
+        assert_eq!(not_installed, vec!["yay-bin"]);
+    }
+
+    #[test]
+    fn test_vote_entry_json() {
+        let mut entries = vec![VoteEntry::skipped("yay-bin", "not installed")];
+        entries.extend(VoteEntry::from_results(vec![
+            ("paru-bin".to_owned(), VoteResult::AlreadyVoted),
+            (
+                "pacman-mirrorup".to_owned(),
+                VoteResult::Throttled("Wait".to_owned()),
+            ),
+        ]));
+        let json = serde_json::to_string(&entries).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"yay-bin","result":null,"skipped":"not installed"},{"package":"paru-bin","result":"already-voted","skipped":null},{"package":"pacman-mirrorup","result":"throttled","skipped":null}]"#
+        );
+
+        // Stable structure, without advice of throttled
+        let entries: Vec<VoteEntry> =
+            serde_json::from_str(&json.replace("throttled", "failed")).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                VoteEntry::skipped("yay-bin", "not installed"),
+                VoteEntry {
+                    package: "paru-bin".to_owned(),
+                    result: Some(VoteResult::AlreadyVoted),
+                    skipped: None,
+                },
+                VoteEntry {
+                    package: "pacman-mirrorup".to_owned(),
+                    result: Some(VoteResult::Failed),
+                    skipped: None,
+                },
+            ]
+        );
+    }
 
     #[test]
     fn test_fancy() {
         // Already voted
         let status = ("pacman-mirrorup".to_owned(), VoteResult::AlreadyVoted);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!(
             "{}    {}",
             status.0.bold().white(),
@@ -54,20 +271,39 @@ mod tests {
 
         // Voted
         let status = ("pacman-mirrorup".to_owned(), VoteResult::Voted);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!("{}    {}", status.0.bold().white(), "Voted".bright_green());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
 
         // Failed
         let status = ("pacman-mirrorup".to_owned(), VoteResult::Failed);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!("{}    {}", status.0.bold().white(), "Failed".bright_red());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
 
         // N/A
         let status = ("pacman-mirrorup".to_owned(), VoteResult::NotAvailable);
-        let result = fancy(&status).unwrap();
+        let result = fancy(&status, &Style::default()).unwrap();
         let expect = format!("{}    {}", status.0.bold().white(), "N/A".bright_red());
         assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Throttled
+        let status = (
+            "pacman-mirrorup".to_owned(),
+            VoteResult::Throttled("Please wait".to_owned()),
+        );
+        let result = fancy(&status, &Style::default()).unwrap();
+        let expect = format!(
+            "{}    {}",
+            status.0.bold().white(),
+            "Throttled: Please wait".bright_yellow()
+        );
+        assert_eq!(result, expect, "`{}` != `{}`", result, expect);
+
+        // Plain output, with ASCII marks and without color
+        let style = Style::new(Theme::Dark, Symbols::Unicode, true);
+        let status = ("pacman-mirrorup".to_owned(), VoteResult::Voted);
+        let result = fancy(&status, &style).unwrap();
+        assert_eq!(result, "pacman-mirrorup    +");
     }
 }
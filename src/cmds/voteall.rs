@@ -0,0 +1,61 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{
+    args::OutputFormat,
+    aur::SessionOptions,
+    cmds::{
+        autovote::without_ignored,
+        vote::{fancy, fancy_dry_run, print_entries, VoteEntry},
+    },
+    config::Configuration,
+    error::{check_failures, check_stopped},
+    helper::{list_foreign_pkgs, print_flush, PkgName},
+    theme::Style,
+    timing,
+};
+
+/// Vote for all installed AUR packages, except `ignore` of configuration.
+/// Unlike autovote, packages no longer installed are never unvoted.
+pub fn vote_all<P: AsRef<Path>>(
+    config_path: P,
+    dry_run: bool,
+    options: &SessionOptions,
+    format: OutputFormat,
+    style: &Style,
+) -> Result<()> {
+    let config = Configuration::load_and_verify_config(&config_path)?;
+    let mut auth = config.authentication(options)?;
+
+    // Foreign packages which are AUR packages
+    let installed: Vec<PkgName> = list_foreign_pkgs()?.into_keys().collect();
+    timing::phase("list installed packages");
+    let mut packages: Vec<PkgName> = auth
+        .info_query(&installed)?
+        .into_iter()
+        .map(|pkg| pkg.name)
+        .collect();
+    packages.sort();
+    timing::phase("query package info");
+    let packages = without_ignored(packages, config.ignore.as_deref().unwrap_or_default());
+
+    if dry_run {
+        for pkg in &packages {
+            print_flush(fancy_dry_run(pkg, style))?;
+        }
+        return Ok(());
+    }
+
+    auth.login(&config.account)?;
+    timing::phase("login");
+    let results = match format {
+        OutputFormat::Human => auth.vote(&packages, |result| print_flush(fancy(result, style)?))?,
+        _ => auth.vote(&packages, |_| Ok(()))?,
+    };
+    timing::phase("vote");
+    let status = check_stopped(auth.stopped(), &packages, results.len())
+        .and_then(|_| check_failures(&results));
+    print_entries(&VoteEntry::from_results(results), format)?;
+
+    status
+}
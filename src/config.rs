@@ -1,54 +1,509 @@
-use crate::aur::Account;
+use crate::args::{Commands, DEFAULT_COMMANDS};
+use crate::aur::{
+    Account, AuthMethod, Authentication, SessionOptions, DEFAULT_CONNECT_TIMEOUT,
+    DEFAULT_REQUEST_TIMEOUT, DEFAULT_RETRIES, DEFAULT_RETRY_DELAY_MS, PAGE_SIZES,
+};
 use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Display;
 use std::fs;
-use std::io::Write;
+use std::io::{ErrorKind, Write};
 use std::os::unix::fs::OpenOptionsExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
 
-use crate::helper::is_file_secure;
+use crate::error::AurError;
+use crate::helper::{self, is_file_secure};
+use crate::httpcache::HttpCache;
+use crate::progress;
+use crate::theme::Theme;
+use crate::votedcache::VotedCache;
+
+lazy_static! {
+    /// Overrides from `--set key=value`, applied to every loaded configuration
+    static ref OVERRIDES: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+}
+
+/// Set overrides from `--set key=value` for configuration loaded afterward
+pub fn set_overrides(overrides: &[(String, String)]) {
+    *OVERRIDES.lock().expect("Lock overrides") = overrides.to_vec();
+}
+
+lazy_static! {
+    /// Name of account in `accounts` from `--account`, used by every loaded configuration
+    static ref SELECTED_ACCOUNT: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Select account by name from `--account` for configuration loaded afterward
+pub fn set_account(name: Option<&str>) {
+    *SELECTED_ACCOUNT.lock().expect("Lock selected account") = name.map(str::to_owned);
+}
+
+/// Environment variable overriding `cookie_file` of selected account
+pub const COOKIE_FILE_ENV: &str = "AUR_THUMBSUP_COOKIE_FILE";
+
+/// Cookie file from value of `COOKIE_FILE_ENV`, if set and not empty
+fn cookie_file_override(value: Option<OsString>) -> Option<PathBuf> {
+    value.filter(|path| !path.is_empty()).map(PathBuf::from)
+}
 
 #[derive(Default, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Configuration {
+    /// Number of packages per page when listing voted packages: 50, 100, or 250
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+
+    /// Number of packages to vote, unvote, or check at once, up to 8, default: 4
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrent_votes: Option<usize>,
+
+    /// Number of packages to vote at once in autovote, before pausing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autovote_batch_size: Option<usize>,
+
+    /// Pause in seconds between batches of votes in autovote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autovote_batch_pause: Option<u64>,
+
+    /// Do not vote for packages installed in a newer version than AUR version, as `--skip-newer`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autovote_skip_newer: Option<bool>,
+
+    /// Packages autovote never votes for nor unvotes, e.g. test builds of own packages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<String>>,
+
+    /// Sub-command to run when none is given: autovote, list, or unvote-all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_command: Option<String>,
+
+    /// Minimum TLS version of connections to AUR: 1.2 or 1.3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_tls_version: Option<String>,
+
+    /// Base URL of AUR, e.g. of a self-hosted aurweb instance, default: https://aur.archlinux.org
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aur_url: Option<String>,
+
+    /// URL of proxy of connections to AUR, e.g. `http://proxy.example.org:3128`,
+    /// default: from `HTTP_PROXY`, `HTTPS_PROXY`, or `ALL_PROXY` environment variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// Seconds to wait for connection to AUR, default: 10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds to wait for a whole request to AUR, default: 30
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout: Option<u64>,
+
+    /// Directory of state files and caches, default: `$XDG_CACHE_HOME/aur-thumbsup`.
+    /// `~` and environment variables are expanded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Accept configuration file readable by other users, i.e. not 0600, with a warning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_insecure_config: Option<bool>,
+
+    /// Number of requests to AUR per run, after which (un)vote/check of next package is not started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<usize>,
+
+    /// Number of retries of a request to AUR on connection errors and 5xx status, default: 3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_retries: Option<u32>,
+
+    /// Milliseconds before the first retry, doubled before each next one, default: 500
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_delay_ms: Option<u64>,
+
+    /// Milliseconds between (un)vote/check of packages and pages of voted list, default: 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_delay_ms: Option<u64>,
+
+    /// Colors of human output for terminal background: auto, dark, or light
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+
+    /// Show results of vote, unvote, and check as symbols, e.g. ✓ instead of Voted, default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<bool>,
+
+    /// List voted packages by fetching every page of AUR packages, instead of stopping
+    /// at the first not voted package, default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_scan: Option<bool>,
+
+    /// Login with `remember_me` for a long-lived session saved to cookie file, default: true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remember_me: Option<bool>,
+
+    /// Remove cookie file when AUR does not accept its session, default: true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prune_invalid_cookie: Option<bool>,
+
+    /// Cache voted list and package pages in cache directory, and revalidate them
+    /// using ETag/Last-Modified on next runs, default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_cache: Option<bool>,
+
+    /// Seconds to use voted packages saved in cache directory by `list` and `autovote`,
+    /// instead of fetching them again. Not cached by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+
+    /// Language of AUR pages sent as AURLANG cookie, default: en.
+    /// Pages are scraped for English texts, regardless of language of account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Seconds to wait for `pacman`, e.g. while its database is locked, default: 30
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pacman_timeout: Option<u64>,
+
+    /// Account used when `--account` is not given. Optional if `accounts` is set.
+    #[serde(default)]
     pub account: Account,
+
+    /// Named accounts selected by `--account <name>`, each with its own cookie file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<BTreeMap<String, Account>>,
 }
 
 impl Configuration {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Configuration> {
+        Self::read_file(path).map_err(AurError::config)
+    }
+
+    fn read_file<P: AsRef<Path>>(path: P) -> Result<Configuration> {
         let config_content = match fs::read_to_string(&path) {
             Ok(c) => c,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Err(not_found_error(&path)),
             Err(err) => return Err(anyhow!("{} `{}`", err, &path.as_ref().to_str().unwrap())),
         };
 
-        let config: Configuration = toml::from_str(config_content.as_str())?;
+        let mut config: Configuration = match toml::from_str(config_content.as_str()) {
+            Ok(c) => c,
+            Err(err) => return Err(anyhow!("{} in `{}`", err, &path.as_ref().to_str().unwrap())),
+        };
+        let selected = SELECTED_ACCOUNT
+            .lock()
+            .expect("Lock selected account")
+            .clone();
+        config.select_account(selected.as_deref())?;
+        // Command line overrides environment
+        if let Some(cookie_file) = cookie_file_override(env::var_os(COOKIE_FILE_ENV)) {
+            config.account.cookie_file = cookie_file;
+        }
+        for (key, value) in OVERRIDES.lock().expect("Lock overrides").iter() {
+            config.set(key, value)?;
+        }
+
+        // Expand `~` and environment variables in paths
+        config.account.cookie_file = helper::expand_path(&config.account.cookie_file)?;
+        if let Some(cache_dir) = &config.cache_dir {
+            config.cache_dir = Some(helper::expand_path(cache_dir)?);
+        }
         Ok(config)
     }
 
+    /// Use account `name` of `accounts` as `account`. Without `name`, `account` is kept,
+    /// or the only one of `accounts` is used if `account` is not set.
+    fn select_account(&mut self, name: Option<&str>) -> Result<()> {
+        let names = || {
+            self.accounts
+                .iter()
+                .flat_map(|accounts| accounts.keys().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match name {
+            Some(name) => match self.accounts.as_ref().and_then(|a| a.get(name)) {
+                Some(account) => self.account = account.clone(),
+                None if self.accounts.is_none() => {
+                    return Err(anyhow!(
+                        "Account `{}` does not exist, no `accounts` in configuration.",
+                        name
+                    ))
+                }
+                None => {
+                    return Err(anyhow!(
+                        "Account `{}` does not exist, choose one of: {}.",
+                        name,
+                        names()
+                    ))
+                }
+            },
+            None if self.account == Account::default() => match &self.accounts {
+                Some(accounts) if accounts.len() == 1 => {
+                    self.account = accounts.values().next().unwrap().clone();
+                }
+                Some(accounts) if !accounts.is_empty() => {
+                    return Err(anyhow!(
+                        "Select an account using `--account <name>`, one of: {}.",
+                        names()
+                    ))
+                }
+                _ => {}
+            },
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Set value of `key` from command line, e.g. `page_size` or `account.cookie_file`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "page_size" => self.page_size = Some(parse_value(key, value)?),
+            "concurrent_votes" => self.concurrent_votes = Some(parse_value(key, value)?),
+            "autovote_batch_size" => self.autovote_batch_size = Some(parse_value(key, value)?),
+            "autovote_batch_pause" => self.autovote_batch_pause = Some(parse_value(key, value)?),
+            "autovote_skip_newer" => self.autovote_skip_newer = Some(parse_value(key, value)?),
+            "ignore" => {
+                self.ignore = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|pkg| !pkg.is_empty())
+                        .map(str::to_owned)
+                        .collect(),
+                )
+            }
+            "default_command" => self.default_command = Some(value.to_owned()),
+            "min_tls_version" => self.min_tls_version = Some(value.to_owned()),
+            "aur_url" => self.aur_url = Some(value.to_owned()),
+            "proxy" => self.proxy = Some(value.to_owned()),
+            "connect_timeout" => self.connect_timeout = Some(parse_value(key, value)?),
+            "request_timeout" => self.request_timeout = Some(parse_value(key, value)?),
+            "cache_dir" => self.cache_dir = Some(PathBuf::from(value)),
+            "allow_insecure_config" => self.allow_insecure_config = Some(parse_value(key, value)?),
+            "max_requests" => self.max_requests = Some(parse_value(key, value)?),
+            "network_retries" => self.network_retries = Some(parse_value(key, value)?),
+            "retry_delay_ms" => self.retry_delay_ms = Some(parse_value(key, value)?),
+            "request_delay_ms" => self.request_delay_ms = Some(parse_value(key, value)?),
+            "full_scan" => self.full_scan = Some(parse_value(key, value)?),
+            "remember_me" => self.remember_me = Some(parse_value(key, value)?),
+            "prune_invalid_cookie" => self.prune_invalid_cookie = Some(parse_value(key, value)?),
+            "http_cache" => self.http_cache = Some(parse_value(key, value)?),
+            "cache_ttl" => self.cache_ttl = Some(parse_value(key, value)?),
+            "language" => self.language = Some(value.to_owned()),
+            "symbols" => self.symbols = Some(parse_value(key, value)?),
+            "pacman_timeout" => self.pacman_timeout = Some(parse_value(key, value)?),
+            "theme" => {
+                self.theme = Some(
+                    toml::Value::String(value.to_owned())
+                        .try_into::<Theme>()
+                        .map_err(|err| {
+                            anyhow!("Invalid value `{}` of `{}`: {}", value, key, err)
+                        })?,
+                )
+            }
+            "account.user" => self.account.user = value.to_owned(),
+            "account.cookie_file" => self.account.cookie_file = PathBuf::from(value),
+            "account.cookie_login_retries" => {
+                self.account.cookie_login_retries = parse_value(key, value)?
+            }
+            "account.auth_method" => {
+                self.account.auth_method = toml::Value::String(value.to_owned())
+                    .try_into::<AuthMethod>()
+                    .map_err(|err| anyhow!("Invalid value `{}` of `{}`: {}", value, key, err))?
+            }
+            _ => return Err(anyhow!("Unknown configuration key `{}`", key)),
+        }
+        Ok(())
+    }
+
     pub fn load_and_verify_config<P: AsRef<Path>>(path: P) -> Result<Configuration> {
-        if !is_file_secure(&path)? {
-            return Err(anyhow!(
-                "`{}` file is not secure.",
-                &path.as_ref().to_str().unwrap()
-            ));
+        Self::verify_file(path).map_err(AurError::config)
+    }
+
+    fn verify_file<P: AsRef<Path>>(path: P) -> Result<Configuration> {
+        if let Err(err) = fs::metadata(&path) {
+            if err.kind() == ErrorKind::NotFound {
+                return Err(not_found_error(&path));
+            }
         }
 
         let config = Configuration::from_file(&path)?;
 
+        if !is_file_secure(&path)? {
+            let path = path.as_ref().to_str().unwrap();
+            if config.allow_insecure_config != Some(true) {
+                return Err(anyhow!(
+                    "`{}` file is not secure. Set its permission to 0600, or set `allow_insecure_config = true` to accept it.",
+                    path
+                ));
+            }
+            warn!(
+                "`{}` file is not secure, its password may be read by other users. Accepted by `allow_insecure_config`.",
+                path
+            );
+        }
+
         if config.account.user.is_empty() {
             return Err(anyhow!("User name is required."));
         }
 
-        if config.account.pass.is_empty() {
-            return Err(anyhow!("Password is required."));
+        if !config.account.pass.is_empty() && config.account.pass_cmd.is_some() {
+            return Err(anyhow!("Either pass or pass_cmd is allowed, not both."));
+        }
+        if config
+            .account
+            .pass_cmd
+            .as_deref()
+            .is_some_and(|cmd| cmd.trim().is_empty())
+        {
+            return Err(anyhow!("pass_cmd must not be empty."));
+        }
+
+        // Login using cookie file alone is possible, e.g. session imported by `import-session`
+        if !config.account.has_pass() && config.account.auth_method == AuthMethod::Password {
+            return Err(anyhow!(
+                "Password is required, set either pass or pass_cmd."
+            ));
         }
 
         if config.account.cookie_file.as_os_str().is_empty() {
             return Err(anyhow!("Cookie file path is required."));
         }
 
+        if let Some(page_size) = config.page_size {
+            if !PAGE_SIZES.contains(&page_size) {
+                return Err(anyhow!("Page size must be one of {:?}.", PAGE_SIZES));
+            }
+        }
+
+        config.default_command()?;
+
+        let mut auth = Authentication::new();
+        if let Some(version) = &config.min_tls_version {
+            auth.set_min_tls_version(version)?;
+        }
+        if let Some(proxy) = &config.proxy {
+            auth.set_proxy(proxy)?;
+        }
+        auth.set_timeouts(
+            config.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            config.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+        )?;
+
+        if config.autovote_batch_size == Some(0) {
+            return Err(anyhow!("Autovote batch size must be greater than 0."));
+        }
+
         Ok(config)
     }
 
+    /// Cache directory from command line, or from configuration if not given
+    pub fn cache_dir(&self, cli_cache_dir: Option<&Path>) -> Result<PathBuf> {
+        helper::cache_dir(cli_cache_dir.or(self.cache_dir.as_deref()))
+    }
+
+    /// Sub-command with default arguments from `default_command`
+    pub fn default_command(&self) -> Result<Option<Commands>> {
+        match &self.default_command {
+            Some(name) => match Commands::from_default_command(name) {
+                Some(cmd) => Ok(Some(cmd)),
+                None => Err(anyhow!(
+                    "Default command must be one of {:?}, got `{}`.",
+                    DEFAULT_COMMANDS,
+                    name
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// New `Authentication` (not logged in yet) using options from command line,
+    /// or from configuration if not given
+    pub fn authentication(&self, options: &SessionOptions) -> Result<Authentication> {
+        let mut auth = Authentication::new();
+        if let Some(page_size) = options.page_size.or(self.page_size) {
+            auth.set_page_size(page_size)?;
+        }
+        if let Some(concurrent_pages) = options.concurrent_pages {
+            auth.set_concurrent_pages(concurrent_pages)?;
+        }
+        if let Some(concurrent_votes) = options.concurrent_votes.or(self.concurrent_votes) {
+            auth.set_concurrent_votes(concurrent_votes)?;
+        }
+        if let Some(version) = &self.min_tls_version {
+            auth.set_min_tls_version(version)?;
+        }
+        if let Some(url) = &self.aur_url {
+            auth.set_aur_url(url)?;
+        }
+        if let Some(proxy) = &self.proxy {
+            auth.set_proxy(proxy)?;
+        }
+        auth.set_timeouts(
+            self.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            self.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+        )?;
+        auth.set_login_cache(!options.no_login_cache);
+        auth.set_verify_login(options.verify_login);
+        if options.progress {
+            auth.set_progress(progress::show);
+        }
+        if let Some(deadline) = options.deadline {
+            auth.set_deadline(Duration::from_secs(deadline));
+        }
+        auth.set_full_scan(self.full_scan.unwrap_or(false));
+        auth.set_remember_me(self.remember_me.unwrap_or(true));
+        auth.set_prune_invalid_cookie(self.prune_invalid_cookie.unwrap_or(true));
+        if self.http_cache == Some(true) {
+            // Pages differ by account
+            let dir = self.cache_dir(None)?.join("http").join(&self.account.user);
+            auth.set_http_cache(HttpCache::new(dir)?);
+        }
+        if let Some(ttl) = self.cache_ttl.filter(|ttl| *ttl > 0) {
+            auth.set_voted_cache(VotedCache::new(
+                &self.cache_dir(None)?,
+                &self.account,
+                Duration::from_secs(ttl),
+                options.refresh,
+            ));
+        }
+        if let Some(language) = &self.language {
+            auth.set_language(language);
+        }
+        if let Some(max_requests) = self.max_requests {
+            auth.set_max_requests(max_requests);
+        }
+        if self.network_retries.is_some() || self.retry_delay_ms.is_some() {
+            auth.set_retry(
+                self.network_retries.unwrap_or(DEFAULT_RETRIES),
+                Duration::from_millis(self.retry_delay_ms.unwrap_or(DEFAULT_RETRY_DELAY_MS)),
+            );
+        }
+        if let Some(delay) = options.request_delay_ms.or(self.request_delay_ms) {
+            auth.set_request_delay(Duration::from_millis(delay));
+        }
+        Ok(auth)
+    }
+
+    /// `Authentication` for RPC queries of commands working without configuration, e.g. `info`.
+    /// Options of configuration file apply only if it can be loaded.
+    pub fn rpc_authentication<P: AsRef<Path>>(
+        path: P,
+        options: &SessionOptions,
+    ) -> Result<Authentication> {
+        match Self::from_file(path) {
+            Ok(config) => config.authentication(options),
+            Err(_) => Ok(Authentication::new()),
+        }
+    }
+
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         if path.as_ref().exists() {
             return Err(anyhow!("`{}` is exist.", path.as_ref().to_str().unwrap()));
@@ -59,6 +514,7 @@ impl Configuration {
         let mut config_file = fs::OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .mode(0o600)
             .open(path)?;
         config_file.write_all(toml.as_bytes())?;
@@ -67,10 +523,28 @@ impl Configuration {
     }
 }
 
+fn parse_value<T>(key: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|err| anyhow!("Invalid value `{}` of `{}`: {}", value, key, err))
+}
+
+fn not_found_error<P: AsRef<Path>>(path: P) -> anyhow::Error {
+    let path = path.as_ref().to_str().unwrap();
+    anyhow!(
+        "Configuration file `{}` does not exist. Create it using `aur-thumbsup create-config {}`.",
+        path,
+        path
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_load_configuration() {
@@ -83,29 +557,311 @@ mod tests {
 
         assert_eq!(
             Configuration {
+                page_size: None,
                 account: Account {
                     user: "foo".to_owned(),
                     pass: "bar".to_owned(),
-                    cookie_file: PathBuf::from(r"/var/tmp/aur-thumbsup-foo.cookie")
-                }
+                    pass_cmd: None,
+                    cookie_file: PathBuf::from(r"/var/tmp/aur-thumbsup-foo.cookie"),
+                    cookie_login_retries: 2,
+                    auth_method: AuthMethod::Cookie,
+                },
+                ..Default::default()
             },
             config
         );
     }
 
+    #[test]
+    fn test_config_not_found() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let file_path = tempdir.path().join("aur-thumbsup.toml");
+        let expect = format!(
+            "Configuration file `{0}` does not exist. Create it using `aur-thumbsup create-config {0}`.",
+            file_path.to_str().unwrap()
+        );
+
+        let err = Configuration::load_and_verify_config(&file_path).unwrap_err();
+        assert_eq!(err.to_string(), expect);
+        let err = Configuration::from_file(&file_path).unwrap_err();
+        assert_eq!(err.to_string(), expect);
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn test_insecure_config() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let file_path = tempdir.path().join("aur-thumbsup.toml");
+        let content = r#"
+[account]
+user = "foo"
+pass = "bar"
+cookie_file = "/var/tmp/aur-thumbsup-foo.cookie"
+"#;
+        fs::write(&file_path, content).unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        // Enforced by default
+        let err = Configuration::load_and_verify_config(&file_path).unwrap_err();
+        assert!(err.to_string().contains("is not secure"), "{}", err);
+
+        // Opted out
+        fs::write(
+            &file_path,
+            format!("allow_insecure_config = true\n{}", content),
+        )
+        .unwrap();
+        let config = Configuration::load_and_verify_config(&file_path).unwrap();
+        assert_eq!(config.allow_insecure_config, Some(true));
+
+        // Explicitly strict
+        fs::write(
+            &file_path,
+            format!("allow_insecure_config = false\n{}", content),
+        )
+        .unwrap();
+        assert!(Configuration::load_and_verify_config(&file_path).is_err());
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn test_pass_cmd() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let file_path = tempdir.path().join("aur-thumbsup.toml");
+        let write_config = |account: &str| {
+            let content = format!(
+                "[account]\nuser = \"foo\"\ncookie_file = \"/var/tmp/aur-thumbsup-foo.cookie\"\nauth_method = \"password\"\n{}",
+                account
+            );
+            fs::write(&file_path, content).unwrap();
+            fs::set_permissions(&file_path, fs::Permissions::from_mode(0o600)).unwrap();
+        };
+
+        write_config("pass_cmd = \"pass show aur\"\n");
+        let config = Configuration::load_and_verify_config(&file_path).unwrap();
+        assert_eq!(config.account.pass, "");
+        assert_eq!(config.account.pass_cmd.as_deref(), Some("pass show aur"));
+
+        // Both
+        write_config("pass = \"bar\"\npass_cmd = \"pass show aur\"\n");
+        let err = Configuration::load_and_verify_config(&file_path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Either pass or pass_cmd is allowed, not both."
+        );
+
+        // Neither
+        write_config("");
+        let err = Configuration::load_and_verify_config(&file_path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Password is required, set either pass or pass_cmd."
+        );
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn test_unknown_config_key() {
+        let err = toml::from_str::<Configuration>(
+            r#"
+[account]
+usr = "foo"
+pass = "bar"
+cookie_file = "/var/tmp/aur-thumbsup-foo.cookie"
+"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `usr`"), "{}", err);
+
+        let err = toml::from_str::<Configuration>(
+            r#"
+pagesize = 100
+
+[account]
+user = "foo"
+pass = "bar"
+cookie_file = "/var/tmp/aur-thumbsup-foo.cookie"
+"#,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown field `pagesize`"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_auth_method() {
+        let config = toml::from_str::<Configuration>(
+            r#"
+[account]
+user = "foo"
+pass = "bar"
+cookie_file = "/var/tmp/aur-thumbsup-foo.cookie"
+auth_method = "password"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.account.auth_method, AuthMethod::Password);
+
+        let err = toml::from_str::<Configuration>(
+            r#"
+[account]
+user = "foo"
+pass = "bar"
+cookie_file = "/var/tmp/aur-thumbsup-foo.cookie"
+auth_method = "token"
+"#,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown variant `token`"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_select_account() {
+        let content = r#"
+[accounts.personal]
+user = "foo"
+cookie_file = "/var/tmp/aur-thumbsup-foo.cookie"
+
+[accounts.work]
+user = "baz"
+cookie_file = "/var/tmp/aur-thumbsup-baz.cookie"
+"#;
+        let mut config = toml::from_str::<Configuration>(content).unwrap();
+        config.select_account(Some("work")).unwrap();
+        assert_eq!(config.account.user, "baz");
+        assert_eq!(
+            config.account.cookie_file,
+            PathBuf::from("/var/tmp/aur-thumbsup-baz.cookie")
+        );
+
+        let err = config.select_account(Some("other")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Account `other` does not exist, choose one of: personal, work."
+        );
+
+        // Ambiguous without `--account`
+        let mut config = toml::from_str::<Configuration>(content).unwrap();
+        let err = config.select_account(None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Select an account using `--account <name>`, one of: personal, work."
+        );
+
+        // `account` is kept without `--account`
+        let content = format!(
+            "[account]\nuser = \"bar\"\ncookie_file = \"/var/tmp/bar.cookie\"\n{}",
+            content
+        );
+        let mut config = toml::from_str::<Configuration>(&content).unwrap();
+        config.select_account(None).unwrap();
+        assert_eq!(config.account.user, "bar");
+        config.select_account(Some("personal")).unwrap();
+        assert_eq!(config.account.user, "foo");
+
+        let mut config = Configuration::default();
+        let err = config.select_account(Some("work")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Account `work` does not exist, no `accounts` in configuration."
+        );
+    }
+
+    #[test]
+    fn test_cookie_file_override() {
+        assert_eq!(
+            cookie_file_override(Some(OsString::from("~/aur.cookie"))),
+            Some(PathBuf::from("~/aur.cookie"))
+        );
+        assert_eq!(cookie_file_override(Some(OsString::new())), None);
+        assert_eq!(cookie_file_override(None), None);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut config = Configuration::default();
+        config.set("page_size", "100").unwrap();
+        config.set("cache_dir", "/tmp/aur-thumbsup").unwrap();
+        config.set("account.cookie_login_retries", "5").unwrap();
+        config.set("account.auth_method", "password").unwrap();
+        config.set("theme", "light").unwrap();
+        config.set("ignore", "foo-git, bar-test").unwrap();
+        assert_eq!(config.page_size, Some(100));
+        assert_eq!(config.cache_dir, Some(PathBuf::from("/tmp/aur-thumbsup")));
+        assert_eq!(config.account.cookie_login_retries, 5);
+        assert_eq!(config.account.auth_method, AuthMethod::Password);
+        assert_eq!(config.theme, Some(Theme::Light));
+        assert_eq!(
+            config.ignore,
+            Some(vec!["foo-git".to_owned(), "bar-test".to_owned()])
+        );
+
+        let err = config.set("page_size", "many").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Invalid value `many` of `page_size`"));
+        assert!(config.set("account.auth_method", "token").is_err());
+        assert!(config.set("theme", "solarized").is_err());
+        let err = config.set("read_timeout", "5").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown configuration key `read_timeout`");
+        assert!(config.set("account.pass", "secret").is_err());
+    }
+
     #[test]
     fn test_configuration_to_file() {
         let tempdir = tempfile::tempdir().unwrap();
         let file_path = tempdir.path().join("aur-thumbsup-foo.toml");
         let config = Configuration {
+            page_size: Some(100),
+            concurrent_votes: Some(6),
+            autovote_batch_size: Some(20),
+            autovote_batch_pause: Some(30),
+            autovote_skip_newer: Some(true),
+            ignore: Some(vec!["foo-git".to_owned()]),
+            default_command: Some("autovote".to_owned()),
+            min_tls_version: Some("1.3".to_owned()),
+            aur_url: Some("https://aur.example.org".to_owned()),
+            proxy: Some("http://proxy.example.org:3128".to_owned()),
+            connect_timeout: Some(5),
+            request_timeout: Some(60),
+            cache_dir: Some(PathBuf::from("/var/cache/aur-thumbsup")),
+            allow_insecure_config: Some(false),
+            max_requests: Some(500),
+            network_retries: Some(5),
+            retry_delay_ms: Some(250),
+            request_delay_ms: Some(1000),
+            theme: Some(Theme::Light),
+            symbols: Some(true),
+            full_scan: Some(true),
+            remember_me: Some(false),
+            prune_invalid_cookie: Some(false),
+            http_cache: Some(true),
+            cache_ttl: Some(3600),
+            language: Some("en".to_owned()),
+            pacman_timeout: Some(60),
             account: Account {
                 user: "foo".to_owned(),
                 pass: "bar".to_owned(),
                 cookie_file: PathBuf::from(r"/var/tmp/aur-thumbsup-foo.cookie"),
+                auth_method: AuthMethod::Password,
+                ..Default::default()
             },
+            accounts: None,
         };
-        let result = config.to_file(file_path);
+        let result = config.to_file(&file_path);
         assert!(result.is_ok());
+        assert_eq!(Configuration::from_file(&file_path).unwrap(), config);
         tempdir.close().unwrap();
     }
 }
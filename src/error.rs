@@ -0,0 +1,267 @@
+use std::fmt;
+use tracing::warn;
+
+use crate::aur::VoteResult;
+
+/// Exit status when `--deadline` is reached before all packages are processed
+pub const DEADLINE_EXIT_STATUS: i32 = 4;
+
+/// Exit status when login fails, e.g. wrong user, pass, suspended account, or not logged in
+pub const AUTH_EXIT_STATUS: i32 = 5;
+
+/// Exit status when AUR cannot be reached
+pub const NETWORK_EXIT_STATUS: i32 = 6;
+
+/// Exit status when configuration file is missing, insecure, or invalid
+pub const CONFIG_EXIT_STATUS: i32 = 7;
+
+/// Exit status when (un)vote of some packages failed, while the others succeeded
+pub const PARTIAL_EXIT_STATUS: i32 = 8;
+
+/// Exit status when `max_requests` is reached before all packages are processed
+pub const REQUEST_CAP_EXIT_STATUS: i32 = 9;
+
+/// Exit status when `ensure-voted`/`ensure-unvoted` changed vote of some packages
+pub const CHANGED_EXIT_STATUS: i32 = 10;
+
+/// Reason to stop starting (un)vote/check of next package
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stop {
+    /// `--deadline` is reached
+    Deadline,
+
+    /// `max_requests` is reached
+    RequestCap,
+}
+
+/// Terminate with a specific exit code, without reporting an error
+#[derive(PartialEq, Eq, Debug)]
+pub struct ExitStatus(pub i32);
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Exit with status {}", self.0)
+    }
+}
+
+impl std::error::Error for ExitStatus {}
+
+/// AUR refuses login because account is suspended/disabled, unlike a wrong user, pass
+#[derive(PartialEq, Eq, Debug)]
+pub struct AccountSuspended;
+
+impl fmt::Display for AccountSuspended {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Login failed: account is suspended.")
+    }
+}
+
+impl std::error::Error for AccountSuspended {}
+
+/// AUR returns a maintenance page, e.g. while it is read-only, so nothing is changed
+#[derive(PartialEq, Eq, Debug)]
+pub struct Maintenance;
+
+impl fmt::Display for Maintenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AUR is in maintenance mode, try later.")
+    }
+}
+
+impl std::error::Error for Maintenance {}
+
+/// Category of error, reported as exit status
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+    Auth,
+    Network,
+    Config,
+}
+
+impl ErrorKind {
+    pub fn exit_status(&self) -> i32 {
+        match self {
+            ErrorKind::Auth => AUTH_EXIT_STATUS,
+            ErrorKind::Network => NETWORK_EXIT_STATUS,
+            ErrorKind::Config => CONFIG_EXIT_STATUS,
+        }
+    }
+}
+
+/// Error tagged with its category, displayed as the wrapped error
+#[derive(Debug)]
+pub struct AurError {
+    pub kind: ErrorKind,
+    error: anyhow::Error,
+}
+
+impl AurError {
+    pub fn wrap<E: Into<anyhow::Error>>(kind: ErrorKind, error: E) -> anyhow::Error {
+        AurError {
+            kind,
+            error: error.into(),
+        }
+        .into()
+    }
+
+    pub fn auth<E: Into<anyhow::Error>>(error: E) -> anyhow::Error {
+        AurError::wrap(ErrorKind::Auth, error)
+    }
+
+    pub fn network<E: Into<anyhow::Error>>(error: E) -> anyhow::Error {
+        AurError::wrap(ErrorKind::Network, error)
+    }
+
+    pub fn config<E: Into<anyhow::Error>>(error: E) -> anyhow::Error {
+        AurError::wrap(ErrorKind::Config, error)
+    }
+}
+
+impl fmt::Display for AurError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for AurError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Exit status of error: `ExitStatus` as is, 5-7 by category of error, otherwise 1
+pub fn exit_status(err: &anyhow::Error) -> i32 {
+    if let Some(status) = err.downcast_ref::<ExitStatus>() {
+        return status.0;
+    }
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<AurError>() {
+            return err.kind.exit_status();
+        }
+        if cause.is::<AccountSuspended>() {
+            return AUTH_EXIT_STATUS;
+        }
+        if cause.is::<reqwest::Error>() || cause.is::<Maintenance>() {
+            return NETWORK_EXIT_STATUS;
+        }
+    }
+    1
+}
+
+/// Report packages left when `--deadline` or `max_requests` is reached,
+/// i.e. after the first `done` packages
+pub fn check_stopped(
+    stopped: Option<Stop>,
+    packages: &[String],
+    done: usize,
+) -> anyhow::Result<()> {
+    if done >= packages.len() {
+        return Ok(());
+    }
+    let (reason, status) = match stopped {
+        Some(Stop::RequestCap) => ("Request cap reached", REQUEST_CAP_EXIT_STATUS),
+        Some(Stop::Deadline) | None => ("Deadline reached", DEADLINE_EXIT_STATUS),
+    };
+    let remaining = &packages[done..];
+    warn!(
+        "{}, {} remaining: {}",
+        reason,
+        remaining.len(),
+        remaining.join(", ")
+    );
+    Err(ExitStatus(status).into())
+}
+
+/// Report packages failed to (un)vote, if any
+pub fn check_failures(results: &[(String, VoteResult)]) -> anyhow::Result<()> {
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|(_, result)| result.is_failed())
+        .map(|(pkg, _)| pkg.as_str())
+        .collect();
+    if failed.is_empty() {
+        return Ok(());
+    }
+    warn!("{} packages failed: {}", failed.len(), failed.join(", "));
+    Err(ExitStatus(PARTIAL_EXIT_STATUS).into())
+}
+
+/// Report packages not available on AUR, if any, as failed
+pub fn check_missing(results: &[(String, VoteResult)]) -> anyhow::Result<()> {
+    let missing: Vec<&str> = results
+        .iter()
+        .filter(|(_, result)| *result == VoteResult::NotAvailable)
+        .map(|(pkg, _)| pkg.as_str())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    warn!(
+        "{} packages are not available on AUR: {}",
+        missing.len(),
+        missing.join(", ")
+    );
+    Err(ExitStatus(PARTIAL_EXIT_STATUS).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_check_stopped() {
+        let packages = vec!["pkg1".to_owned(), "pkg2".to_owned()];
+        assert!(check_stopped(None, &packages, 2).is_ok());
+        let err = check_stopped(Some(Stop::Deadline), &packages, 1).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExitStatus>(),
+            Some(&ExitStatus(DEADLINE_EXIT_STATUS))
+        );
+        let err = check_stopped(Some(Stop::RequestCap), &packages, 1).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExitStatus>(),
+            Some(&ExitStatus(REQUEST_CAP_EXIT_STATUS))
+        );
+    }
+
+    #[test]
+    fn test_check_failures() {
+        let mut results = vec![("pkg1".to_owned(), VoteResult::Voted)];
+        assert!(check_failures(&results).is_ok());
+        results.push(("pkg2".to_owned(), VoteResult::Failed));
+        let err = check_failures(&results).unwrap_err();
+        assert_eq!(exit_status(&err), PARTIAL_EXIT_STATUS);
+    }
+
+    #[test]
+    fn test_check_missing() {
+        let mut results = vec![
+            ("pkg1".to_owned(), VoteResult::Voted),
+            ("pkg2".to_owned(), VoteResult::Failed),
+        ];
+        assert!(check_missing(&results).is_ok());
+        results.push(("pkg3".to_owned(), VoteResult::NotAvailable));
+        let err = check_missing(&results).unwrap_err();
+        assert_eq!(exit_status(&err), PARTIAL_EXIT_STATUS);
+    }
+
+    #[test]
+    fn test_exit_status() {
+        assert_eq!(exit_status(&ExitStatus(3).into()), 3);
+        assert_eq!(exit_status(&anyhow!("Other error")), 1);
+        assert_eq!(exit_status(&AccountSuspended.into()), AUTH_EXIT_STATUS);
+        assert_eq!(exit_status(&Maintenance.into()), NETWORK_EXIT_STATUS);
+
+        let err = AurError::auth(anyhow!("Not logged in."));
+        assert_eq!(err.to_string(), "Not logged in.");
+        assert_eq!(exit_status(&err), AUTH_EXIT_STATUS);
+
+        // Category is kept when context is added
+        let err = AurError::network(anyhow!("Unable to access AUR")).context("Unable to login");
+        assert_eq!(exit_status(&err), NETWORK_EXIT_STATUS);
+
+        let err = AurError::config(anyhow!("User name is required."));
+        assert_eq!(exit_status(&err), CONFIG_EXIT_STATUS);
+    }
+}
@@ -1,16 +1,28 @@
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::ffi::OsStr;
-use std::fs::File;
-use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Display;
+use std::fs::{self, DirBuilder, File};
+use std::io::{self, IsTerminal, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::thread;
+use std::time::Duration;
+use wait_timeout::ChildExt;
+
+use crate::args::merge_packages;
+use crate::progress;
 
 pub type PkgName = String;
 pub type PkgVersion = String;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
 pub enum Versioning {
     Older,
     Same,
@@ -34,18 +46,353 @@ pub fn is_file_secure<P: AsRef<Path>>(path: P) -> Result<bool> {
     Ok(permissions.mode() & 0o666 == 0o600)
 }
 
+/// Cache directory for state files and caches, created with 0700 permission if missing.
+/// Use `override_dir` if given, otherwise `$XDG_CACHE_HOME/aur-thumbsup` or `~/.cache/aur-thumbsup`.
+pub fn cache_dir(override_dir: Option<&Path>) -> Result<PathBuf> {
+    let dir = cache_dir_path(override_dir)?;
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|err| {
+            anyhow!(
+                "Unable to create cache directory `{}`: {}",
+                dir.display(),
+                err
+            )
+        })?;
+    Ok(dir)
+}
+
+/// Path of cache directory as `cache_dir`, without creating it
+pub fn cache_dir_path(override_dir: Option<&Path>) -> Result<PathBuf> {
+    resolve_cache_dir(
+        override_dir,
+        env::var_os("XDG_CACHE_HOME"),
+        env::var_os("HOME"),
+    )
+}
+
+fn resolve_cache_dir(
+    override_dir: Option<&Path>,
+    xdg_cache_home: Option<OsString>,
+    home: Option<OsString>,
+) -> Result<PathBuf> {
+    if let Some(dir) = override_dir {
+        return Ok(dir.to_path_buf());
+    }
+
+    // Relative XDG_CACHE_HOME is invalid, see XDG Base Directory Specification
+    if let Some(xdg_cache_home) = xdg_cache_home.map(PathBuf::from) {
+        if xdg_cache_home.is_absolute() {
+            return Ok(xdg_cache_home.join(env!("CARGO_PKG_NAME")));
+        }
+    }
+
+    match home {
+        Some(home) => Ok(PathBuf::from(home)
+            .join(".cache")
+            .join(env!("CARGO_PKG_NAME"))),
+        None => Err(anyhow!(
+            "Unable to find cache directory, neither XDG_CACHE_HOME nor HOME is set"
+        )),
+    }
+}
+
+/// Parse human age, e.g. `30d`, `2w`, `6mo`, `1y`. A month is 30 days, a year is 365 days.
+pub fn parse_age(age: &str) -> Result<Duration> {
+    const DAY: u64 = 24 * 60 * 60;
+    let age = age.trim();
+    let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (number, unit) = age.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid age `{}`, e.g. 30d, 2w, 6mo, 1y", age))?;
+    let unit_secs = match unit {
+        "d" => DAY,
+        "w" => 7 * DAY,
+        "mo" => 30 * DAY,
+        "y" => 365 * DAY,
+        _ => {
+            return Err(anyhow!(
+                "Invalid unit of age `{}`, must be d, w, mo, or y",
+                age
+            ))
+        }
+    };
+    Ok(Duration::from_secs(number.saturating_mul(unit_secs)))
+}
+
+/// Print a line to stdout and flush it immediately
+pub fn print_flush<D: Display>(line: D) -> Result<()> {
+    if QUIET.load(AtomicOrdering::Relaxed) {
+        return Ok(());
+    }
+    progress::suspend(|| {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+        Ok(())
+    })
+}
+
+/// Print `text` as is, e.g. lines already ended by newline
+pub fn print_text<D: Display>(text: D) -> Result<()> {
+    if QUIET.load(AtomicOrdering::Relaxed) {
+        return Ok(());
+    }
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "{}", text)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Print nothing afterward by `print_flush`, `print_text`, and `print_csv`, e.g. for `--quiet`
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, AtomicOrdering::Relaxed);
+}
+
+/// Print records as CSV with a header of their field names, e.g. for `--format csv`
+pub fn print_csv<S: Serialize>(records: &[S]) -> Result<()> {
+    if QUIET.load(AtomicOrdering::Relaxed) {
+        return Ok(());
+    }
+    write_csv(io::stdout().lock(), records)
+}
+
+fn write_csv<W: Write, S: Serialize>(writer: W, records: &[S]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Whether stdin is a terminal, i.e. user can answer a confirmation
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Expand `~`, `$VAR`, and `${VAR}` in path, error if variable is not defined
+pub fn expand_path(path: &Path) -> Result<PathBuf> {
+    expand_path_with(
+        path,
+        || env::var_os("HOME").map(PathBuf::from),
+        |var| env::var(var).ok(),
+    )
+}
+
+fn expand_path_with<H, L>(path: &Path, home_dir: H, mut lookup: L) -> Result<PathBuf>
+where
+    H: FnOnce() -> Option<PathBuf>,
+    L: FnMut(&str) -> Option<String>,
+{
+    // Non UTF-8 path cannot contain variables to expand
+    let raw = match path.to_str() {
+        Some(raw) => raw,
+        None => return Ok(path.to_path_buf()),
+    };
+    let expanded = shellexpand::full_with_context(raw, home_dir, |var| match lookup(var) {
+        Some(value) => Ok(Some(value)),
+        None => Err("not defined"),
+    })
+    .map_err(|err| {
+        anyhow!(
+            "Unable to expand `{}`: `${}` is {}",
+            raw,
+            err.var_name,
+            err.cause
+        )
+    })?;
+    Ok(PathBuf::from(expanded.as_ref()))
+}
+
+/// Read package names from file, one per line. Blank lines and `#` comments are ignored.
+pub fn read_pkgs_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PkgName>> {
+    let content = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(err) => return Err(anyhow!("{} `{}`", err, &path.as_ref().to_str().unwrap())),
+    };
+    Ok(parse_pkg_list(&content))
+}
+
+/// Read package names from reader, e.g. stdin, one per line
+pub fn read_pkgs<R: Read>(mut reader: R) -> Result<Vec<PkgName>> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    Ok(parse_pkg_list(&content))
+}
+
+fn parse_pkg_list(content: &str) -> Vec<PkgName> {
+    content
+        .lines()
+        .map(|line| match line.find('#') {
+            Some(pos) => &line[..pos],
+            None => line,
+        })
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+/// Merge excluded packages from command line and exclude file
+pub fn load_exclusions<P: AsRef<Path>>(
+    exclude: &[PkgName],
+    exclude_file: Option<P>,
+) -> Result<HashSet<PkgName>> {
+    let mut exclusions: HashSet<PkgName> = exclude.iter().map(|pkg| pkg.to_owned()).collect();
+    if let Some(path) = exclude_file {
+        exclusions.extend(read_pkgs_from_file(path)?);
+    }
+    Ok(exclusions)
+}
+
+/// Merge packages from command line, `stdin` if `-` is the only positional package,
+/// and file of `--from-file`, in that order and without duplicates
+pub fn load_packages<P: AsRef<Path>, R: Read>(
+    packages: Vec<PkgName>,
+    package: Vec<PkgName>,
+    from_file: Option<P>,
+    stdin: R,
+) -> Result<Vec<PkgName>> {
+    let packages = match packages.as_slice() {
+        [pkg] if pkg == "-" => read_pkgs(stdin)?,
+        _ if packages.iter().any(|pkg| pkg == "-") => {
+            return Err(anyhow!(
+                "`-` reads packages from stdin, it must be the only positional package"
+            ))
+        }
+        _ => packages,
+    };
+    let mut pkgs = merge_packages(packages, package);
+    if let Some(path) = from_file {
+        pkgs = merge_packages(pkgs, read_pkgs_from_file(path)?);
+    }
+    Ok(pkgs)
+}
+
+/// External programs used to query pacman database, found in `PATH`
+pub const REQUIRED_PROGRAMS: [&str; 3] = ["pacman", "pacman-conf", "vercmp"];
+
+/// Programs of `programs` which are not found
+pub fn missing_programs<'a>(programs: &[&'a str]) -> Vec<&'a str> {
+    programs
+        .iter()
+        .filter(|program| find_program(program).is_none())
+        .copied()
+        .collect()
+}
+
+/// Executable file of `program` in `PATH`, or `program` itself if it is a path
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    let is_executable = |path: &Path| {
+        fs::metadata(path)
+            .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    };
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+/// Command of `program` found in `PATH`, with a clear error if it is missing, e.g. not on Arch Linux
+fn command(program: &str) -> Result<Command> {
+    match find_program(program) {
+        Some(path) => Ok(Command::new(path)),
+        None => Err(anyhow!(
+            "`{}` not found, aur-thumbsup requires an Arch-based system for this command",
+            program
+        )),
+    }
+}
+
+/// Default seconds to wait for `pacman`, e.g. while its database is locked by another process
+pub const DEFAULT_PACMAN_TIMEOUT: u64 = 30;
+
+static PACMAN_TIMEOUT: AtomicU64 = AtomicU64::new(DEFAULT_PACMAN_TIMEOUT);
+
+/// Set seconds to wait for `pacman` and `pacman-conf` before giving up
+pub fn set_pacman_timeout(secs: u64) {
+    PACMAN_TIMEOUT.store(secs, AtomicOrdering::Relaxed);
+}
+
+/// Read `stdout` while waiting for `program` to exit, kill it after `PACMAN_TIMEOUT`.
+/// `stdout` may be of the last process of a pipeline started by `child`.
+fn wait_pacman(child: &mut Child, stdout: ChildStdout, program: &str) -> Result<Vec<u8>> {
+    let timeout = Duration::from_secs(PACMAN_TIMEOUT.load(AtomicOrdering::Relaxed));
+    wait_with_timeout(child, stdout, program, timeout)
+}
+
+/// Read `stdout` while waiting for `child` to exit, kill it after `timeout`
+fn wait_with_timeout(
+    child: &mut Child,
+    mut stdout: ChildStdout,
+    program: &str,
+    timeout: Duration,
+) -> Result<Vec<u8>> {
+    // Drain output in background, or a full pipe blocks the child forever
+    let output = thread::spawn(move || -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output)?;
+        Ok(output)
+    });
+
+    let status = match child.wait_timeout(timeout)? {
+        Some(status) => status,
+        None => {
+            child.kill()?;
+            child.wait()?;
+            return Err(anyhow!(
+                "`{}` is busy, timed out after {:?} (database locked?)",
+                program,
+                timeout
+            ));
+        }
+    };
+    let output = output
+        .join()
+        .map_err(|_| anyhow!("Unable to read output of `{}`", program))??;
+    if !status.success() {
+        return Err(anyhow!("Error calling `{}`", program));
+    }
+    Ok(output)
+}
+
 /// List all installed packages on system
 pub fn list_installed_pkgs() -> Result<HashMap<PkgName, PkgVersion>> {
-    let packman_child = Command::new("/usr/bin/pacman")
-        .arg("-Q")
+    query_pkgs("-Q")
+}
+
+/// List explicitly installed packages on system, i.e. not installed as dependencies
+pub fn list_explicitly_installed_pkgs() -> Result<HashMap<PkgName, PkgVersion>> {
+    query_pkgs("-Qe")
+}
+
+/// List foreign packages on system, i.e. not found in any sync database, e.g. from AUR
+pub fn list_foreign_pkgs() -> Result<HashMap<PkgName, PkgVersion>> {
+    query_pkgs("-Qm")
+}
+
+/// List installed packages using `pacman` query operation
+fn query_pkgs(operation: &str) -> Result<HashMap<PkgName, PkgVersion>> {
+    let mut packman_child = command("pacman")?
+        .arg(operation)
         .stdout(Stdio::piped())
         .spawn()?;
 
-    let pacman_output = packman_child.wait_with_output()?;
-    let lines = String::from_utf8(pacman_output.stdout)?;
+    let stdout = packman_child.stdout.take().expect("Piped stdout");
+    let pacman_output = wait_pacman(&mut packman_child, stdout, "pacman")?;
+    let lines = String::from_utf8(pacman_output)?;
     let pkglist: HashMap<PkgName, PkgVersion> = lines
         .split('\n')
-        .into_iter()
         .filter(|line| !line.is_empty())
         .map(|line| {
             let cols: Vec<&str> = line.split(' ').collect();
@@ -55,16 +402,13 @@ pub fn list_installed_pkgs() -> Result<HashMap<PkgName, PkgVersion>> {
     Ok(pkglist)
 }
 
-/// Compare version using `/usr/bin/vercmp` from pacman
+/// Compare version using `vercmp` from pacman
 pub fn vercmp<L, R>(left: L, right: R) -> Result<Versioning>
 where
     L: AsRef<OsStr>,
     R: AsRef<OsStr>,
 {
-    let output = Command::new("/usr/bin/vercmp")
-        .arg(&left)
-        .arg(&right)
-        .output()?;
+    let output = command("vercmp")?.arg(&left).arg(&right).output()?;
 
     if !output.status.success() {
         return Err(anyhow!("Error calling `vercmp`"));
@@ -81,17 +425,16 @@ where
 
 /// List available repositories on system
 pub fn list_repos(select: SelectRepository) -> Result<Vec<String>> {
-    let output = Command::new("/usr/bin/pacman-conf")
+    let mut child = command("pacman-conf")?
         .arg("--repo-list")
-        .output()?;
-    if !output.status.success() {
-        return Err(anyhow!("Error calling `pacman-conf`"));
-    }
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("Piped stdout");
+    let output = wait_pacman(&mut child, stdout, "pacman-conf")?;
 
-    let lines = String::from_utf8(output.stdout)?;
+    let lines = String::from_utf8(output)?;
     let repolist: Vec<String> = lines
         .split('\n')
-        .into_iter()
         .filter(|repo| !repo.is_empty())
         .filter(|repo| match select {
             SelectRepository::Official => {
@@ -112,50 +455,82 @@ pub fn list_repos(select: SelectRepository) -> Result<Vec<String>> {
 
 /// List installed packages from a repository
 pub fn list_installed_pkgs_repo<S: AsRef<str>>(repo: S) -> Result<HashMap<PkgName, PkgVersion>> {
-    let mut packman_child = Command::new("/usr/bin/pacman")
+    // Untranslated `[installed]` marker
+    let mut packman_child = command("pacman")?
         .arg("-Sl")
         .arg(repo.as_ref())
+        .env("LC_ALL", "C")
         .stdout(Stdio::piped())
         .spawn()?;
 
-    if let Some(pacman_output) = packman_child.stdout.take() {
-        let mut grep_child = Command::new("/usr/bin/grep")
-            .arg("\\[installed\\]$")
-            .stdin(pacman_output)
-            .stdout(Stdio::piped())
-            .spawn()?;
-        if let Some(grep_output) = grep_child.stdout.take() {
-            let awk_child = Command::new("/usr/bin/awk")
-                .arg("{ print $2, $3 }")
-                .stdin(grep_output)
-                .stdout(Stdio::piped())
-                .spawn()?;
-            packman_child.wait()?;
-            grep_child.wait()?;
-            let awk_output = awk_child.wait_with_output()?;
-            let lines = String::from_utf8(awk_output.stdout)?;
-            let pkglist: Vec<&str> = lines.split('\n').collect();
-            let pkgs: HashMap<PkgName, PkgVersion> = pkglist
-                .iter()
-                .filter(|pkg| !pkg.is_empty())
-                .map(|pkg| {
-                    let pkg_info: Vec<&str> = pkg.split(' ').collect();
-                    (pkg_info[0].to_owned(), pkg_info[1].to_owned())
-                })
-                .collect();
-            return Ok(pkgs);
-        }
-    }
-    Err(anyhow!(
-        "Failed list installed package from {}",
-        repo.as_ref()
-    ))
+    let stdout = packman_child.stdout.take().expect("Piped stdout");
+    let pacman_output = wait_pacman(&mut packman_child, stdout, "pacman")?;
+    Ok(parse_installed_pkgs_repo(&String::from_utf8(
+        pacman_output,
+    )?))
+}
+
+/// Name and version of installed packages from output of `pacman -Sl`,
+/// i.e. lines of `repo name version [installed]`. Packages installed in another version,
+/// i.e. `[installed: version]`, are skipped.
+fn parse_installed_pkgs_repo(lines: &str) -> HashMap<PkgName, PkgVersion> {
+    lines
+        .lines()
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            match cols[..] {
+                [_, name, version, "[installed]"] => Some((name.to_owned(), version.to_owned())),
+                _ => None,
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_cache_dir() {
+        let home = Some(OsString::from("/home/foo"));
+        assert_eq!(
+            resolve_cache_dir(None, None, home.clone()).unwrap(),
+            PathBuf::from("/home/foo/.cache/aur-thumbsup")
+        );
+        assert_eq!(
+            resolve_cache_dir(None, Some(OsString::from("/var/cache/foo")), home.clone()).unwrap(),
+            PathBuf::from("/var/cache/foo/aur-thumbsup")
+        );
+        // Relative XDG_CACHE_HOME is ignored
+        assert_eq!(
+            resolve_cache_dir(None, Some(OsString::from("cache")), home.clone()).unwrap(),
+            PathBuf::from("/home/foo/.cache/aur-thumbsup")
+        );
+        assert_eq!(
+            resolve_cache_dir(
+                Some(Path::new("/tmp/aur-thumbsup")),
+                Some(OsString::from("/var/cache/foo")),
+                home
+            )
+            .unwrap(),
+            PathBuf::from("/tmp/aur-thumbsup")
+        );
+        assert!(resolve_cache_dir(None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_cache_dir() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path().join("nested").join("cache");
+        assert_eq!(cache_dir(Some(&dir)).unwrap(), dir);
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // Existing directory
+        assert!(cache_dir(Some(&dir)).is_ok());
+        tempdir.close().unwrap();
+    }
+
     #[test]
     fn test_is_file_secure() {
         let f1 = tempfile::Builder::new()
@@ -173,6 +548,148 @@ mod tests {
         assert!(is_secure);
     }
 
+    #[test]
+    fn test_write_csv() {
+        #[derive(Serialize)]
+        struct Record<'a> {
+            package: &'a str,
+            voted: Option<bool>,
+            relation: Option<Versioning>,
+        }
+        let records = vec![
+            Record {
+                package: "pacman-mirrorup",
+                voted: Some(true),
+                relation: Some(Versioning::Older),
+            },
+            Record {
+                package: "with,comma",
+                voted: None,
+                relation: None,
+            },
+        ];
+        let mut output: Vec<u8> = Vec::new();
+        write_csv(&mut output, &records).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "package,voted,relation\npacman-mirrorup,true,older\n\"with,comma\",,\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_age() {
+        let day = 24 * 60 * 60;
+        assert_eq!(parse_age("30d").unwrap(), Duration::from_secs(30 * day));
+        assert_eq!(parse_age("2w").unwrap(), Duration::from_secs(14 * day));
+        assert_eq!(parse_age("6mo").unwrap(), Duration::from_secs(180 * day));
+        assert_eq!(parse_age("1y").unwrap(), Duration::from_secs(365 * day));
+        assert!(parse_age("1").is_err());
+        assert!(parse_age("y").is_err());
+        assert!(parse_age("1m").is_err());
+        assert!(parse_age("-1y").is_err());
+    }
+
+    #[test]
+    fn test_parse_pkg_list() {
+        let content =
+            "# Keep these votes\npacman-mirrorup\n\n  networkd-broker  \nyay # AUR helper\n";
+        assert_eq!(
+            parse_pkg_list(content),
+            vec!["pacman-mirrorup", "networkd-broker", "yay"]
+        );
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let home = || Some(PathBuf::from("/home/foo"));
+        let lookup = |var: &str| match var {
+            "HOME" => Some("/home/foo".to_owned()),
+            "XDG_CACHE_HOME" => Some("/home/foo/.cache".to_owned()),
+            _ => None,
+        };
+
+        assert_eq!(
+            expand_path_with(Path::new("~/.cache/aur-thumbsup.cookie"), home, lookup).unwrap(),
+            PathBuf::from("/home/foo/.cache/aur-thumbsup.cookie")
+        );
+        assert_eq!(
+            expand_path_with(Path::new("$HOME/aur-thumbsup.cookie"), home, lookup).unwrap(),
+            PathBuf::from("/home/foo/aur-thumbsup.cookie")
+        );
+        assert_eq!(
+            expand_path_with(Path::new("${XDG_CACHE_HOME}/aur-thumbsup"), home, lookup).unwrap(),
+            PathBuf::from("/home/foo/.cache/aur-thumbsup")
+        );
+        assert_eq!(
+            expand_path_with(Path::new("/var/tmp/aur-thumbsup.cookie"), home, lookup).unwrap(),
+            PathBuf::from("/var/tmp/aur-thumbsup.cookie")
+        );
+
+        let err = expand_path_with(Path::new("$UNDEFINED/aur-thumbsup"), home, lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unable to expand `$UNDEFINED/aur-thumbsup`: `$UNDEFINED` is not defined"
+        );
+    }
+
+    #[test]
+    fn test_read_pkgs() {
+        let pkgs = read_pkgs("aur-thumbsup\nparu-bin\n".as_bytes()).unwrap();
+        assert_eq!(pkgs, vec!["aur-thumbsup", "paru-bin"]);
+    }
+
+    #[test]
+    fn test_load_packages() {
+        let no_file: Option<&Path> = None;
+        let pkgs = load_packages(
+            vec!["-".to_owned()],
+            vec!["yay".to_owned()],
+            no_file,
+            "aur-thumbsup\n# comment\nyay\n".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(pkgs, vec!["aur-thumbsup", "yay"]);
+
+        let mut from_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(from_file, "paru-bin\n\nyay").unwrap();
+        let pkgs = load_packages(
+            vec!["yay".to_owned()],
+            vec![],
+            Some(from_file.path()),
+            io::empty(),
+        )
+        .unwrap();
+        assert_eq!(pkgs, vec!["yay", "paru-bin"]);
+
+        // Stdin is not read with other positional packages
+        assert!(load_packages(
+            vec!["yay".to_owned(), "-".to_owned()],
+            vec![],
+            no_file,
+            io::empty()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_load_exclusions() {
+        let mut exclude_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(exclude_file, "yay\npacman-mirrorup").unwrap();
+
+        let exclusions = load_exclusions(
+            &["networkd-broker".to_owned(), "yay".to_owned()],
+            Some(exclude_file.path()),
+        )
+        .unwrap();
+        assert_eq!(exclusions.len(), 3);
+        assert!(exclusions.contains("yay"));
+        assert!(exclusions.contains("pacman-mirrorup"));
+        assert!(exclusions.contains("networkd-broker"));
+
+        let exclusions = load_exclusions::<&Path>(&[], None).unwrap();
+        assert!(exclusions.is_empty());
+    }
+
     #[test]
     fn test_version_compare() {
         assert_eq!(
@@ -221,4 +738,69 @@ mod tests {
         assert!(pkgs.contains_key("systemd"));
         assert!(pkgs.contains_key("systemd-libs"));
     }
+
+    #[test]
+    fn test_parse_installed_pkgs_repo() {
+        let lines = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/",
+            "test-installed-pkgs-from-a-repo.raw"
+        ));
+        let pkgs = parse_installed_pkgs_repo(lines);
+        assert_eq!(pkgs.len(), 50);
+        assert_eq!(pkgs.get("acpi"), Some(&"1.7-2".to_owned()));
+
+        let lines = "core linux 5.16.8.arch1-1 [installed]\n\
+                     core linux-lts 5.15.22-1\n\
+                     core pacman 6.0.1-3 [installed: 6.0.1-2]\n\
+                     \n";
+        assert_eq!(
+            parse_installed_pkgs_repo(lines),
+            HashMap::from([("linux".to_owned(), "5.16.8.arch1-1".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_find_program() {
+        assert!(find_program("sh").is_some());
+        assert_eq!(find_program("/bin/sh"), Some(PathBuf::from("/bin/sh")));
+        assert_eq!(find_program("aur-thumbsup-no-such-program"), None);
+        assert_eq!(find_program("/nonexistent/pacman"), None);
+        assert_eq!(
+            missing_programs(&["sh", "aur-thumbsup-no-such-program"]),
+            vec!["aur-thumbsup-no-such-program"]
+        );
+
+        let err = command("aur-thumbsup-no-such-program").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`aur-thumbsup-no-such-program` not found, \
+             aur-thumbsup requires an Arch-based system for this command"
+        );
+    }
+
+    #[test]
+    fn test_wait_with_timeout() {
+        let mut child = Command::new("/bin/echo")
+            .arg("pacman 6.0.1-4")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let output = wait_with_timeout(&mut child, stdout, "echo", Duration::from_secs(5)).unwrap();
+        assert_eq!(output, b"pacman 6.0.1-4\n");
+
+        let mut child = Command::new("/bin/sleep")
+            .arg("10")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let err =
+            wait_with_timeout(&mut child, stdout, "sleep", Duration::from_millis(100)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`sleep` is busy, timed out after 100ms (database locked?)"
+        );
+    }
 }
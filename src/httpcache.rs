@@ -0,0 +1,116 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, DirBuilder},
+    io::Write,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+};
+
+/// On-disk cache of responses, revalidated by `ETag`/`Last-Modified`, one file per URL
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+/// Cached response of URL with its validators
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl HttpCache {
+    /// Cache in `dir`, created if not exist. Pages may contain private data, so it is 0700.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)
+            .map_err(|err| {
+                anyhow!(
+                    "Unable to create HTTP cache directory `{}`: {}",
+                    dir.display(),
+                    err
+                )
+            })?;
+        Ok(HttpCache { dir })
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(url.as_bytes())))
+    }
+
+    /// Cached response of `url`, `None` if not cached or cache file is unreadable
+    pub fn load(&self, url: &str) -> Option<CacheEntry> {
+        let content = fs::read_to_string(self.path(url)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        // Different URL of the same hash
+        if entry.url != url {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Save response which has any validator, other responses cannot be revalidated
+    pub fn store(&self, entry: &CacheEntry) -> Result<()> {
+        if entry.etag.is_none() && entry.last_modified.is_none() {
+            return Ok(());
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(self.path(&entry.url))?;
+        file.write_all(serde_json::to_string(entry)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// FNV-1a hash, stable across runs and Rust versions unlike `DefaultHasher`
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_cache() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(tempdir.path().join("http")).unwrap();
+        let url = "https://aur.archlinux.org/packages/pacman-mirrorup";
+        assert_eq!(cache.load(url), None);
+
+        // Not cached without validators
+        let mut entry = CacheEntry {
+            url: url.to_owned(),
+            etag: None,
+            last_modified: None,
+            body: "<html></html>".to_owned(),
+        };
+        cache.store(&entry).unwrap();
+        assert_eq!(cache.load(url), None);
+
+        entry.etag = Some("\"abc\"".to_owned());
+        cache.store(&entry).unwrap();
+        assert_eq!(cache.load(url), Some(entry));
+        assert_eq!(cache.load("https://aur.archlinux.org/"), None);
+
+        tempdir.close().unwrap();
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
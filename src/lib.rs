@@ -0,0 +1,55 @@
+//! A library to manage voting for favorite AUR packages.
+//!
+//! High-level functions in this crate log in to AUR using an [`Account`](aur::Account),
+//! then perform the operation. The caller must supply a valid `Account`: non-empty user,
+//! password, and a writable cookie file path. A saved session in the cookie file is reused
+//! when it is still valid, otherwise a new session is saved to it after logging in.
+
+use anyhow::Result;
+
+pub mod aur;
+
+pub(crate) mod args;
+mod cli;
+pub(crate) mod cmds;
+pub(crate) mod config;
+pub(crate) mod error;
+pub(crate) mod helper;
+pub(crate) mod httpcache;
+pub(crate) mod progress;
+pub(crate) mod state;
+pub(crate) mod stats;
+pub(crate) mod theme;
+pub(crate) mod timing;
+pub(crate) mod votedcache;
+
+pub use crate::cli::run;
+
+use crate::aur::{Account, AurPackageResults, Authentication, VoteResult};
+
+/// Log in to AUR using `account`
+pub fn login(account: &Account) -> Result<Authentication> {
+    let mut auth = Authentication::new();
+    auth.login(account)?;
+    Ok(auth)
+}
+
+/// Vote for `packages` using `account`
+pub fn vote(account: &Account, packages: &[String]) -> Result<Vec<(String, VoteResult)>> {
+    login(account)?.vote(packages, |_| Ok(()))
+}
+
+/// Unvote `packages` using `account`
+pub fn unvote(account: &Account, packages: &[String]) -> Result<Vec<(String, VoteResult)>> {
+    login(account)?.unvote(packages, |_| Ok(()))
+}
+
+/// Check vote status of `packages` using `account`, `None` if package is not available
+pub fn check_vote(account: &Account, packages: &[String]) -> Result<Vec<(String, Option<bool>)>> {
+    login(account)?.check_vote(packages, |_| Ok(()))
+}
+
+/// List all voted packages of `account`
+pub fn list_voted_pkgs(account: &Account) -> Result<AurPackageResults> {
+    login(account)?.list_voted_pkgs()
+}
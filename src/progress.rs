@@ -0,0 +1,51 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use lazy_static::lazy_static;
+use std::{
+    io::{self, IsTerminal},
+    sync::Mutex,
+};
+
+use crate::args::OutputFormat;
+
+lazy_static! {
+    static ref PROGRESS_BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+}
+
+/// Whether to show progress of packages, only on terminal and not for JSON output
+pub fn enabled(format: OutputFormat) -> bool {
+    format != OutputFormat::Json && io::stdout().is_terminal()
+}
+
+/// Show `done` of `total` packages processed, e.g. as progress hook of `Authentication`.
+/// The bar is removed once all packages are processed.
+pub fn show(done: usize, total: usize) {
+    let mut progress_bar = PROGRESS_BAR.lock().expect("Lock progress bar");
+    let bar = progress_bar.get_or_insert_with(|| new_bar(total));
+    bar.set_length(total as u64);
+    bar.set_position(done as u64);
+    if done >= total {
+        bar.finish_and_clear();
+        *progress_bar = None;
+    }
+}
+
+/// Run `f` with progress bar hidden, so lines printed by `f` are not overdrawn
+pub fn suspend<F: FnOnce() -> R, R>(f: F) -> R {
+    match PROGRESS_BAR.lock().expect("Lock progress bar").as_ref() {
+        Some(bar) => bar.suspend(f),
+        None => f(),
+    }
+}
+
+/// Remove progress bar, if packages are not all processed, e.g. run stopped early
+pub fn clear() {
+    if let Some(bar) = PROGRESS_BAR.lock().expect("Lock progress bar").take() {
+        bar.finish_and_clear();
+    }
+}
+
+fn new_bar(total: usize) -> ProgressBar {
+    let style = ProgressStyle::with_template("{bar:30} {pos}/{len} packages processed")
+        .expect("Progress bar template");
+    ProgressBar::new(total as u64).with_style(style)
+}
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    io::{ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+use crate::{aur::Account, helper::PkgName};
+
+/// State file of batch operation `name` of the account, in cache directory
+pub fn state_file_path(cache_dir: &Path, account: &Account, name: &str) -> PathBuf {
+    cache_dir.join(format!("{}.{}.state", account.user, name))
+}
+
+/// Progress of a batch operation on packages, saved to file to resume after interruption
+#[derive(Default, Deserialize, Serialize, PartialEq, Debug)]
+pub struct BatchState {
+    /// All packages of the batch, in processing order
+    pub packages: Vec<PkgName>,
+
+    /// Processed packages
+    pub done: HashSet<PkgName>,
+}
+
+impl BatchState {
+    pub fn new(packages: Vec<PkgName>) -> Self {
+        BatchState {
+            packages,
+            done: HashSet::new(),
+        }
+    }
+
+    /// Load state from file, `None` if file does not exist
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Option<BatchState>> {
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(anyhow!("{} `{}`", err, &path.as_ref().to_str().unwrap())),
+        };
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut state_file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        state_file.write_all(serde_json::to_string(&self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Remove state file, if exists
+    pub fn clear<P: AsRef<Path>>(path: P) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Packages not yet processed, in processing order
+    pub fn remaining(&self) -> Vec<PkgName> {
+        self.packages
+            .iter()
+            .filter(|pkg| !self.done.contains(*pkg))
+            .map(|pkg| pkg.to_owned())
+            .collect()
+    }
+
+    pub fn mark_done(&mut self, pkg: &str) {
+        self.done.insert(pkg.to_owned());
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.remaining().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_file_path() {
+        let account = Account {
+            user: "foo".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            state_file_path(
+                Path::new("/home/foo/.cache/aur-thumbsup"),
+                &account,
+                "unvote-all"
+            ),
+            PathBuf::from("/home/foo/.cache/aur-thumbsup/foo.unvote-all.state")
+        );
+    }
+
+    #[test]
+    fn test_batch_state() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let state_path = tempdir.path().join("unvote-all.state");
+
+        // No state file
+        assert_eq!(BatchState::load(&state_path).unwrap(), None);
+
+        let mut state = BatchState::new(vec![
+            "pkg1".to_owned(),
+            "pkg2".to_owned(),
+            "pkg3".to_owned(),
+        ]);
+        state.mark_done("pkg2");
+        state.save(&state_path).unwrap();
+
+        // Resume from saved state
+        let mut state = BatchState::load(&state_path).unwrap().unwrap();
+        assert_eq!(state.remaining(), vec!["pkg1", "pkg3"]);
+        assert!(!state.is_completed());
+
+        state.mark_done("pkg1");
+        state.mark_done("pkg3");
+        assert!(state.is_completed());
+
+        BatchState::clear(&state_path).unwrap();
+        assert!(!state_path.exists());
+
+        // Clear non-existing state file
+        assert!(BatchState::clear(&state_path).is_ok());
+        tempdir.close().unwrap();
+    }
+}
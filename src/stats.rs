@@ -0,0 +1,81 @@
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref RETRY_STATS: Mutex<RetryStats> = Mutex::new(RetryStats::default());
+}
+
+/// Outcome of operations which are retried, e.g. cookie login, (un)vote after login again
+#[derive(Serialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RetryStats {
+    /// Succeeded on first attempt
+    pub first_try: usize,
+
+    /// Succeeded after retries
+    pub retried: usize,
+
+    /// Failed even after retries
+    pub failed: usize,
+}
+
+impl RetryStats {
+    fn record(&mut self, attempts: u32, succeeded: bool) {
+        match (succeeded, attempts) {
+            (false, _) => self.failed += 1,
+            (true, 0 | 1) => self.first_try += 1,
+            (true, _) => self.retried += 1,
+        }
+    }
+}
+
+/// Record outcome of an operation after `attempts`, including the first one
+pub fn record(attempts: u32, succeeded: bool) {
+    RETRY_STATS
+        .lock()
+        .expect("Lock retry stats")
+        .record(attempts, succeeded);
+}
+
+/// Outcome of retried operations so far in this run
+pub fn retry_stats() -> RetryStats {
+    *RETRY_STATS.lock().expect("Lock retry stats")
+}
+
+/// Report of retried operations, e.g. for bug reports about network reliability
+pub fn report(stats: &RetryStats) -> String {
+    format!(
+        "Retries: {} on first try, {} after retries, {} failed",
+        stats.first_try, stats.retried, stats.failed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut stats = RetryStats::default();
+        stats.record(1, true);
+        stats.record(1, true);
+        stats.record(3, true);
+        stats.record(2, false);
+        assert_eq!(
+            stats,
+            RetryStats {
+                first_try: 2,
+                retried: 1,
+                failed: 1
+            }
+        );
+        assert_eq!(
+            report(&stats),
+            "Retries: 2 on first try, 1 after retries, 1 failed"
+        );
+        assert_eq!(
+            serde_json::to_string(&stats).unwrap(),
+            r#"{"first_try":2,"retried":1,"failed":1}"#
+        );
+    }
+}
@@ -0,0 +1,357 @@
+use clap::ArgEnum;
+use colored::{Color, ColoredString, Colorize};
+use comfy_table::{
+    presets::{ASCII_FULL, UTF8_FULL},
+    Color as TableColor, Table,
+};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Terminal background that colors of human output are picked for
+#[derive(ArgEnum, Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Light if `COLORFGBG` tells a light background, otherwise dark
+    #[default]
+    Auto,
+
+    Dark,
+
+    Light,
+}
+
+/// When to color human output
+#[derive(ArgEnum, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    /// Only on terminal, unless `NO_COLOR` or `CLICOLOR=0` is set
+    #[default]
+    Auto,
+
+    /// Even if output is not a terminal, e.g. piped to a pager
+    Always,
+
+    Never,
+}
+
+/// Words or symbols for results of vote, unvote, and check
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symbols {
+    /// Words, e.g. `Voted`
+    Off,
+
+    /// Unicode symbols, e.g. `✓`
+    Unicode,
+
+    /// ASCII symbols for terminals without Unicode, e.g. `+`
+    Ascii,
+}
+
+/// Kind of result, shown as a symbol when symbols are on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mark {
+    /// e.g. voted, already voted
+    Good,
+
+    /// e.g. failed, not voted
+    Bad,
+
+    /// e.g. not available
+    Unknown,
+}
+
+impl Symbols {
+    /// `word`, or symbol of `mark` when symbols are on
+    fn show(self, word: &'static str, mark: Mark) -> &'static str {
+        match (self, mark) {
+            (Symbols::Off, _) => word,
+            (Symbols::Unicode, Mark::Good) => "✓",
+            (Symbols::Unicode, Mark::Bad) => "✗",
+            (Symbols::Ascii, Mark::Good) => "+",
+            (Symbols::Ascii, Mark::Bad) => "x",
+            (_, Mark::Unknown) => "?",
+        }
+    }
+}
+
+/// Colors by role
+#[derive(PartialEq, Debug)]
+struct Palette {
+    /// Package name, bold
+    name: Color,
+
+    /// Package version, bold
+    version: Color,
+
+    /// Field label, e.g. `Votes:`
+    label: Color,
+
+    /// Success, e.g. voted, up to date
+    good: Color,
+
+    /// Failure or attention, e.g. failed, orphaned
+    bad: Color,
+
+    /// Warning, e.g. not available, skipped
+    warn: Color,
+}
+
+const DARK: Palette = Palette {
+    name: Color::White,
+    version: Color::BrightGreen,
+    label: Color::Cyan,
+    good: Color::BrightGreen,
+    bad: Color::BrightRed,
+    warn: Color::BrightYellow,
+};
+
+const LIGHT: Palette = Palette {
+    name: Color::Black,
+    version: Color::Green,
+    label: Color::Blue,
+    good: Color::Green,
+    bad: Color::Red,
+    warn: Color::Magenta,
+};
+
+impl Theme {
+    fn palette(&self) -> &'static Palette {
+        match self {
+            Theme::Auto => match is_light_background(env::var("COLORFGBG").ok().as_deref()) {
+                true => &LIGHT,
+                false => &DARK,
+            },
+            Theme::Dark => &DARK,
+            Theme::Light => &LIGHT,
+        }
+    }
+}
+
+/// Check if `COLORFGBG`, e.g. `15;0`, tells a light background: white or a bright color except dark gray
+fn is_light_background(colorfgbg: Option<&str>) -> bool {
+    match colorfgbg
+        .and_then(|value| value.rsplit(';').next())
+        .and_then(|bg| bg.parse::<u8>().ok())
+    {
+        Some(bg) => bg == 7 || (9..=15).contains(&bg),
+        None => false,
+    }
+}
+
+/// Set when to color human output printed afterward
+pub fn set_color(mode: ColorMode) {
+    match mode {
+        ColorMode::Auto => colored::control::unset_override(),
+        ColorMode::Always => colored::control::set_override(true),
+        ColorMode::Never => colored::control::set_override(false),
+    }
+}
+
+impl Symbols {
+    /// Symbols of plain output, Unicode symbols are downgraded to ASCII ones
+    fn plain(self) -> Symbols {
+        match self {
+            Symbols::Unicode => Symbols::Ascii,
+            symbols => symbols,
+        }
+    }
+}
+
+/// Style of human output: palette, symbols, and plain output, passed to `fancy` functions
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Style {
+    palette: &'static Palette,
+    symbols: Symbols,
+
+    /// Pure ASCII output without color, from `--plain`
+    plain: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::new(Theme::Dark, Symbols::Off, false)
+    }
+}
+
+impl Style {
+    /// Style of `theme` and `symbols`. Plain style has no color, no Unicode symbols, dashes,
+    /// or table borders.
+    pub fn new(theme: Theme, symbols: Symbols, plain: bool) -> Style {
+        Style {
+            palette: theme.palette(),
+            symbols: match plain {
+                true => symbols.plain(),
+                false => symbols,
+            },
+            plain,
+        }
+    }
+
+    /// Result shown as `word`, or as symbol of `mark` if symbols are on
+    pub fn mark(&self, word: &'static str, mark: Mark) -> &'static str {
+        self.symbols.show(word, mark)
+    }
+
+    /// Dash between numbers of a range, e.g. `1–25`, or `1-25` in plain output
+    pub fn range_dash(&self) -> &'static str {
+        match self.plain {
+            true => "-",
+            false => "–",
+        }
+    }
+
+    fn paint(&self, text: &str, color: Color) -> ColoredString {
+        match self.plain {
+            true => text.normal(),
+            false => text.color(color),
+        }
+    }
+
+    pub fn name(&self, text: &str) -> ColoredString {
+        match self.plain {
+            true => text.normal(),
+            false => text.color(self.palette.name).bold(),
+        }
+    }
+
+    pub fn version(&self, text: &str) -> ColoredString {
+        match self.plain {
+            true => text.normal(),
+            false => text.color(self.palette.version).bold(),
+        }
+    }
+
+    pub fn label(&self, text: &str) -> ColoredString {
+        self.paint(text, self.palette.label)
+    }
+
+    pub fn good(&self, text: &str) -> ColoredString {
+        self.paint(text, self.palette.good)
+    }
+
+    pub fn bad(&self, text: &str) -> ColoredString {
+        self.paint(text, self.palette.bad)
+    }
+
+    pub fn warn(&self, text: &str) -> ColoredString {
+        self.paint(text, self.palette.warn)
+    }
+
+    /// Color of table cell, same as `good` text
+    pub fn good_cell(&self) -> TableColor {
+        table_color(self.palette.good)
+    }
+
+    /// Color of table cell, same as `bad` text
+    pub fn bad_cell(&self) -> TableColor {
+        table_color(self.palette.bad)
+    }
+
+    /// Color of table cell, same as `warn` text
+    pub fn warn_cell(&self) -> TableColor {
+        table_color(self.palette.warn)
+    }
+
+    /// Table for `--format table`. Without color and with ASCII borders, if plain
+    /// or color is disabled, e.g. output is not a terminal without `--color always`.
+    pub fn table(&self) -> Table {
+        let mut table = Table::new();
+        if !self.plain && colored::control::SHOULD_COLORIZE.should_colorize() {
+            // Colored by `--color always` even if output is not a terminal
+            table.enforce_styling().load_preset(UTF8_FULL);
+        } else {
+            table.force_no_tty().load_preset(ASCII_FULL);
+        }
+        table
+    }
+}
+
+fn table_color(color: Color) -> TableColor {
+    match color {
+        Color::Black => TableColor::Black,
+        Color::Red => TableColor::DarkRed,
+        Color::Green => TableColor::DarkGreen,
+        Color::Yellow => TableColor::DarkYellow,
+        Color::Blue => TableColor::DarkBlue,
+        Color::Magenta => TableColor::DarkMagenta,
+        Color::Cyan => TableColor::DarkCyan,
+        Color::White => TableColor::Grey,
+        Color::BrightBlack => TableColor::DarkGrey,
+        Color::BrightRed => TableColor::Red,
+        Color::BrightGreen => TableColor::Green,
+        Color::BrightYellow => TableColor::Yellow,
+        Color::BrightBlue => TableColor::Blue,
+        Color::BrightMagenta => TableColor::Magenta,
+        Color::BrightCyan => TableColor::Cyan,
+        Color::BrightWhite => TableColor::White,
+        Color::TrueColor { r, g, b } => TableColor::Rgb { r, g, b },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_light_background() {
+        assert!(!is_light_background(None));
+        assert!(!is_light_background(Some("15;0")));
+        assert!(!is_light_background(Some("15;default;8")));
+        assert!(is_light_background(Some("0;15")));
+        assert!(is_light_background(Some("0;default;7")));
+        assert!(!is_light_background(Some("garbage")));
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert_eq!(Symbols::Off.show("Voted", Mark::Good), "Voted");
+        assert_eq!(Symbols::Off.show("N/A", Mark::Unknown), "N/A");
+        assert_eq!(Symbols::Unicode.show("Voted", Mark::Good), "✓");
+        assert_eq!(Symbols::Unicode.show("Failed", Mark::Bad), "✗");
+        assert_eq!(Symbols::Unicode.show("N/A", Mark::Unknown), "?");
+        assert_eq!(Symbols::Ascii.show("Voted", Mark::Good), "+");
+        assert_eq!(Symbols::Ascii.show("Failed", Mark::Bad), "x");
+        assert_eq!(Symbols::Ascii.show("N/A", Mark::Unknown), "?");
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(Symbols::Unicode.plain(), Symbols::Ascii);
+        assert_eq!(Symbols::Ascii.plain(), Symbols::Ascii);
+        assert_eq!(Symbols::Off.plain(), Symbols::Off);
+
+        let style = Style::new(Theme::Dark, Symbols::Unicode, false);
+        assert_eq!(style.mark("Voted", Mark::Good), "✓");
+        assert_eq!(style.range_dash(), "–");
+        assert_eq!(
+            style.name("pkg").to_string(),
+            "pkg".bold().white().to_string()
+        );
+
+        // Plain style downgrades symbols, dash, and table borders, and has no color
+        let style = Style::new(Theme::Dark, Symbols::Unicode, true);
+        assert_eq!(style.mark("Voted", Mark::Good), "+");
+        assert_eq!(style.mark("Failed", Mark::Bad), "x");
+        assert_eq!(style.range_dash(), "-");
+        assert_eq!(style.name("pkg"), "pkg".normal());
+        assert_eq!(style.bad("Failed"), "Failed".normal());
+        let mut table = style.table();
+        table
+            .set_header(vec!["Package", "Votes"])
+            .add_row(vec!["yay", "1"]);
+        assert!(table.to_string().is_ascii());
+    }
+
+    #[test]
+    fn test_palette() {
+        assert_eq!(Theme::Dark.palette(), &DARK);
+        assert_eq!(Theme::Light.palette(), &LIGHT);
+
+        // Dark palette keeps the original colors
+        assert_eq!(
+            "pkg".color(DARK.name).bold().to_string(),
+            "pkg".bold().white().to_string()
+        );
+        assert_eq!(table_color(DARK.bad), TableColor::Red);
+        assert_eq!(table_color(LIGHT.bad), TableColor::DarkRed);
+    }
+}
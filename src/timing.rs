@@ -0,0 +1,82 @@
+use lazy_static::lazy_static;
+use std::{
+    fmt::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    static ref PHASES: Mutex<Phases> = Mutex::new(Phases::new());
+}
+
+struct Phases {
+    last: Instant,
+    elapsed: Vec<(String, Duration)>,
+}
+
+impl Phases {
+    fn new() -> Self {
+        Phases {
+            last: Instant::now(),
+            elapsed: Vec::new(),
+        }
+    }
+}
+
+/// Start recording phases of a command
+pub fn start() {
+    let mut phases = PHASES.lock().expect("Lock phases");
+    *phases = Phases::new();
+}
+
+/// Record elapsed time since previous phase (or start) as `name`
+pub fn phase<S: Into<String>>(name: S) {
+    let mut phases = PHASES.lock().expect("Lock phases");
+    let now = Instant::now();
+    let elapsed = now.duration_since(phases.last);
+    phases.elapsed.push((name.into(), elapsed));
+    phases.last = now;
+}
+
+/// Timing report of a command and its recorded phases
+pub fn report(cmd: &str, total: Duration) -> String {
+    let phases = PHASES.lock().expect("Lock phases");
+    format_report(cmd, total, &phases.elapsed)
+}
+
+fn format_report(cmd: &str, total: Duration, phases: &[(String, Duration)]) -> String {
+    let mut output = format!("Timing: {} {:.3}s", cmd, total.as_secs_f64());
+    let width = phases.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, elapsed) in phases {
+        write!(
+            output,
+            "\n  {:width$}  {:.3}s",
+            name,
+            elapsed.as_secs_f64(),
+            width = width
+        )
+        .expect("Write timing");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let phases = vec![
+            ("login".to_owned(), Duration::from_millis(1200)),
+            ("fetch voted list".to_owned(), Duration::from_millis(3456)),
+        ];
+        let report = format_report("list", Duration::from_millis(4700), &phases);
+        assert_eq!(
+            report,
+            "Timing: list 4.700s\n  login             1.200s\n  fetch voted list  3.456s"
+        );
+
+        let report = format_report("check-config", Duration::from_millis(5), &[]);
+        assert_eq!(report, "Timing: check-config 0.005s");
+    }
+}
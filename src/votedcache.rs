@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, DirBuilder},
+    io::{ErrorKind, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::aur::{Account, AurPackageResults};
+
+/// Voted packages of an account saved to file, used instead of fetching them again
+/// until `ttl` is over. Removed whenever a vote is changed, so it is never stale by own votes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VotedCache {
+    path: PathBuf,
+    ttl: Duration,
+
+    /// Ignore saved packages, fetch them again and save them
+    refresh: bool,
+}
+
+/// Content of cache file
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+struct CachedVotedPkgs {
+    /// Unix timestamp
+    fetched_at: u64,
+
+    packages: AurPackageResults,
+}
+
+impl VotedCache {
+    /// Cache of voted packages of `account` in `cache_dir`
+    pub fn new(cache_dir: &Path, account: &Account, ttl: Duration, refresh: bool) -> Self {
+        VotedCache {
+            path: cache_dir.join(format!("{}.voted.json", account.user)),
+            ttl,
+            refresh,
+        }
+    }
+
+    /// Saved packages, `None` if not saved, expired, unreadable, or to be refreshed
+    pub fn load(&self) -> Option<AurPackageResults> {
+        self.load_at(SystemTime::now())
+    }
+
+    fn load_at(&self, now: SystemTime) -> Option<AurPackageResults> {
+        if self.refresh {
+            return None;
+        }
+        let content = fs::read_to_string(&self.path).ok()?;
+        let cached: CachedVotedPkgs = serde_json::from_str(&content).ok()?;
+        let age = now
+            .duration_since(UNIX_EPOCH + Duration::from_secs(cached.fetched_at))
+            .ok()?;
+        match age < self.ttl {
+            true => Some(cached.packages),
+            false => None,
+        }
+    }
+
+    /// Save packages fetched now. Packages may tell private data, so the file is 0600.
+    pub fn store(&self, packages: &AurPackageResults) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .map_err(|err| {
+                    anyhow!(
+                        "Unable to create cache directory `{}`: {}",
+                        dir.display(),
+                        err
+                    )
+                })?;
+        }
+        let cached = CachedVotedPkgs {
+            fetched_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            packages: packages.to_vec(),
+        };
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&self.path)?;
+        file.write_all(serde_json::to_string(&cached)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Remove cache file, if exists
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(anyhow!(
+                "Unable to remove `{}`: {}",
+                self.path.display(),
+                err
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aur::AurPackageResultItem;
+
+    #[test]
+    fn test_voted_cache() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let account = Account {
+            user: "foo".to_owned(),
+            ..Default::default()
+        };
+        let cache_dir = tempdir.path().join("aur-thumbsup");
+        let cache = VotedCache::new(&cache_dir, &account, Duration::from_secs(3600), false);
+        assert_eq!(cache.path, cache_dir.join("foo.voted.json"));
+        assert_eq!(cache.load(), None);
+
+        let voted_pkgs = vec![AurPackageResultItem {
+            name: "pacman-mirrorup".to_owned(),
+            version: "0.5.0-1".to_owned(),
+            votes: 12,
+            popularity: 0.25,
+            voted: true,
+            maintainer: "bpetlert".to_owned(),
+            ..Default::default()
+        }];
+        cache.store(&voted_pkgs).unwrap();
+        assert_eq!(cache.load(), Some(voted_pkgs.clone()));
+
+        // Expired
+        let later = SystemTime::now() + Duration::from_secs(3600);
+        assert_eq!(cache.load_at(later), None);
+
+        // Refreshed
+        let refresh = VotedCache::new(&cache_dir, &account, Duration::from_secs(3600), true);
+        assert_eq!(refresh.load(), None);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.load(), None);
+        assert!(cache.clear().is_ok());
+    }
+}